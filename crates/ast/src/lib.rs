@@ -1,7 +1,9 @@
 pub mod validation;
 
+use lexer::EscapeError;
 use syntax::{SyntaxElement, SyntaxKind, SyntaxNode, SyntaxToken};
 use smol_str::SmolStr;
+use text_size::TextRange;
 
 #[derive(Debug)]
 pub struct Root(SyntaxNode);
@@ -11,7 +13,6 @@ impl Root {
         if node.kind() == SyntaxKind::Root {
             Some(Self(node))
         } else {
-            println!("False Root. This should never happen");
             None
         }
     }
@@ -28,6 +29,7 @@ pub enum Stmt {
     Record(Record),
     AnonRecord(AnonRecord),
     Struct(Struct),
+    Section(Section),
     Expr(Expr),
     // Type(Type),
 }
@@ -37,16 +39,31 @@ impl Stmt {
         let result = match node.kind() {
             SyntaxKind::ImportStmt      => Self::ImportStmt(ImportStmt(node)),
             SyntaxKind::Accessor        => Self::Accessor(Accessor(node)),
+            SyntaxKind::Record if is_accessor(&node) => Self::Accessor(Accessor(node)),
             SyntaxKind::Record          => Self::Record(Record::cast(node)?),
             SyntaxKind::AnonymousRecord => Self::Record(Record::cast(node)?),
             SyntaxKind::Schema          => Self::Record(Record::cast(node)?),
             SyntaxKind::Struct          => Self::Struct(Struct(node)),
+            SyntaxKind::Section         => Self::Section(Section(node)),
             // SyntaxKind::Type         => Self::Type(Type),
             _                           => Self::Expr(Expr::cast(node)?),
         };
 
         Some(result)
     }
+
+    pub fn text_range(&self) -> TextRange {
+        match self {
+            Self::ImportStmt(s) => trimmed_range(&s.0),
+            Self::Accessor(s) => trimmed_range(&s.0),
+            Self::Record(Record::Mono(s)) => trimmed_range(&s.0),
+            Self::Record(Record::Poly(s)) => trimmed_range(&s.0),
+            Self::AnonRecord(s) => trimmed_range(&s.0),
+            Self::Struct(s) => trimmed_range(&s.0),
+            Self::Section(s) => trimmed_range(&s.0),
+            Self::Expr(e) => e.text_range(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -56,20 +73,52 @@ impl ImportStmt {
     // TODO: This 
 }
 
+/// `#zalgo: @global::zalgo` comes out of the parser as a record named by a
+/// transform, whose body is a path starting at an `@` root.
+fn is_accessor(node: &SyntaxNode) -> bool {
+    let named_by_transform = node.first_child()
+        .is_some_and(|n| n.kind() == SyntaxKind::Transform);
+
+    let mut root = node.children()
+        .find(|n| n.kind() == SyntaxKind::Body)
+        .and_then(|body| body.first_child());
+    while let Some(n) = root.clone().filter(|n| n.kind() == SyntaxKind::InfixExpr) {
+        root = n.first_child();
+    }
+
+    named_by_transform && root.is_some_and(|n| n.kind() == SyntaxKind::Keyword)
+}
+
 #[derive(Debug)]
 pub struct Accessor(SyntaxNode);
 
 impl Accessor {
     pub fn name(&self) -> Option<SyntaxToken> {
         self.0
-            .children_with_tokens()
-            .filter_map(SyntaxElement::into_node)
-            .find(|node| node.kind() == SyntaxKind::Name).unwrap()
+            .descendants()
+            .find(|node| node.kind() == SyntaxKind::Name)?
             .children_with_tokens()
             .filter_map(SyntaxElement::into_token)
             .find(|token| token.kind() == SyntaxKind::Ident)
     }
 
+    /// The tokens naming where the accessor points, like `@`, `global`, `zalgo`.
+    pub fn source(&self) -> Vec<SyntaxToken> {
+        let target = self.0.children()
+            .find(|node| matches!(node.kind(), SyntaxKind::Body | SyntaxKind::Path));
+
+        target.into_iter()
+            .flat_map(|node| node.descendants())
+            .filter(|node| matches!(node.kind(), SyntaxKind::Name | SyntaxKind::Keyword))
+            .flat_map(|node| {
+                node.children_with_tokens()
+                    .filter_map(SyntaxElement::into_token)
+                    .filter(|token| !matches!(token.kind(), SyntaxKind::Whitespace | SyntaxKind::Comment))
+                    .collect::<Vec<SyntaxToken>>()
+            })
+            .collect()
+    }
+
     pub fn path(&self) -> Option<Path> {
         self.0.children_with_tokens()
             .filter_map(SyntaxElement::into_node)
@@ -80,11 +129,16 @@ impl Accessor {
         return self.0
             .children_with_tokens()
             .filter_map(SyntaxElement::into_node)
-            .find(|node| node.kind() == SyntaxKind::Path).unwrap()
-            .children_with_tokens()
+            .find(|node| node.kind() == SyntaxKind::Path)
+            .into_iter()
+            .flat_map(|path| path.children_with_tokens())
             .filter_map(SyntaxElement::into_token)
             .collect::<Vec<SyntaxToken>>()
     }
+
+    pub fn text_range(&self) -> TextRange {
+        trimmed_range(&self.0)
+    }
 }
 
 #[derive(Debug)]
@@ -120,13 +174,28 @@ pub enum Record {
 }
 
 impl Record {
+    pub fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Self::Mono(r) => &r.0,
+            Self::Poly(r) => &r.0,
+        }
+    }
+
+    /// The record's name as written, without a schema's `$`.
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        record_name(self.syntax())
+    }
+
+    pub fn text_range(&self) -> TextRange {
+        trimmed_range(self.syntax())
+    }
+
     pub fn cast(node: SyntaxNode) -> Option<Self> {
         if let Some(body) = node.children_with_tokens()
             .filter_map(SyntaxElement::into_node)
             .find(|n| n.kind() == SyntaxKind::Body) {
                 match body.first_child()?.kind() {
                     SyntaxKind::Struct => {
-                        println!("Polyrecord");
                         return Some(Self::Poly(PolyRec(node)))
                     }
                     // TODO: Wrap this shit
@@ -136,12 +205,13 @@ impl Record {
                     | SyntaxKind::PrefixExpr
                     | SyntaxKind::ConstraintExpr
                     | SyntaxKind::Ref
+                    | SyntaxKind::Schema
+                    | SyntaxKind::Type
+                    | SyntaxKind::List
                     | SyntaxKind::Literal   =>  {
-                        println!("MONORECORD");
                         return Some(Self::Mono(MonoRec(node)))
                     }
                     _ => {
-                        println!("hard fail {:?}", body.kind());
                         return None
                     }
                 }
@@ -149,7 +219,6 @@ impl Record {
             else {
                 // Even if the record is malformed, we need to return
                 // SOMETHING with a body.
-                println!("Returning Malformed MonoRec");
                 return Some(Self::Mono(MonoRec(node)));
             }
         }
@@ -168,16 +237,7 @@ impl MonoRec {
         }
     }
     fn raw_name(&self) -> Option<SyntaxToken> {
-        if let Some(n) = self.0.children_with_tokens()
-            .filter_map(SyntaxElement::into_node)
-            .find(|node| node.kind() == SyntaxKind::Name) 
-        {
-            return n.children_with_tokens()
-                .filter_map(SyntaxElement::into_token)
-                .find(|token| token.kind() == SyntaxKind::Ident);
-        } else {
-            return None;
-        }
+        record_name(&self.0)
     }
 
     pub fn value(&self) -> Option<Expr> {
@@ -192,22 +252,21 @@ impl MonoRec {
     }
 
     pub fn constraint(&self) -> Option<Expr> {
-        self.0.children().find_map(Expr::cast)
+        self.0.children()
+            .find(|node| node.kind() == SyntaxKind::ConstraintExpr)
+            .and_then(Expr::cast)
     }
 
     pub fn is_schema(&self) -> bool {
-        self.0.kind() == SyntaxKind::Schema
+        record_is_schema(&self.0)
     }
 
-    pub fn scope(&self) -> Option<Expr> {
-        if let Some(s) = self.0.children_with_tokens()
-            .filter_map(SyntaxElement::into_node)
-            .find(|node| node.kind() == SyntaxKind::ScopeBlock) {
-                return Expr::cast(s)
-        }
-        else {
-            return None;
-        }
+    pub fn is_optional(&self) -> bool {
+        record_is_optional(&self.0)
+    }
+
+    pub fn scope(&self) -> Option<ScopeBlock> {
+        record_scope(&self.0)
     }
 }
 
@@ -224,16 +283,7 @@ impl PolyRec {
     }
 
     fn raw_name(&self) -> Option<SyntaxToken> {
-        if let Some(n) = self.0.children_with_tokens()
-            .filter_map(SyntaxElement::into_node)
-            .find(|node| node.kind() == SyntaxKind::Name) 
-        {
-            return n.children_with_tokens()
-                .filter_map(SyntaxElement::into_token)
-                .find(|token| token.kind() == SyntaxKind::Ident);
-        } else {
-            return None;
-        }
+        record_name(&self.0)
     }
 
     pub fn value(&self) -> Vec<Record> {
@@ -244,7 +294,6 @@ impl PolyRec {
             if let Some(nn) = n.children_with_tokens()
                 .filter_map(SyntaxElement::into_node)
                 .find(|node| node.kind() == SyntaxKind::Struct) {
-                    println!("thunk");
                     return nn.children_with_tokens()
                         .filter_map(SyntaxElement::into_node)
                         .filter_map(Record::cast)
@@ -258,26 +307,78 @@ impl PolyRec {
         }
     }
 
+    /// Like `value`, but keeping sections.
+    pub fn members(&self) -> Vec<Member> {
+        self.0.children()
+            .find(|node| node.kind() == SyntaxKind::Body)
+            .and_then(|body| body.children().find_map(Struct::cast))
+            .map_or_else(Vec::new, |body| body.members())
+    }
+
     pub fn constraint(&self) -> Option<Expr> {
-        self.0.children().find_map(Expr::cast)
+        self.0.children()
+            .find(|node| node.kind() == SyntaxKind::ConstraintExpr)
+            .and_then(Expr::cast)
     }
 
     pub fn is_schema(&self) -> bool {
-        self.0.kind() == SyntaxKind::Schema
+        record_is_schema(&self.0)
     }
 
-    pub fn scope(&self) -> Option<Expr> {
-        if let Some(s) = self.0.children_with_tokens()
-            .filter_map(SyntaxElement::into_node)
-            .find(|node| node.kind() == SyntaxKind::ScopeBlock) {
-                return Expr::cast(s)
-        }
-        else {
-            return None;
-        }
+    pub fn is_optional(&self) -> bool {
+        record_is_optional(&self.0)
+    }
+
+    pub fn scope(&self) -> Option<ScopeBlock> {
+        record_scope(&self.0)
     }
 }
 
+/// Top-level schemas wrap their name in a `Schema` node, nested ones are the
+/// `Schema` node, so we have to look in both places.
+fn record_name(node: &SyntaxNode) -> Option<SyntaxToken> {
+    let name = node.children()
+        .find(|n| n.kind() == SyntaxKind::Name)
+        .or_else(|| node.children()
+            .find(|n| n.kind() == SyntaxKind::Schema)?
+            .children()
+            .find(|n| n.kind() == SyntaxKind::Name))?;
+
+    name.children_with_tokens()
+        .filter_map(SyntaxElement::into_token)
+        .find(|token| token.kind() == SyntaxKind::Ident)
+}
+
+/// A record's scope block is either written before its colon, or leads off
+/// its body, as in `foo: <; @global::mixins ;> & $schema`.
+fn record_scope(node: &SyntaxNode) -> Option<ScopeBlock> {
+    if let Some(scope) = node.children().find_map(ScopeBlock::cast) {
+        return Some(scope);
+    }
+
+    let mut leftmost = node.children()
+        .find(|n| n.kind() == SyntaxKind::Body)?
+        .first_child();
+    while let Some(n) = leftmost.clone().filter(|n| n.kind() == SyntaxKind::InfixExpr) {
+        leftmost = n.first_child();
+    }
+
+    ScopeBlock::cast(leftmost?)
+}
+
+fn record_is_schema(node: &SyntaxNode) -> bool {
+    node.kind() == SyntaxKind::Schema
+        || node.children().any(|n| n.kind() == SyntaxKind::Schema)
+}
+
+/// An optional record, `name?: ...`, has a `?` in its name.
+fn record_is_optional(node: &SyntaxNode) -> bool {
+    node.children()
+        .filter(|n| n.kind() == SyntaxKind::Name)
+        .flat_map(|n| n.children_with_tokens())
+        .any(|element| element.kind() == SyntaxKind::QMark)
+}
+
 #[derive(Debug)]
 pub enum RecordBody {
     Field(Field),
@@ -297,7 +398,6 @@ impl RecordBody {
             SyntaxKind::ParenExpr   => Self::Expr(Expr::cast(node)?),
             SyntaxKind::Ref         => Self::Expr(Expr::cast(node)?),
             _ => {
-                println!("I AM THE GODHEAD {:?}", node.kind());
                 return None
             }
         };
@@ -310,6 +410,10 @@ impl RecordBody {
 pub struct Struct(SyntaxNode);
 
 impl Struct {
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
     pub fn name(&self) -> Option<SyntaxToken> {
         self.0
             .children_with_tokens()
@@ -319,7 +423,6 @@ impl Struct {
 
     // TODO: Should this be an arena?
     pub fn value(&self) -> Option<Vec<Record>> {
-        println!("{:#?}", self.0.children());
         return Some(
             self.0.children_with_tokens()
             .filter_map(SyntaxElement::into_node)
@@ -328,6 +431,11 @@ impl Struct {
         );
     }
 
+    /// Records and sections, in the order they were written.
+    pub fn members(&self) -> Vec<Member> {
+        members(&self.0)
+    }
+
     pub fn cast(node: SyntaxNode) -> Option<Self> {
         let result = match node.kind() {
             SyntaxKind::Struct => Self(node),
@@ -338,6 +446,90 @@ impl Struct {
     }
 }
 
+#[derive(Debug)]
+pub enum Member {
+    Record(Record),
+    Section(Section),
+}
+
+fn members(node: &SyntaxNode) -> Vec<Member> {
+    node.children()
+        .filter_map(|node| match node.kind() {
+            SyntaxKind::Section => Some(Member::Section(Section(node))),
+            _ => Record::cast(node).map(Member::Record),
+        })
+        .collect()
+}
+
+/// `--- #name:` followed by records, which are still fields of the
+/// surrounding struct.
+#[derive(Debug)]
+pub struct Section(SyntaxNode);
+
+impl Section {
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
+    pub fn name(&self) -> Option<SmolStr> {
+        self.name_token().map(|token| token.text().into())
+    }
+
+    pub fn name_token(&self) -> Option<SyntaxToken> {
+        self.0.children()
+            .find(|node| node.kind() == SyntaxKind::Name)?
+            .descendants_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| token.kind() == SyntaxKind::Ident)
+    }
+
+    pub fn text_range(&self) -> TextRange {
+        trimmed_range(&self.0)
+    }
+
+    pub fn scope(&self) -> Option<ScopeBlock> {
+        self.0.children().find_map(ScopeBlock::cast)
+    }
+
+    pub fn members(&self) -> Vec<Member> {
+        members(&self.0)
+    }
+}
+
+/// `<; @global::mixins ;>`, which brings another namespace into scope.
+#[derive(Debug)]
+pub struct ScopeBlock(SyntaxNode);
+
+impl ScopeBlock {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        if node.kind() == SyntaxKind::ScopeBlock {
+            Some(Self(node))
+        } else {
+            None
+        }
+    }
+
+    /// The tokens of each path in the block, like `@`, `global`, `mixins`.
+    pub fn sources(&self) -> Vec<Vec<SyntaxToken>> {
+        self.0.descendants()
+            .find(|node| node.kind() == SyntaxKind::List)
+            .into_iter()
+            .flat_map(|list| list.children())
+            .map(|path| {
+                path.descendants_with_tokens()
+                    .filter_map(SyntaxElement::into_token)
+                    .filter(|token| matches!(token.kind(), SyntaxKind::At | SyntaxKind::Ident))
+                    .collect::<Vec<SyntaxToken>>()
+            })
+            .filter(|tokens| !tokens.is_empty())
+            .collect()
+    }
+
+    pub fn text_range(&self) -> TextRange {
+        trimmed_range(&self.0)
+    }
+}
+
 #[derive(Debug)]
 pub struct Field(SyntaxNode);
 
@@ -371,6 +563,10 @@ pub enum Expr {
     ParenExpr(ParenExpr),
     UnaryExpr(UnaryExpr),
     Ref(Ref),
+    Struct(Struct),
+    ScopeBlock(ScopeBlock),
+    TypePrimitive(TypePrimitive),
+    Transform(Transform),
 }
 
 impl Expr {
@@ -379,20 +575,56 @@ impl Expr {
             SyntaxKind::InfixExpr      => Self::BinaryExpr(BinaryExpr(node)),
             SyntaxKind::Literal        => Self::Literal(Literal(node)),
             SyntaxKind::ParenExpr      => Self::ParenExpr(ParenExpr(node)),
-            SyntaxKind::PrefixExpr     => Self::UnaryExpr(UnaryExpr(node)),
+            SyntaxKind::PrefixExpr     => match Transform::cast(node.clone()) {
+                Some(transform) => Self::Transform(transform),
+                None => Self::UnaryExpr(UnaryExpr(node)),
+            },
             SyntaxKind::Ref            => Self::Ref(Ref(node)),
-            SyntaxKind::Type           => Self::Ref(Ref(node)),
+            SyntaxKind::Schema         => Self::Ref(Ref(node)),
+            SyntaxKind::Struct         => Self::Struct(Struct(node)),
+            SyntaxKind::Type           => Self::TypePrimitive(TypePrimitive::cast(node)?),
             SyntaxKind::Transform      => Self::Ref(Ref(node)),
-            SyntaxKind::ConstraintExpr => Self::cast(node.first_child().unwrap())?,
+            SyntaxKind::ConstraintExpr => Self::cast(node.first_child()?)?,
             SyntaxKind::List           => Self::List(List(node)),
+            SyntaxKind::ScopeBlock     => Self::ScopeBlock(ScopeBlock(node)),
             _ => {
-                println!("EXPR CAST FAIL {:?}", node.kind());
                 return None;
             }
         };
 
         Some(result)
     }
+
+    pub fn syntax(&self) -> &SyntaxNode {
+        match self {
+            Self::BinaryExpr(e) => &e.0,
+            Self::Literal(e) => &e.0,
+            Self::List(e) => &e.0,
+            Self::ParenExpr(e) => &e.0,
+            Self::UnaryExpr(e) => &e.0,
+            Self::Ref(e) => &e.0,
+            Self::Struct(e) => &e.0,
+            Self::ScopeBlock(e) => &e.0,
+            Self::TypePrimitive(e) => &e.0,
+            Self::Transform(e) => &e.0,
+        }
+    }
+
+    pub fn text_range(&self) -> TextRange {
+        trimmed_range(self.syntax())
+    }
+}
+
+/// The node's range without the whitespace and comments it swallowed at the end.
+fn trimmed_range(node: &SyntaxNode) -> TextRange {
+    let end = node
+        .descendants_with_tokens()
+        .filter_map(SyntaxElement::into_token)
+        .filter(|token| !matches!(token.kind(), SyntaxKind::Whitespace | SyntaxKind::Comment))
+        .last()
+        .map_or(node.text_range().end(), |token| token.text_range().end());
+
+    TextRange::new(node.text_range().start(), end)
 }
 
 #[derive(Debug)]
@@ -408,19 +640,23 @@ impl BinaryExpr {
     }
 
     pub fn op(&self) -> Option<SyntaxToken> {
-        println!("Binary Ops:{:?}",
-            self.0
-                .children_with_tokens()
-                .filter_map(SyntaxElement::into_token)
-                .collect::<Vec<SyntaxToken>>()
-                // .nth(1)
-        );
-
         self.0
             .children_with_tokens()
             .filter_map(SyntaxElement::into_token)
             .nth(0)
     }
+
+    /// Whether this is a `..=` range, which includes its upper bound.
+    pub fn is_inclusive(&self) -> bool {
+        self.0
+            .children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .any(|token| token.kind() == SyntaxKind::Equals)
+    }
+
+    pub fn text_range(&self) -> TextRange {
+        trimmed_range(&self.0)
+    }
 }
 
 #[derive(Debug)]
@@ -435,10 +671,13 @@ impl List {
         }
     }
 
+    /// Every entry, row by row.
     pub fn items(&self) -> Vec<Expr> {
-        self.0.children_with_tokens()
-            .filter_map(SyntaxElement::into_node)
-            .filter_map(Expr::cast)
+        self.0.children()
+            .filter(|node| node.kind() == SyntaxKind::Row)
+            .flat_map(|row| row.children())
+            .filter(|node| node.kind() == SyntaxKind::Entry)
+            .filter_map(|entry| entry.children().find_map(Expr::cast))
             .collect()
     }
 
@@ -448,6 +687,20 @@ impl List {
 #[derive(Debug)]
 pub struct Literal(SyntaxNode);
 
+/// The value of a number literal. Ints are decoded from decimal, `0x`, `0o`
+/// and `0b`, and have no sign of their own, since `-` is an operator.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(u64),
+    Float(f64),
+    /// `1v3`, which is offset 3 in segment 1.
+    Address { segment: u64, offset: u64 },
+    /// `2d6`, two six-sided dice added up.
+    RandInt { count: u64, sides: u64 },
+    /// `0.8d1.2`, a float from `0.8` up to `1.2`.
+    RandFloat { low: f64, high: f64 },
+}
+
 impl Literal {
     pub fn cast(node: SyntaxNode) -> Option<Self> {
         if node.kind() == SyntaxKind::Literal {
@@ -461,8 +714,77 @@ impl Literal {
         self.0.first_token().unwrap().text().to_string()
     }
 
-    pub fn parse(&self) -> Option<u64> {
-        self.0.first_token().unwrap().text().parse().ok()
+    /// Returns the value of a number literal, however it was written, or
+    /// `None` if it isn't one or doesn't fit.
+    pub fn number(&self) -> Option<Number> {
+        let text = self.raw().replace('_', "");
+        let int = |digits: &str, radix| u64::from_str_radix(digits, radix).ok();
+
+        match self.kind() {
+            SyntaxKind::Number => int(&text, 10).map(Number::Int),
+            SyntaxKind::BinaryNumber => int(&text[2..], 2).map(Number::Int),
+            SyntaxKind::OctalNumber => int(&text[2..], 8).map(Number::Int),
+            SyntaxKind::HexNumber => int(&text[2..], 16).map(Number::Int),
+            SyntaxKind::FloatLiteral => text.parse().ok().filter(|f: &f64| f.is_finite()).map(Number::Float),
+            SyntaxKind::AddressLiteral => {
+                let (segment, offset) = text.split_once('v')?;
+                Some(Number::Address {
+                    segment: int(segment, 10)?,
+                    offset: int(offset, 10)?,
+                })
+            }
+            SyntaxKind::RandIntLiteral => {
+                let (count, sides) = text.split_once('d')?;
+                Some(Number::RandInt {
+                    count: int(count, 10)?,
+                    sides: int(sides, 10)?,
+                })
+            }
+            SyntaxKind::RandFloatLiteral => {
+                let (low, high) = text.split_once('d')?;
+                let float = |text: &str| text.parse().ok().filter(|f: &f64| f.is_finite());
+                Some(Number::RandFloat {
+                    low: float(low)?,
+                    high: float(high)?,
+                })
+            }
+            _ => None,
+        }
+    }
+
+    /// Whether this is a literal `number` decodes, which it only fails to
+    /// do when the number doesn't fit.
+    pub fn is_number(&self) -> bool {
+        matches!(
+            self.kind(),
+            SyntaxKind::Number
+                | SyntaxKind::BinaryNumber
+                | SyntaxKind::OctalNumber
+                | SyntaxKind::HexNumber
+                | SyntaxKind::FloatLiteral
+                | SyntaxKind::AddressLiteral
+                | SyntaxKind::RandIntLiteral
+                | SyntaxKind::RandFloatLiteral
+        )
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.0.first_token().unwrap().kind()
+    }
+
+    pub fn text_range(&self) -> TextRange {
+        self.0.first_token().unwrap().text_range()
+    }
+
+    /// Returns the value of a string literal with its escapes decoded, or
+    /// the escapes in it that aren't ones.
+    pub fn string(&self) -> Option<Result<SmolStr, Vec<EscapeError>>> {
+        if self.kind() != SyntaxKind::String {
+            return None;
+        }
+
+        let raw = self.0.first_token().unwrap();
+        Some(lexer::string_value(raw.text(), raw.text_range().start()).map(SmolStr::from))
     }
 }
 
@@ -524,21 +846,57 @@ impl UnaryExpr {
                     token.kind(),
                     SyntaxKind::Minus | SyntaxKind::Octothorpe
                     | SyntaxKind::At | SyntaxKind::DollarSign
+                    | SyntaxKind::Star | SyntaxKind::Slash | SyntaxKind::Carrot
                     | SyntaxKind::RAngleBrack | SyntaxKind::GreaterEqual
                     | SyntaxKind::LAngleBrack | SyntaxKind::LessEqual
+                    | SyntaxKind::DotDot | SyntaxKind::DotDotDot
                 )
             })
     }
+
+    pub fn text_range(&self) -> TextRange {
+        trimmed_range(&self.0)
+    }
 }
 
 #[derive(Debug)]
 pub struct Ref(SyntaxNode);
 
 impl Ref {
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
     pub fn name(&self) -> Option<SyntaxToken> {
         self.0.first_token()
     }
 
+    pub fn is_schema(&self) -> bool {
+        self.0.kind() == SyntaxKind::Schema
+    }
+
+    /// The struct in `$schema { ... }`, which is shorthand for `$schema & { ... }`.
+    pub fn body(&self) -> Option<Struct> {
+        self.0.children().find_map(Struct::cast)
+    }
+
+    /// The referenced name. Schema references keep their `$`, since schemas
+    /// and records don't share a namespace.
+    pub fn text(&self) -> Option<SmolStr> {
+        if self.is_schema() {
+            let ident = self.0.descendants_with_tokens()
+                .filter_map(SyntaxElement::into_token)
+                .find(|token| token.kind() == SyntaxKind::Ident)?;
+            Some(format!("${}", ident.text()).into())
+        } else {
+            self.name().map(|token| token.text().into())
+        }
+    }
+
+    pub fn text_range(&self) -> TextRange {
+        trimmed_range(&self.0)
+    }
+
     pub fn full_name(&self) -> Vec<SyntaxToken> {
         self.0.descendants_with_tokens()
             .filter_map(SyntaxElement::into_node)
@@ -568,6 +926,89 @@ impl Ref {
     }
 }
 
+/// A transform and what's passed to it, like `#TitleCase`, `#clamp(0, 10)`
+/// or `#reduce: a + b`.
+#[derive(Debug)]
+pub struct Transform(SyntaxNode);
+
+impl Transform {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        let is_transform = node.kind() == SyntaxKind::PrefixExpr
+            && node.first_child().is_some_and(|n| n.kind() == SyntaxKind::Transform);
+        is_transform.then(|| Self(node))
+    }
+
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
+    /// The transform's name, without the `#`.
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0.first_child()?
+            .descendants_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| token.kind() == SyntaxKind::Ident)
+    }
+
+    /// The arguments between the parentheses, or the expression after the
+    /// name if there are none.
+    pub fn args(&self) -> Vec<Expr> {
+        match self.0.children().find(|n| n.kind() == SyntaxKind::FuncArgs) {
+            Some(args) => args.children().filter_map(Expr::cast).collect(),
+            None => self.0.children().skip(1).filter_map(Expr::cast).collect(),
+        }
+    }
+
+    pub fn text_range(&self) -> TextRange {
+        trimmed_range(&self.0)
+    }
+}
+
+/// A built-in type, `!int`, or one built from others between brackets, like
+/// `!scalar[!int & >=0]` or `!vec[3 -> !float]`.
+#[derive(Debug)]
+pub struct TypePrimitive(SyntaxNode);
+
+impl TypePrimitive {
+    pub fn cast(node: SyntaxNode) -> Option<Self> {
+        let is_type = matches!(node.kind(), SyntaxKind::Type | SyntaxKind::TypePrimitive)
+            && node.first_token().map(|token| token.kind()) == Some(SyntaxKind::Bang);
+        is_type.then(|| Self(node))
+    }
+
+    pub fn syntax(&self) -> &SyntaxNode {
+        &self.0
+    }
+
+    /// The type's name, without the `!`.
+    pub fn name(&self) -> Option<SyntaxToken> {
+        self.0.descendants_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .find(|token| token.kind() == SyntaxKind::Ident)
+    }
+
+    /// What's between the brackets.
+    pub fn arg(&self) -> Option<Expr> {
+        let open = self.0.children_with_tokens()
+            .filter_map(SyntaxElement::into_token)
+            .any(|token| token.kind() == SyntaxKind::LBrack);
+        if open {
+            self.0.children().find_map(Expr::cast)
+        } else {
+            None
+        }
+    }
+
+    /// The struct in `!enum{ ... }`.
+    pub fn body(&self) -> Option<Struct> {
+        self.0.children().find_map(Struct::cast)
+    }
+
+    pub fn text_range(&self) -> TextRange {
+        trimmed_range(&self.0)
+    }
+}
+
 #[derive(Debug)]
 pub struct Path(SyntaxNode);
 
@@ -688,6 +1129,17 @@ mod tests {
                         )
                 }
             },
+            crate::Expr::Struct(_) => "{...}".to_string(),
+            crate::Expr::ScopeBlock(_) => "<;...;>".to_string(),
+            crate::Expr::Transform(exp) => format!(
+                "#{}({})",
+                exp.name().unwrap().text(),
+                exp.args().into_iter().map(recurse_expression).collect::<Vec<String>>().join(", ")
+            ),
+            crate::Expr::TypePrimitive(exp) => match exp.arg() {
+                Some(arg) => format!("!{}[{}]", exp.name().unwrap().text(), recurse_expression(arg)),
+                None => format!("!{}", exp.name().unwrap().text()),
+            },
             crate::Expr::List(exp) => {
                 format!("[{}]", 
                     exp.items().into_iter()
@@ -804,7 +1256,7 @@ mod tests {
     fn validate_arrow_expression_list() {
         check_expression_associativity(
             r#"var: [x,y,z] -> $uint"#,
-        r#" ([x, y, z] -> $uint) "#
+        r#"( [x, y, z] -> $uint )"#
         )
     }
 
@@ -812,7 +1264,7 @@ mod tests {
     fn validate_arrow_expression_list_complex() {
         check_expression_associativity(
             r#"var: [4+8, $string, 0..6] -> $uint"#,
-        r#" ([ (4 + 8) , $string,  (0 .. 6) ] -> $uint) "#
+        r#"( [( 4 + 8 ), $string, ( 0 .. 6 )] -> $uint )"#
         )
     }
 
@@ -859,5 +1311,55 @@ mod tests {
             r#" ("elbow" -> #TitleCase) "#)
     }
 
+    fn numbers(input: &str) -> Vec<Option<crate::Number>> {
+        parser::parse(input)
+            .syntax()
+            .descendants()
+            .filter_map(crate::Literal::cast)
+            .map(|literal| literal.number())
+            .collect()
+    }
+
+    #[test]
+    fn decode_numbers() {
+        use crate::Number;
+
+        assert_eq!(
+            numbers("[1_000, 0x1F, 0o17, 0b101, 2.5, 1_0.2_5, 1v3, 2d6, 0.8d1.2]"),
+            vec![
+                Some(Number::Int(1000)),
+                Some(Number::Int(31)),
+                Some(Number::Int(15)),
+                Some(Number::Int(5)),
+                Some(Number::Float(2.5)),
+                Some(Number::Float(10.25)),
+                Some(Number::Address { segment: 1, offset: 3 }),
+                Some(Number::RandInt { count: 2, sides: 6 }),
+                Some(Number::RandFloat { low: 0.8, high: 1.2 }),
+            ]
+        );
+        assert_eq!(numbers(r#"[0x1FFFFFFFFFFFFFFFF, "1"]"#), vec![None, None]);
+    }
+
+    #[test]
+    fn transform_names_and_args() {
+        let transforms = parser::parse("a: \"elbow\" -> #replace(\"l\", \"L\")\nb: #clamp(-3, 0, 10)\nc: #round 2.5\nd: x -> #TitleCase")
+            .syntax()
+            .descendants()
+            .filter_map(crate::Transform::cast)
+            .map(|transform| (transform.name().unwrap().text().to_string(), transform.args().len()))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            transforms,
+            vec![
+                (String::from("replace"), 2),
+                (String::from("clamp"), 3),
+                (String::from("round"), 1),
+                (String::from("TitleCase"), 0),
+            ]
+        );
+    }
+
 }
 
@@ -1,4 +1,6 @@
 use crate::Literal;
+use diagnostics::Diagnostic;
+use lexer::EscapeError;
 use std::fmt;
 use syntax::SyntaxNode;
 use text_size::TextRange;
@@ -21,9 +23,24 @@ impl fmt::Display for ValidationError {
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+impl From<&ValidationError> for Diagnostic {
+    fn from(error: &ValidationError) -> Self {
+        let diagnostic = Diagnostic::error(error.kind.to_string());
+        match &error.kind {
+            ValidationErrorKind::NumberLiteralTooLarge => diagnostic
+                .with_primary(error.range, "doesn't fit in an integer")
+                .with_note("numbers that don't need to be exact can be written as floats, ending in `.0`"),
+            ValidationErrorKind::InvalidEscape(_) => diagnostic
+                .with_primary(error.range, "invalid escape")
+                .with_note(r#"strings can be written raw, like `r"C:\path"`, to leave backslashes alone"#),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
 enum ValidationErrorKind {
     NumberLiteralTooLarge,
+    InvalidEscape(EscapeError),
 }
 
 impl fmt::Display for ValidationErrorKind {
@@ -34,6 +51,7 @@ impl fmt::Display for ValidationErrorKind {
                 "number literal is larger than an integers maximum value, {}",
                 u64::MAX,
             ),
+            Self::InvalidEscape(error) => write!(f, "{}", error),
         }
     }
 }
@@ -50,11 +68,21 @@ pub fn validate(node: &SyntaxNode) -> Vec<ValidationError> {
     errors
 }
 
+/// Numbers are checked for fitting, and strings for escapes that aren't
+/// ones.
 fn validate_literal(literal: Literal, errors: &mut Vec<ValidationError>) {
-    if literal.parse().is_none() {
+    if let Some(Err(escapes)) = literal.string() {
+        errors.extend(escapes.into_iter().map(|escape| ValidationError {
+            range: escape.range(),
+            kind: ValidationErrorKind::InvalidEscape(escape),
+        }));
+        return;
+    }
+
+    if literal.is_number() && literal.number().is_none() {
         errors.push(ValidationError {
             kind: ValidationErrorKind::NumberLiteralTooLarge,
-            range: literal.0.first_token().unwrap().text_range(),
+            range: literal.text_range(),
         });
     }
 }
@@ -70,7 +98,7 @@ mod tests {
         let expected_errors: Vec<_> = expected_errors
             .iter()
             .map(|(kind, range)| ValidationError {
-                kind: *kind,
+                kind: kind.clone(),
                 range: {
                     let start = range.start.into();
                     let end = range.end.into();
@@ -96,6 +124,44 @@ mod tests {
         );
     }
 
+    #[test]
+    fn validate_other_literals() {
+        check("a: \"jimbo\"\nb: 1.5", &[]);
+    }
+
+    #[test]
+    fn validate_number_forms() {
+        check("a: 1_000\nb: 0xFF\nc: 2v7", &[]);
+        check(
+            "a: 0x10000000000000000",
+            &[(ValidationErrorKind::NumberLiteralTooLarge, (3..22))],
+        );
+    }
+
+    #[test]
+    fn validate_escapes() {
+        check(r#"a: "tab\there \u{e9}""#, &[]);
+        check(
+            r#"a: "C:\path\u{d800}""#,
+            &[
+                (
+                    ValidationErrorKind::InvalidEscape(EscapeError::Unknown {
+                        escape: String::from(r"\p"),
+                        range: TextRange::new(6.into(), 8.into()),
+                    }),
+                    (6..8),
+                ),
+                (
+                    ValidationErrorKind::InvalidEscape(EscapeError::NotAChar {
+                        code: 0xd800,
+                        range: TextRange::new(11.into(), 19.into()),
+                    }),
+                    (11..19),
+                ),
+            ],
+        );
+    }
+
     #[test]
     fn validate_math() {
         println!("Validating Math");
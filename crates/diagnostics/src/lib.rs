@@ -0,0 +1,316 @@
+/*
+TL;DR: One shape for every error the pipeline reports, whether it came from
+parsing, validation, lowering or resolution, and two ways to print it.
+
+    error: expected `:`, but found integer
+     --> units/goblin.vada:2:4
+      |
+    2 | hp 5
+      |    ^ expected `:`
+      |
+      = note: records are written `name: value`
+
+Ranges are byte offsets into the file, like everywhere else. They only become
+lines and columns when rendered, so producers don't need the source text.
+*/
+
+mod line_index;
+pub use line_index::{LineCol, LineIndex};
+
+use serde::Serialize;
+use std::fmt;
+use text_size::TextRange;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A range of the source with something to say about it. The primary label
+/// is where the problem is, secondary ones point at related code.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    pub range: TextRange,
+    pub message: String,
+    pub primary: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub labels: Vec<Label>,
+    pub notes: Vec<String>,
+}
+
+impl Diagnostic {
+    pub fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+            labels: Vec::new(),
+            notes: Vec::new(),
+        }
+    }
+
+    pub fn with_primary(mut self, range: TextRange, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            range,
+            message: message.into(),
+            primary: true,
+        });
+        self
+    }
+
+    pub fn with_label(mut self, range: TextRange, message: impl Into<String>) -> Self {
+        self.labels.push(Label {
+            range,
+            message: message.into(),
+            primary: false,
+        });
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.notes.push(note.into());
+        self
+    }
+
+    pub fn primary(&self) -> Option<&Label> {
+        self.labels.iter().find(|label| label.primary)
+    }
+
+    /// Renders the diagnostic for a terminal, quoting the lines of `text` its
+    /// labels point at.
+    pub fn render(&self, file: &str, text: &str) -> String {
+        let index = LineIndex::new(text);
+        let mut labels = self.labels.iter().collect::<Vec<&Label>>();
+        labels.sort_by_key(|label| (label.range.start(), !label.primary));
+
+        let width = labels
+            .iter()
+            .map(|label| (index.line_col(label.range.start()).line + 1).to_string().len())
+            .max()
+            .unwrap_or(1);
+        let pad = " ".repeat(width);
+
+        let mut out = format!("{}: {}\n", self.severity, self.message);
+        match self.primary().or_else(|| labels.first().copied()) {
+            Some(label) => {
+                let start = index.line_col(label.range.start());
+                out += &format!("{}--> {}:{}:{}\n", pad, file, start.line + 1, start.col + 1);
+            }
+            None => out += &format!("{}--> {}\n", pad, file),
+        }
+
+        if !labels.is_empty() {
+            out += &format!("{} |\n", pad);
+        }
+
+        let mut last_line = None;
+        for label in &labels {
+            let start = index.line_col(label.range.start());
+            let end = index.line_col(label.range.end());
+            let line = index.line(start.line);
+
+            if last_line != Some(start.line) {
+                if last_line.is_some_and(|last| start.line > last + 1) {
+                    out += "...\n";
+                }
+                out += &format!("{:>width$} | {}\n", start.line + 1, line, width = width);
+                last_line = Some(start.line);
+            }
+
+            // Ranges that run onto later lines are underlined to the end of
+            // their first one. Tabs are kept so the carets line up.
+            let end_col = if end.line == start.line {
+                end.col
+            } else {
+                line.chars().count() as u32
+            };
+            let indent = line
+                .chars()
+                .take(start.col as usize)
+                .map(|c| if c == '\t' { '\t' } else { ' ' })
+                .collect::<String>();
+            let marker = if label.primary { "^" } else { "-" };
+            let underline = marker.repeat(end_col.saturating_sub(start.col).max(1) as usize);
+
+            let row = format!("{} | {}{} {}", pad, indent, underline, label.message);
+            out += row.trim_end();
+            out += "\n";
+        }
+
+        if !labels.is_empty() && !self.notes.is_empty() {
+            out += &format!("{} |\n", pad);
+        }
+        for note in &self.notes {
+            out += &format!("{} = note: {}\n", pad, note);
+        }
+
+        out
+    }
+
+    /// The diagnostic as a single line of JSON, for editors and scripts.
+    /// Lines and columns are 1-based, offsets are bytes.
+    pub fn to_json(&self, file: &str, text: &str) -> String {
+        let index = LineIndex::new(text);
+        let position = |offset| {
+            let lc = index.line_col(offset);
+            JsonPosition {
+                offset: offset.into(),
+                line: lc.line + 1,
+                column: lc.col + 1,
+            }
+        };
+
+        let json = JsonDiagnostic {
+            severity: self.severity,
+            message: &self.message,
+            file,
+            labels: self
+                .labels
+                .iter()
+                .map(|label| JsonLabel {
+                    primary: label.primary,
+                    message: &label.message,
+                    start: position(label.range.start()),
+                    end: position(label.range.end()),
+                })
+                .collect(),
+            notes: &self.notes,
+        };
+
+        // Plain structs of strings and numbers always serialize.
+        serde_json::to_string(&json).unwrap()
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+#[derive(Serialize)]
+struct JsonDiagnostic<'a> {
+    severity: Severity,
+    message: &'a str,
+    file: &'a str,
+    labels: Vec<JsonLabel<'a>>,
+    notes: &'a [String],
+}
+
+#[derive(Serialize)]
+struct JsonLabel<'a> {
+    primary: bool,
+    message: &'a str,
+    start: JsonPosition,
+    end: JsonPosition,
+}
+
+#[derive(Serialize)]
+struct JsonPosition {
+    offset: u32,
+    line: u32,
+    column: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn range(start: u32, end: u32) -> TextRange {
+        TextRange::new(start.into(), end.into())
+    }
+
+    #[test]
+    fn render_primary_label() {
+        let text = "a: 1\nhp 5\n";
+        let diagnostic = Diagnostic::error("expected `:`, but found integer")
+            .with_primary(range(8, 9), "expected `:`")
+            .with_note("records are written `name: value`");
+
+        assert_eq!(
+            diagnostic.render("goblin.vada", text),
+            "error: expected `:`, but found integer
+ --> goblin.vada:2:4
+  |
+2 | hp 5
+  |    ^ expected `:`
+  |
+  = note: records are written `name: value`
+"
+        );
+    }
+
+    #[test]
+    fn render_secondary_labels() {
+        let text = "a: 1\na: 2\n\nb: a\n";
+        let diagnostic = Diagnostic::error("`a` is defined more than once")
+            .with_primary(range(14, 15), "ambiguous reference")
+            .with_label(range(0, 4), "defined here")
+            .with_label(range(5, 9), "and here");
+
+        assert_eq!(
+            diagnostic.render("a.vada", text),
+            "error: `a` is defined more than once
+ --> a.vada:4:4
+  |
+1 | a: 1
+  | ---- defined here
+2 | a: 2
+  | ---- and here
+...
+4 | b: a
+  |    ^ ambiguous reference
+"
+        );
+    }
+
+    #[test]
+    fn render_keeps_tabs_and_clips_multiline_ranges() {
+        let text = "a: {\n\tb: [\n1\n]}";
+        let diagnostic = Diagnostic::error("bad list").with_primary(range(9, 16), "here");
+
+        assert_eq!(
+            diagnostic.render("a.vada", text),
+            "error: bad list
+ --> a.vada:2:5
+  |
+2 | \tb: [
+  | \t   ^ here
+"
+        );
+    }
+
+    #[test]
+    fn render_without_labels() {
+        let diagnostic = Diagnostic::error("import cycle: a -> b -> a");
+        assert_eq!(diagnostic.render("a.vada", ""), "error: import cycle: a -> b -> a\n --> a.vada\n");
+    }
+
+    #[test]
+    fn json() {
+        let text = "a: 1\nhp 5";
+        let diagnostic = Diagnostic::error("expected `:`")
+            .with_primary(range(8, 9), "here")
+            .with_note("a note");
+
+        assert_eq!(
+            diagnostic.to_json("goblin.vada", text),
+            r#"{"severity":"error","message":"expected `:`","file":"goblin.vada","labels":[{"primary":true,"message":"here","start":{"offset":8,"line":2,"column":4},"end":{"offset":9,"line":2,"column":5}}],"notes":["a note"]}"#
+        );
+    }
+}
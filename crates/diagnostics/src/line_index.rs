@@ -0,0 +1,117 @@
+use text_size::TextSize;
+
+/// A 0-based line, and a 0-based column counted in characters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LineCol {
+    pub line: u32,
+    pub col: u32,
+}
+
+/// Where each line of a file starts, for turning offsets into lines and
+/// columns.
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    starts: Vec<TextSize>,
+    text: String,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut starts = vec![TextSize::from(0)];
+        starts.extend(
+            text.match_indices('\n')
+                .map(|(idx, _)| TextSize::from(idx as u32 + 1)),
+        );
+
+        Self {
+            starts,
+            text: text.to_string(),
+        }
+    }
+
+    pub fn line_col(&self, offset: TextSize) -> LineCol {
+        let offset = offset.min(TextSize::of(self.text.as_str()));
+        let line = self.starts.partition_point(|start| *start <= offset) - 1;
+        let start = usize::from(self.starts[line]);
+
+        // Offsets inside a character count as that character.
+        let col = self.text[start..]
+            .char_indices()
+            .take_while(|(idx, _)| start + idx < usize::from(offset))
+            .count();
+
+        LineCol {
+            line: line as u32,
+            col: col as u32,
+        }
+    }
+
+    /// The offset of a line and column, the other way around from `line_col`.
+    /// Columns past the end of the line clamp to its end.
+    pub fn offset(&self, line_col: LineCol) -> TextSize {
+        let start = match self.starts.get(line_col.line as usize) {
+            Some(start) => *start,
+            None => return TextSize::of(self.text.as_str()),
+        };
+        let line = self.line(line_col.line);
+        let col = line
+            .char_indices()
+            .nth(line_col.col as usize)
+            .map_or(line.len(), |(idx, _)| idx);
+
+        start + TextSize::from(col as u32)
+    }
+
+    /// The text of a line, without its line ending.
+    pub fn line(&self, line: u32) -> &str {
+        let start = match self.starts.get(line as usize) {
+            Some(start) => usize::from(*start),
+            None => return "",
+        };
+        let end = self
+            .starts
+            .get(line as usize + 1)
+            .map_or(self.text.len(), |end| usize::from(*end));
+
+        self.text[start..end].trim_end_matches(['\n', '\r'])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn line_col() {
+        let index = LineIndex::new("a: 1\nbé: 2\n");
+        let at = |offset: u32| index.line_col(offset.into());
+
+        assert_eq!(at(0), LineCol { line: 0, col: 0 });
+        assert_eq!(at(4), LineCol { line: 0, col: 4 });
+        assert_eq!(at(5), LineCol { line: 1, col: 0 });
+        assert_eq!(at(8), LineCol { line: 1, col: 2 });
+        assert_eq!(at(13), LineCol { line: 2, col: 0 });
+    }
+
+    #[test]
+    fn offset() {
+        let index = LineIndex::new("a: 1\nbé: 2\n");
+        let at = |line, col| u32::from(index.offset(LineCol { line, col }));
+
+        assert_eq!(at(0, 0), 0);
+        assert_eq!(at(1, 2), 8);
+        assert_eq!(at(1, 40), 11);
+        assert_eq!(at(2, 0), 12);
+        assert_eq!(at(9, 0), 12);
+    }
+
+    #[test]
+    fn lines_without_endings() {
+        let text = "a: 1\r\nb: 2";
+        let index = LineIndex::new(text);
+
+        assert_eq!(index.line(0), "a: 1");
+        assert_eq!(index.line(1), "b: 2");
+        assert_eq!(index.line(2), "");
+    }
+}
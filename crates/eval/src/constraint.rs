@@ -0,0 +1,177 @@
+use std::fmt;
+use text_size::TextRange;
+
+/// A bound on a number that hasn't been pinned down yet, like `>=0`.
+#[derive(Debug, Clone)]
+pub struct Constraint {
+    pub bound: Bound,
+    /// Where the bound was written, so violations can point back at it.
+    pub range: Option<TextRange>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Bound {
+    Gr(f64),
+    GrEq(f64),
+    Ls(f64),
+    LsEq(f64),
+    /// `*n`
+    MultipleOf(f64),
+    /// `/n`
+    DivisorOf(f64),
+    /// `^n`
+    PowerOf(f64),
+}
+
+impl Constraint {
+    pub fn new(bound: Bound, range: TextRange) -> Self {
+        Self {
+            bound,
+            range: Some(range),
+        }
+    }
+
+    pub fn allows(&self, n: f64) -> bool {
+        self.bound.allows(n)
+    }
+}
+
+impl From<Bound> for Constraint {
+    fn from(bound: Bound) -> Self {
+        Self { bound, range: None }
+    }
+}
+
+/// The same bound written in two places is still the same constraint.
+impl PartialEq for Constraint {
+    fn eq(&self, other: &Self) -> bool {
+        self.bound == other.bound
+    }
+}
+
+impl Bound {
+    pub fn allows(&self, n: f64) -> bool {
+        match *self {
+            Self::Gr(bound) => n > bound,
+            Self::GrEq(bound) => n >= bound,
+            Self::Ls(bound) => n < bound,
+            Self::LsEq(bound) => n <= bound,
+            Self::MultipleOf(m) => m != 0.0 && (n / m).fract() == 0.0,
+            Self::DivisorOf(d) => n != 0.0 && (d / n).fract() == 0.0,
+            Self::PowerOf(base) => is_power_of(base, n),
+        }
+    }
+}
+
+/// Whether `n` is `base` raised to some non-negative whole exponent.
+fn is_power_of(base: f64, n: f64) -> bool {
+    if n == 1.0 {
+        return true;
+    }
+    if base.abs() <= 1.0 {
+        return n == base;
+    }
+
+    let mut power = base;
+    while power.abs() <= n.abs() {
+        if power == n {
+            return true;
+        }
+        power *= base;
+    }
+    false
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.bound)
+    }
+}
+
+impl fmt::Display for Bound {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Gr(bound) => write!(f, ">{}", bound),
+            Self::GrEq(bound) => write!(f, ">={}", bound),
+            Self::Ls(bound) => write!(f, "<{}", bound),
+            Self::LsEq(bound) => write!(f, "<={}", bound),
+            Self::MultipleOf(m) => write!(f, "*{}", m),
+            Self::DivisorOf(d) => write!(f, "/{}", d),
+            Self::PowerOf(base) => write!(f, "^{}", base),
+        }
+    }
+}
+
+/// Returns a pair of bounds that can never both hold, if there is one.
+pub(crate) fn find_conflict(constraints: &[Constraint]) -> Option<(&Constraint, &Constraint)> {
+    for lower in constraints {
+        for upper in constraints {
+            let disjoint = match (&lower.bound, &upper.bound) {
+                (Bound::GrEq(lo), Bound::LsEq(hi)) => lo > hi,
+                (Bound::Gr(lo), Bound::LsEq(hi))
+                | (Bound::GrEq(lo), Bound::Ls(hi))
+                | (Bound::Gr(lo), Bound::Ls(hi)) => lo >= hi,
+                _ => false,
+            };
+
+            if disjoint {
+                return Some((lower, upper));
+            }
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_constraints() {
+        assert_eq!(Constraint::from(Bound::GrEq(0.0)).to_string(), ">=0");
+        assert_eq!(Constraint::from(Bound::Ls(2.5)).to_string(), "<2.5");
+        assert_eq!(Constraint::from(Bound::MultipleOf(5.0)).to_string(), "*5");
+    }
+
+    #[test]
+    fn overlapping_bounds_do_not_conflict() {
+        assert_eq!(
+            find_conflict(&[Bound::GrEq(5.0).into(), Bound::LsEq(5.0).into()]),
+            None
+        );
+    }
+
+    #[test]
+    fn disjoint_bounds_conflict() {
+        let constraints = [Bound::Gr(5.0).into(), Bound::Ls(5.0).into()];
+        assert_eq!(
+            find_conflict(&constraints),
+            Some((&constraints[0], &constraints[1]))
+        );
+    }
+
+    #[test]
+    fn multiple_of() {
+        assert!(Bound::MultipleOf(5.0).allows(15.0));
+        assert!(Bound::MultipleOf(0.5).allows(1.5));
+        assert!(!Bound::MultipleOf(5.0).allows(12.0));
+        assert!(!Bound::MultipleOf(0.0).allows(0.0));
+    }
+
+    #[test]
+    fn divisor_of() {
+        assert!(Bound::DivisorOf(12.0).allows(4.0));
+        assert!(!Bound::DivisorOf(12.0).allows(5.0));
+        assert!(!Bound::DivisorOf(12.0).allows(0.0));
+    }
+
+    #[test]
+    fn power_of() {
+        assert!(Bound::PowerOf(2.0).allows(1.0));
+        assert!(Bound::PowerOf(2.0).allows(64.0));
+        assert!(!Bound::PowerOf(2.0).allows(48.0));
+        assert!(Bound::PowerOf(-3.0).allows(-27.0));
+        assert!(!Bound::PowerOf(1.0).allows(4.0));
+    }
+}
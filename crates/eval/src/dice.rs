@@ -0,0 +1,127 @@
+/*
+TL;DR: Random literals, rolled the same way every time for the same seed.
+
+    gold: 2d6           → two six-sided dice added up, from 2 to 12
+    scale: 0.8d1.2      → a float from 0.8 up to 1.2
+
+A roll is decided by the seed, the path of the record the literal is written
+in, like `loot.gold` or `drops[2].count`, and how many rolls that record made
+before it. So a record rolls the same however often it's evaluated or
+referred to, and adding records elsewhere doesn't change its rolls. Every
+roll is kept, so a build can be traced back to the rolls that went into it.
+*/
+
+use crate::Value;
+
+/// More dice than this in one literal is almost certainly a mistake, and
+/// would take a while to roll.
+const MAX_DICE: u64 = 10_000;
+
+/// A roll made while evaluating, for reproducing the output later.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Roll {
+    /// Where the record that rolled is declared.
+    pub path: String,
+    /// How many rolls the record made before this one.
+    pub index: usize,
+    /// The literal as it would be written, like `2d6`.
+    pub dice: String,
+    pub value: Value,
+}
+
+/// `count` dice with `sides` sides each, added up.
+pub(crate) fn roll_int(seed: u64, path: &str, index: usize, count: u64, sides: u64) -> Value {
+    if sides == 0 {
+        return Value::bottom(format!("`{}d0` has dice without sides", count));
+    }
+    if count > MAX_DICE {
+        return Value::bottom(format!("can't roll more than {} dice at once", MAX_DICE));
+    }
+    if count.checked_mul(sides).is_none_or(|max| i64::try_from(max).is_err()) {
+        return Value::bottom(format!("`{}d{}` can add up to more than fits in an int", count, sides));
+    }
+
+    let mut rng = Rng::new(seed, path, index);
+    let total = (0..count).map(|_| 1 + rng.below(sides)).sum::<u64>();
+    Value::Int(total as i64)
+}
+
+/// A float from `low` up to, but not including, `high`.
+pub(crate) fn roll_float(seed: u64, path: &str, index: usize, low: f64, high: f64) -> Value {
+    if low > high {
+        return Value::bottom(format!("`{:?}d{:?}` goes from higher to lower", low, high));
+    }
+
+    let mut rng = Rng::new(seed, path, index);
+    Value::Float(low + (high - low) * rng.unit())
+}
+
+/// SplitMix64, started from the seed, the path and the index. It's written
+/// out here rather than taken from a crate so rolls can't change under us
+/// when a dependency does.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64, path: &str, index: usize) -> Self {
+        // FNV-1a over the path, then the index, mixed into the seed.
+        let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+        for byte in path.bytes().chain((index as u64).to_le_bytes()) {
+            hash = (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3);
+        }
+        Self(seed ^ hash)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A number from 0 up to `n`.
+    fn below(&mut self, n: u64) -> u64 {
+        ((u128::from(self.next()) * u128::from(n)) >> 64) as u64
+    }
+
+    /// A float from 0 up to 1.
+    fn unit(&mut self) -> f64 {
+        (self.next() >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rolls_depend_on_seed_path_and_index() {
+        let roll = |seed, path, index| roll_int(seed, path, index, 3, 1_000_000);
+
+        assert_eq!(roll(7, "loot.gold", 0), roll(7, "loot.gold", 0));
+        assert_ne!(roll(7, "loot.gold", 0), roll(8, "loot.gold", 0));
+        assert_ne!(roll(7, "loot.gold", 0), roll(7, "loot.gems", 0));
+        assert_ne!(roll(7, "loot.gold", 0), roll(7, "loot.gold", 1));
+    }
+
+    #[test]
+    fn rolls_stay_in_range() {
+        for seed in 0..200 {
+            match roll_int(seed, "hp", 0, 2, 6) {
+                Value::Int(n) => assert!((2..=12).contains(&n), "2d6 rolled {}", n),
+                value => panic!("2d6 rolled {}", value),
+            }
+            match roll_float(seed, "scale", 0, 0.8, 1.2) {
+                Value::Float(f) => assert!((0.8..1.2).contains(&f), "0.8d1.2 rolled {}", f),
+                value => panic!("0.8d1.2 rolled {}", value),
+            }
+        }
+    }
+
+    #[test]
+    fn rolls_that_cant_be_made() {
+        assert_eq!(roll_int(0, "a", 0, 2, 0), Value::bottom("`2d0` has dice without sides"));
+        assert_eq!(roll_int(0, "a", 0, 20_000, 6), Value::bottom("can't roll more than 10000 dice at once"));
+        assert_eq!(roll_float(0, "a", 0, 2.0, 1.0), Value::bottom("`2.0d1.0` goes from higher to lower"));
+    }
+}
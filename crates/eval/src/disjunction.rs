@@ -0,0 +1,205 @@
+use crate::{unify, Value};
+
+/// One of the values a disjunction could still turn out to be.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alternative {
+    pub value: Value,
+    /// Marked with `*`, so it's picked when a concrete value is needed.
+    pub is_default: bool,
+}
+
+impl Alternative {
+    pub fn new(value: Value, is_default: bool) -> Self {
+        Self { value, is_default }
+    }
+}
+
+/// Builds a disjunction from its live alternatives. Alternatives that have
+/// already failed are dropped, and a single survivor stands on its own.
+pub(crate) fn disjoin(alternatives: Vec<Alternative>) -> Value {
+    let mut live: Vec<Alternative> = Vec::new();
+    let mut reasons = Vec::new();
+
+    for alternative in alternatives {
+        if let Value::Bottom(reason, _) = &alternative.value {
+            reasons.push(reason.clone());
+            continue;
+        }
+
+        match live.iter_mut().find(|a| a.value == alternative.value) {
+            Some(existing) => existing.is_default |= alternative.is_default,
+            None => live.push(alternative),
+        }
+    }
+
+    match live.len() {
+        0 => Value::bottom(format!(
+            "no alternative of the disjunction holds ({})",
+            reasons.join("; ")
+        )),
+        1 => live.pop().unwrap().value,
+        _ => Value::Disjunction(live),
+    }
+}
+
+/// Splits a value into the alternatives it stands for.
+pub(crate) fn alternatives(value: Value) -> Vec<Alternative> {
+    match value {
+        Value::Disjunction(alternatives) => alternatives,
+        value => vec![Alternative::new(value, false)],
+    }
+}
+
+/// Unifies every pairing of alternatives. A side without any marked default
+/// treats all of its alternatives as defaults, so `(*1 | 2) & (1 | 2)` keeps 1
+/// as the default.
+pub(crate) fn unify_disjunction(lhs: Vec<Alternative>, rhs: Vec<Alternative>) -> Value {
+    let lhs_marked = lhs.iter().any(|a| a.is_default);
+    let rhs_marked = rhs.iter().any(|a| a.is_default);
+
+    let mut result = Vec::new();
+    for a in &lhs {
+        for b in &rhs {
+            let is_default = (a.is_default || !lhs_marked) && (b.is_default || !rhs_marked);
+            result.push(Alternative::new(
+                unify(a.value.clone(), b.value.clone()),
+                is_default && (lhs_marked || rhs_marked),
+            ));
+        }
+    }
+
+    disjoin(result)
+}
+
+/// Replaces every disjunction that has exactly one default with that default.
+pub(crate) fn resolve_defaults(value: Value) -> Value {
+    match value {
+        Value::Disjunction(alternatives) => {
+            let mut defaults = alternatives.iter().filter(|a| a.is_default);
+            match (defaults.next(), defaults.next()) {
+                (Some(default), None) => resolve_defaults(default.value.clone()),
+                _ => Value::Disjunction(alternatives),
+            }
+        }
+        Value::List(items) => Value::List(items.into_iter().map(resolve_defaults).collect()),
+        Value::Struct(fields) => Value::Struct(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name, resolve_defaults(value)))
+                .collect(),
+        ),
+        Value::Optional(value) => Value::Optional(Box::new(resolve_defaults(*value))),
+        value => value,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Bound;
+
+    fn string(s: &str) -> Value {
+        Value::String(s.into())
+    }
+
+    #[test]
+    fn disjoin_drops_bottom() {
+        assert_eq!(
+            disjoin(vec![
+                Alternative::new(Value::bottom("nope"), true),
+                Alternative::new(string("B"), false),
+            ]),
+            string("B")
+        );
+    }
+
+    #[test]
+    fn disjoin_merges_duplicates() {
+        assert_eq!(
+            disjoin(vec![
+                Alternative::new(string("A"), false),
+                Alternative::new(string("B"), false),
+                Alternative::new(string("A"), true),
+            ]),
+            Value::Disjunction(vec![
+                Alternative::new(string("A"), true),
+                Alternative::new(string("B"), false),
+            ])
+        );
+    }
+
+    #[test]
+    fn disjoin_nothing_left() {
+        assert_eq!(
+            disjoin(vec![
+                Alternative::new(Value::bottom("a"), false),
+                Alternative::new(Value::bottom("b"), false),
+            ]),
+            Value::bottom("no alternative of the disjunction holds (a; b)")
+        );
+    }
+
+    #[test]
+    fn unify_narrows_alternatives() {
+        let lhs = vec![
+            Alternative::new(Value::Int(1), true),
+            Alternative::new(Value::Int(5), false),
+            Alternative::new(Value::Int(10), false),
+        ];
+        let rhs = alternatives(Value::Constraint(vec![Bound::GrEq(5.0).into()]));
+
+        assert_eq!(
+            unify_disjunction(lhs, rhs),
+            Value::Disjunction(vec![
+                Alternative::new(Value::Int(5), false),
+                Alternative::new(Value::Int(10), false),
+            ])
+        );
+    }
+
+    #[test]
+    fn unify_keeps_defaults_from_marked_side() {
+        let lhs = vec![
+            Alternative::new(Value::Int(1), true),
+            Alternative::new(Value::Int(2), false),
+        ];
+        let rhs = vec![
+            Alternative::new(Value::Int(1), false),
+            Alternative::new(Value::Int(2), false),
+        ];
+
+        assert_eq!(
+            unify_disjunction(lhs, rhs),
+            Value::Disjunction(vec![
+                Alternative::new(Value::Int(1), true),
+                Alternative::new(Value::Int(2), false),
+            ])
+        );
+    }
+
+    #[test]
+    fn resolve_single_default() {
+        let value = Value::Struct(vec![(
+            "kind".into(),
+            Value::Disjunction(vec![
+                Alternative::new(string("A"), false),
+                Alternative::new(string("B"), true),
+            ]),
+        )]);
+
+        assert_eq!(
+            resolve_defaults(value),
+            Value::Struct(vec![("kind".into(), string("B"))])
+        );
+    }
+
+    #[test]
+    fn resolve_without_default_is_unchanged() {
+        let value = Value::Disjunction(vec![
+            Alternative::new(string("A"), false),
+            Alternative::new(string("B"), false),
+        ]);
+
+        assert_eq!(resolve_defaults(value.clone()), value);
+    }
+}
@@ -0,0 +1,206 @@
+/*
+TL;DR: `!enum{ ... }` and the members picked out of it.
+
+    $tags: !enum{             tag: $tags & "A"      → A
+      --- #flags              tag: $tags & "A|C"    → A|C, which is 5
+      A: 1 .> 0               tag: $tags & 3        → A|B
+      B: 1 .> 1               tag: $tags & "AB"     → A|B
+      C: 1 .> 2               tag: $tags & 8        → _|_
+      --- #groups
+      AB: A & B
+    }
+
+A member is picked by its name or its value. Under `#flags`, a value can be
+any combination of flags, written with `|` between them, and the name of a
+range or group stands for all of its members at once; without `#flags`,
+naming one is an error. A member remembers both its name and its value, so
+it can be exported as either.
+*/
+
+use crate::Value;
+use smol_str::SmolStr;
+use std::fmt;
+
+/// An enum's members in declaration order, and the named sets of them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Enum {
+    pub members: Vec<(SmolStr, u64)>,
+    pub sets: Vec<(SmolStr, Vec<SmolStr>)>,
+    /// Whether members are bits that combine.
+    pub flags: bool,
+}
+
+/// One member of an enum or, for flags, a combination of them, named like
+/// `A|C`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Member {
+    pub name: SmolStr,
+    pub value: u64,
+}
+
+impl Enum {
+    /// The member `value` picks out, or why it doesn't pick one.
+    pub fn member(&self, value: &Value) -> Result<Member, String> {
+        let member = match value {
+            Value::String(name) => self.named(name),
+            Value::Int(n) => u64::try_from(*n).ok().and_then(|n| self.numbered(n)),
+            Value::Member(member) => self.numbered(member.value),
+            value => return Err(format!("conflicting kinds enum and {}", value.kind_name())),
+        };
+
+        member.ok_or_else(|| {
+            if let Value::String(name) = value {
+                // A range or group stands for several members, which only
+                // flags can hold at once.
+                if !self.flags && self.sets.iter().any(|(set, _)| set == name) {
+                    return format!("`{}` names several members of {}, so it needs `#flags`", name, self);
+                }
+            }
+            if self.flags {
+                format!("{} isn't a combination of flags from {}", value, self)
+            } else {
+                format!("{} isn't a member of {}", value, self)
+            }
+        })
+    }
+
+    fn named(&self, name: &str) -> Option<Member> {
+        if !self.flags {
+            let (name, value) = self.members.iter().find(|(member, _)| member == name)?;
+            return Some(Member {
+                name: name.clone(),
+                value: *value,
+            });
+        }
+
+        let mut bits = 0;
+        for part in name.split('|') {
+            bits |= self.bits(part.trim())?;
+        }
+        self.numbered(bits)
+    }
+
+    /// The flags of a member, range or group.
+    fn bits(&self, name: &str) -> Option<u64> {
+        let value = |name: &str| self.members.iter().find(|(member, _)| member == name).map(|(_, value)| *value);
+
+        if let Some(value) = value(name) {
+            return Some(value);
+        }
+        let (_, set) = self.sets.iter().find(|(set, _)| set == name)?;
+        Some(set.iter().filter_map(|member| value(member)).fold(0, |bits, value| bits | value))
+    }
+
+    /// The member with this value or, for flags, the members that make it up
+    /// in declaration order.
+    fn numbered(&self, value: u64) -> Option<Member> {
+        if let Some((name, _)) = self.members.iter().find(|(_, n)| *n == value) {
+            return Some(Member {
+                name: name.clone(),
+                value,
+            });
+        }
+        if !self.flags || value == 0 {
+            return None;
+        }
+
+        let mut rest = value;
+        let mut names = Vec::new();
+        for (name, bits) in &self.members {
+            if *bits != 0 && value & bits == *bits && rest & bits != 0 {
+                rest &= !bits;
+                names.push(name.as_str());
+            }
+        }
+        (rest == 0).then(|| Member {
+            name: names.join("|").into(),
+            value,
+        })
+    }
+}
+
+impl Member {
+    /// Whether a plain value names this member or is its value.
+    pub fn is(&self, value: &Value) -> bool {
+        match value {
+            Value::String(name) => *name == self.name,
+            Value::Int(n) => u64::try_from(*n) == Ok(self.value),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Enum {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "!enum{{")?;
+        for (idx, (name, _)) in self.members.iter().enumerate() {
+            if idx > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", name)?;
+        }
+        write!(f, "}}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tags() -> Enum {
+        Enum {
+            members: vec![("A".into(), 1), ("B".into(), 2), ("C".into(), 4)],
+            sets: vec![("AB".into(), vec!["A".into(), "B".into()])],
+            flags: true,
+        }
+    }
+
+    fn member(name: &str, value: u64) -> Result<Member, String> {
+        Ok(Member {
+            name: name.into(),
+            value,
+        })
+    }
+
+    #[test]
+    fn pick_flags() {
+        let tags = tags();
+
+        assert_eq!(tags.member(&Value::String("B".into())), member("B", 2));
+        assert_eq!(tags.member(&Value::String("C | A".into())), member("A|C", 5));
+        assert_eq!(tags.member(&Value::String("AB".into())), member("A|B", 3));
+        assert_eq!(tags.member(&Value::Int(6)), member("B|C", 6));
+        assert_eq!(
+            tags.member(&Value::Int(8)),
+            Err(String::from("8 isn't a combination of flags from !enum{A, B, C}"))
+        );
+        assert_eq!(
+            tags.member(&Value::Float(1.0)),
+            Err(String::from("conflicting kinds enum and float"))
+        );
+    }
+
+    #[test]
+    fn pick_members() {
+        let colors = Enum {
+            members: vec![("Red".into(), 0), ("Green".into(), 1), ("Blue".into(), 2)],
+            sets: vec![("Warm".into(), vec!["Red".into(), "Green".into()])],
+            flags: false,
+        };
+
+        assert_eq!(colors.member(&Value::Int(0)), member("Red", 0));
+        assert_eq!(colors.member(&Value::String("Blue".into())), member("Blue", 2));
+        assert_eq!(
+            colors.member(&Value::Int(3)),
+            Err(String::from("3 isn't a member of !enum{Red, Green, Blue}"))
+        );
+        assert_eq!(
+            colors.member(&Value::String("Red|Green".into())),
+            Err(String::from(r#""Red|Green" isn't a member of !enum{Red, Green, Blue}"#))
+        );
+        assert_eq!(
+            colors.member(&Value::String("Warm".into())),
+            Err(String::from("`Warm` names several members of !enum{Red, Green, Blue}, so it needs `#flags`"))
+        );
+    }
+}
@@ -0,0 +1,594 @@
+use std::ptr;
+
+use crate::dice::{roll_float, roll_int, Roll};
+use crate::disjunction::{alternatives, disjoin, Alternative};
+use crate::transform::Registry;
+use crate::{unify, Bound, Constraint, Enum, Scalar, Value};
+use hir::{visible, BinaryOp, ConstraintOp, Database, ExprIdx, HirExpr, HirRecord, HirStmt, Primitive, UnaryOp};
+use smol_str::SmolStr;
+use text_size::TextRange;
+
+/// A vector longer than this is almost certainly a mistake, and wouldn't fit
+/// in memory anyway.
+const MAX_VEC_LEN: usize = 10_000;
+
+/// Walks the HIR, resolving references against the records that are
+/// lexically visible from the expression being evaluated.
+pub(crate) struct Evaluator<'a> {
+    db: &'a Database,
+    /// Each frame holds the records of one struct body, outermost first.
+    scopes: Vec<Vec<&'a HirRecord>>,
+    /// Where the record each frame is the body of is declared.
+    scope_paths: Vec<String>,
+    /// Records currently being evaluated, used to catch cycles.
+    active: Vec<&'a HirRecord>,
+    /// Whether schemas show up as fields of the structs they're declared in.
+    schemas: bool,
+    /// Where the record being evaluated is declared, like `loot.gold`.
+    path: String,
+    /// How many rolls the record being evaluated has made so far.
+    rolled: usize,
+    seed: u64,
+    /// Every roll made, each once however often its record was evaluated.
+    pub(crate) rolls: Vec<Roll>,
+    transforms: Registry,
+    /// What other modules' records evaluated to, by the names of the
+    /// accessors that import them.
+    imports: &'a [(SmolStr, Value)],
+}
+
+impl<'a> Evaluator<'a> {
+    pub(crate) fn new(db: &'a Database) -> Self {
+        Self {
+            db,
+            scopes: Vec::new(),
+            scope_paths: Vec::new(),
+            active: Vec::new(),
+            schemas: true,
+            path: String::new(),
+            rolled: 0,
+            seed: 0,
+            rolls: Vec::new(),
+            transforms: Registry::std(),
+            imports: &[],
+        }
+    }
+
+    /// An evaluator that rolls random literals with `seed` rather than 0.
+    pub(crate) fn seeded(self, seed: u64) -> Self {
+        Self { seed, ..self }
+    }
+
+    /// An evaluator that can refer to records of other modules through the
+    /// accessors that import them.
+    pub(crate) fn importing(self, imports: &'a [(SmolStr, Value)]) -> Self {
+        Self { imports, ..self }
+    }
+
+    /// An evaluator whose structs leave out the schemas declared in them.
+    /// They can still be referred to, they just aren't data.
+    pub(crate) fn without_schemas(db: &'a Database) -> Self {
+        Self {
+            schemas: false,
+            ..Self::new(db)
+        }
+    }
+
+    pub(crate) fn root(&mut self, stmts: &'a [HirStmt]) -> Value {
+        let records = root_records(stmts);
+
+        self.enter(records.clone());
+        let value = self.fields(&records);
+        self.leave();
+
+        value
+    }
+
+    /// Evaluates the root schema `$name`, if there is one.
+    pub(crate) fn schema(&mut self, stmts: &'a [HirStmt], name: &str) -> Option<Value> {
+        let records = root_records(stmts);
+        let schema = records
+            .iter()
+            .copied()
+            .find(|record| is_schema(record) && record_name(record).is_some_and(|n| n == name))?;
+
+        self.enter(records.clone());
+        let value = self.record_at(schema, name.to_string());
+        self.leave();
+
+        Some(value)
+    }
+
+    fn enter(&mut self, records: Vec<&'a HirRecord>) {
+        self.scopes.push(records);
+        self.scope_paths.push(self.path.clone());
+    }
+
+    fn leave(&mut self) {
+        self.scopes.pop();
+        self.scope_paths.pop();
+    }
+
+    fn fields(&mut self, records: &[&'a HirRecord]) -> Value {
+        let mut fields = Vec::new();
+        for record in records {
+            if !self.schemas && is_schema(record) {
+                continue;
+            }
+            if let Some(name) = record_name(record) {
+                let value = self.record_at(record, field_path(&self.path, name));
+                if is_optional(record) {
+                    fields.push((name.clone(), Value::Optional(Box::new(value))));
+                } else {
+                    fields.push((name.clone(), value));
+                }
+            }
+        }
+        Value::Struct(fields)
+    }
+
+    /// Evaluates a record declared at `path`, which its rolls are made for.
+    fn record_at(&mut self, record: &'a HirRecord, path: String) -> Value {
+        self.at(path, |this| this.record(record))
+    }
+
+    /// Evaluates something with rolls of its own, like a record or a list
+    /// item, at `path`.
+    fn at(&mut self, path: String, eval: impl FnOnce(&mut Self) -> Value) -> Value {
+        let outer = (std::mem::replace(&mut self.path, path), self.rolled);
+        self.rolled = 0;
+        let value = eval(self);
+        (self.path, self.rolled) = outer;
+        value
+    }
+
+    fn record(&mut self, record: &'a HirRecord) -> Value {
+        if self.active.iter().any(|r| ptr::eq(*r, record)) {
+            let name = record_name(record).map_or("<anonymous>", |n| n.as_str());
+            return Value::bottom(format!("cyclic reference to `{}`", name));
+        }
+
+        self.active.push(record);
+        let value = match record {
+            HirRecord::Missing => Value::bottom("missing record"),
+            HirRecord::Section { value, .. } => self.body(value),
+            HirRecord::Mono { value, constraint, .. } => {
+                let value = self.expr(value);
+                self.constrain(constraint, value)
+            }
+            HirRecord::Poly { value, constraint, .. } => {
+                let value = self.body(value);
+                self.constrain(constraint, value)
+            }
+        };
+        self.active.pop();
+
+        value
+    }
+
+    fn body(&mut self, records: &'a [HirRecord]) -> Value {
+        let records = visible(records);
+        self.enter(records.clone());
+        let value = self.fields(&records);
+        self.leave();
+        value
+    }
+
+    /// Unifies a record's value with its constraint, if it has one.
+    fn constrain(&mut self, constraint: &'a Option<HirExpr>, value: Value) -> Value {
+        match constraint {
+            None | Some(HirExpr::Missing) => value,
+            Some(constraint) => {
+                let constraint = self.expr(constraint);
+                unify(constraint, value)
+            }
+        }
+    }
+
+    pub(crate) fn expr(&mut self, expr: &'a HirExpr) -> Value {
+        match expr {
+            HirExpr::Missing => Value::bottom("missing expression"),
+            HirExpr::Int { n } => Value::Int(*n),
+            HirExpr::Float { f } => Value::Float(*f),
+            HirExpr::Address { .. } => Value::bottom("memory addresses can't be evaluated yet"),
+            HirExpr::RandInt { count, sides } => {
+                let value = roll_int(self.seed, &self.path, self.rolled, *count, *sides);
+                self.record_roll(format!("{}d{}", count, sides), value)
+            }
+            HirExpr::RandFloat { low, high } => {
+                let value = roll_float(self.seed, &self.path, self.rolled, *low, *high);
+                self.record_roll(format!("{:?}d{:?}", low, high), value)
+            }
+            HirExpr::String { s } => Value::String(s.clone()),
+            HirExpr::Binary { op: BinaryOp::Dsj, .. } => {
+                let mut collected = Vec::new();
+                self.alternatives(expr, false, &mut collected);
+                disjoin(collected)
+            }
+            HirExpr::Binary { op, lhs, rhs, range } => {
+                let lhs = self.expr(&self.db[*lhs]);
+                let rhs = self.expr(&self.db[*rhs]);
+                binary(op, lhs, rhs, *range)
+            }
+            HirExpr::Unary { op, expr } => {
+                let value = self.expr(&self.db[*expr]);
+                unary(op, value)
+            }
+            HirExpr::Constraint { op, expr, range } => {
+                let value = self.expr(&self.db[*expr]);
+                let bound = match number(value) {
+                    Ok(n) => n,
+                    Err(value) => return value,
+                };
+
+                let bound = match op {
+                    ConstraintOp::Gr => Bound::Gr(bound),
+                    ConstraintOp::GrEq => Bound::GrEq(bound),
+                    ConstraintOp::Ls => Bound::Ls(bound),
+                    ConstraintOp::LsEq => Bound::LsEq(bound),
+                    ConstraintOp::MultipleOf => Bound::MultipleOf(bound),
+                    ConstraintOp::DivisorOf => Bound::DivisorOf(bound),
+                    ConstraintOp::PowerOf => Bound::PowerOf(bound),
+                };
+                Value::Constraint(vec![Constraint::new(bound, *range)])
+            }
+            HirExpr::Range { start, end, inclusive, range } => {
+                self.range(*start, *end, *inclusive, *range)
+            }
+            HirExpr::Ref { var, fields, range } => {
+                let value = self.reference(var, *range);
+                follow(value, var, fields)
+            }
+            // Other files aren't visible from here, so a scope block adds no fields.
+            HirExpr::Scope { .. } => Value::Struct(Vec::new()),
+            HirExpr::Struct { fields } => self.body(fields),
+            // Each item rolls as if it were a record of its own.
+            HirExpr::List { items } => Value::List(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(idx, item)| self.at(format!("{}[{}]", self.path, idx), |this| this.expr(item)))
+                    .collect(),
+            ),
+            HirExpr::Type { primitive, args, range } => self.ty(*primitive, args, *range),
+            HirExpr::Transform { path, name, args, range } => self.transform(path, name, args, *range),
+            HirExpr::Enum(lowered) => Value::Enum(Enum {
+                members: lowered.members.clone(),
+                sets: lowered.sets.clone(),
+                flags: lowered.flags,
+            }),
+        }
+    }
+
+    /// Keeps a roll the record being evaluated just made, unless an earlier
+    /// evaluation of the record already kept it.
+    fn record_roll(&mut self, dice: String, value: Value) -> Value {
+        let index = self.rolled;
+        self.rolled += 1;
+
+        if !self.rolls.iter().any(|roll| roll.path == self.path && roll.index == index) {
+            self.rolls.push(Roll {
+                path: self.path.clone(),
+                index,
+                dice,
+                value: value.clone(),
+            });
+        }
+        value
+    }
+
+    /// A built-in type. `!scalar` is any int, float or string, narrowed by
+    /// what's between its brackets, and `!vec[n -> t]` is a list of `n` `t`s.
+    fn ty(&mut self, primitive: Primitive, args: &[ExprIdx], range: TextRange) -> Value {
+        match (primitive, args) {
+            (Primitive::Int, []) => Value::Type(Scalar::Int, Vec::new()),
+            (Primitive::Float, []) => Value::Type(Scalar::Float, Vec::new()),
+            (Primitive::String, []) => Value::Type(Scalar::String, Vec::new()),
+            (Primitive::Scalar, []) => scalars(),
+            (Primitive::Scalar, [refinement]) => match self.expr(&self.db[*refinement]) {
+                value @ (Value::List(_) | Value::Struct(_)) => Value::bottom(format!(
+                    "`!scalar` is an int, float or string, not a {}",
+                    value.kind_name()
+                )),
+                value => unify(scalars(), value),
+            },
+            (Primitive::Vec, [len, item]) => {
+                let len = match self.expr(&self.db[*len]) {
+                    Value::Int(n) if n > MAX_VEC_LEN as i64 => {
+                        return Value::bottom_at(format!("a vector can't be longer than {}", MAX_VEC_LEN), range)
+                    }
+                    Value::Int(n) if n >= 0 => n as usize,
+                    Value::Bottom(reason, range) => return Value::Bottom(reason, range),
+                    len => {
+                        return Value::bottom_at(
+                            format!("a vector's length has to be a whole number, not {}", len),
+                            range,
+                        )
+                    }
+                };
+                let item = self.expr(&self.db[*item]);
+                Value::List(vec![item; len])
+            }
+            (Primitive::Int | Primitive::Float | Primitive::String, _) => Value::bottom(format!(
+                "`!{}` doesn't take anything between brackets",
+                primitive.name()
+            )),
+            (Primitive::Scalar, _) => Value::bottom("`!scalar` takes one type, like `!scalar[!int & >=0]`"),
+            (Primitive::Vec, _) => Value::bottom("`!vec` takes a length and a type, like `!vec[3 -> !float]`"),
+            (Primitive::Enum, _) => Value::bottom("`!enum` takes its members between braces, like `!enum{ Red: 0 }`"),
+        }
+    }
+
+    /// Looks up a transform and runs it on its arguments, once it's known
+    /// they're what it takes.
+    fn transform(&mut self, path: &[SmolStr], name: &SmolStr, args: &[ExprIdx], range: TextRange) -> Value {
+        // Only the standard library can be named so far.
+        if !(path.is_empty() || path == ["std"]) {
+            return Value::bottom_at(
+                format!("there's no module `{}` to take `#{}` from", path.join("::"), name),
+                range,
+            );
+        }
+        let Some(transform) = self.transforms.get(name) else {
+            return Value::bottom_at(format!("unknown transform `#{}`", name), range);
+        };
+
+        let args = args.iter().map(|arg| self.expr(&self.db[*arg])).collect::<Vec<Value>>();
+        transform.apply(&args, range)
+    }
+
+    /// A range is the pair of bounds on either end, so `0..6` is `>=0 & <6`.
+    fn range(
+        &mut self,
+        start: Option<ExprIdx>,
+        end: Option<ExprIdx>,
+        inclusive: bool,
+        range: TextRange,
+    ) -> Value {
+        let mut constraints = Vec::new();
+
+        if let Some(start) = start {
+            match number(self.expr(&self.db[start])) {
+                Ok(n) => constraints.push(Constraint::new(Bound::GrEq(n), range)),
+                Err(value) => return value,
+            }
+        }
+        if let Some(end) = end {
+            match number(self.expr(&self.db[end])) {
+                Ok(n) if inclusive => constraints.push(Constraint::new(Bound::LsEq(n), range)),
+                Ok(n) => constraints.push(Constraint::new(Bound::Ls(n), range)),
+                Err(value) => return value,
+            }
+        }
+
+        unify(Value::Constraint(Vec::new()), Value::Constraint(constraints))
+    }
+
+    /// Flattens a chain of `|` into its alternatives, marking the ones under `*`.
+    fn alternatives(&mut self, expr: &'a HirExpr, is_default: bool, out: &mut Vec<Alternative>) {
+        match expr {
+            HirExpr::Binary { op: BinaryOp::Dsj, lhs, rhs, .. } => {
+                self.alternatives(&self.db[*lhs], is_default, out);
+                self.alternatives(&self.db[*rhs], is_default, out);
+            }
+            HirExpr::Unary { op: UnaryOp::Default, expr } => {
+                self.alternatives(&self.db[*expr], true, out);
+            }
+            expr => {
+                let value = self.expr(expr);
+                out.extend(alternatives(value).into_iter().map(|mut alternative| {
+                    alternative.is_default |= is_default;
+                    alternative
+                }));
+            }
+        }
+    }
+
+    /// Finds the innermost record named `var`, then evaluates it with only
+    /// the scopes that were visible where it was declared.
+    fn reference(&mut self, var: &SmolStr, range: TextRange) -> Value {
+        let found = self.scopes.iter().enumerate().rev().find_map(|(depth, scope)| {
+            scope
+                .iter()
+                .find(|r| record_key(r).as_ref() == Some(var))
+                .map(|r| (depth, *r))
+        });
+
+        let (depth, record) = match found {
+            Some(found) => found,
+            // Accessors aren't records, so they only count once no record
+            // has the name.
+            None => {
+                return match self.imports.iter().find(|(name, _)| name == var) {
+                    Some((_, value)) => value.clone(),
+                    None => Value::bottom_at(format!("unresolved reference `{}`", var), range),
+                }
+            }
+        };
+
+        let hidden = self.scopes.split_off(depth + 1);
+        let hidden_paths = self.scope_paths.split_off(depth + 1);
+        let path = match record_name(record) {
+            Some(name) => field_path(&self.scope_paths[depth], name),
+            None => self.scope_paths[depth].clone(),
+        };
+        let value = self.record_at(record, path);
+        self.scopes.extend(hidden);
+        self.scope_paths.extend(hidden_paths);
+
+        value
+    }
+}
+
+/// `!scalar`, which could be any of the scalar types.
+fn scalars() -> Value {
+    Value::Disjunction(
+        [Scalar::Int, Scalar::Float, Scalar::String]
+            .into_iter()
+            .map(|scalar| Alternative::new(Value::Type(scalar, Vec::new()), false))
+            .collect(),
+    )
+}
+
+/// The path of the field `name` in the struct at `path`.
+fn field_path(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+/// Steps into the fields of `a.b::c`, one at a time.
+fn follow(mut value: Value, var: &SmolStr, fields: &[SmolStr]) -> Value {
+    let mut path = var.to_string();
+    for field in fields {
+        if value.is_bottom() {
+            return value;
+        }
+        value = match value.field(field) {
+            Some(Value::Optional(value)) => (**value).clone(),
+            Some(value) => value.clone(),
+            None => return Value::bottom(format!("`{}` has no field `{}`", path, field)),
+        };
+        path = format!("{}.{}", path, field);
+    }
+    value
+}
+
+/// The records at the top of a module, with sections flattened into it.
+fn root_records(stmts: &[HirStmt]) -> Vec<&HirRecord> {
+    stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            HirStmt::Record(HirRecord::Section { value, .. }) => Some(visible(value)),
+            HirStmt::Record(record) => Some(vec![record]),
+            _ => None,
+        })
+        .flatten()
+        .collect()
+}
+
+fn record_name(record: &HirRecord) -> Option<&SmolStr> {
+    match record {
+        HirRecord::Mono { name, .. } | HirRecord::Poly { name, .. } => name.as_ref(),
+        HirRecord::Missing | HirRecord::Section { .. } => None,
+    }
+}
+
+fn is_schema(record: &HirRecord) -> bool {
+    matches!(
+        record,
+        HirRecord::Mono { schema: true, .. } | HirRecord::Poly { schema: true, .. }
+    )
+}
+
+fn is_optional(record: &HirRecord) -> bool {
+    matches!(
+        record,
+        HirRecord::Mono { optional: true, .. } | HirRecord::Poly { optional: true, .. }
+    )
+}
+
+/// The name a record is referred to by. Schemas are only visible through `$`.
+fn record_key(record: &HirRecord) -> Option<SmolStr> {
+    match record {
+        HirRecord::Mono { schema: true, name: Some(name), .. }
+        | HirRecord::Poly { schema: true, name: Some(name), .. } => {
+            Some(format!("${}", name).into())
+        }
+        _ => record_name(record).cloned(),
+    }
+}
+
+fn binary(op: &BinaryOp, lhs: Value, rhs: Value, range: TextRange) -> Value {
+    if let BinaryOp::Unify = op {
+        return unify(lhs, rhs);
+    }
+
+    if lhs.is_bottom() {
+        return lhs;
+    }
+    if rhs.is_bottom() {
+        return rhs;
+    }
+
+    let symbol = match op {
+        BinaryOp::Add => "+",
+        BinaryOp::Sub => "-",
+        BinaryOp::Mul => "*",
+        BinaryOp::Div => "/",
+        BinaryOp::Unify | BinaryOp::Dsj => unreachable!(),
+    };
+
+    match (lhs, rhs) {
+        (Value::Int(a), Value::Int(b)) => {
+            let result = match op {
+                BinaryOp::Add => a.checked_add(b),
+                BinaryOp::Sub => a.checked_sub(b),
+                BinaryOp::Mul => a.checked_mul(b),
+                BinaryOp::Div if b == 0 => return Value::bottom_at("division by zero", range),
+                // Integer division only stays an integer when nothing is lost.
+                // `i64::MIN / -1` has no remainder but doesn't fit, so it's
+                // left for the overflow below.
+                BinaryOp::Div if a.checked_rem(b).is_some_and(|rem| rem != 0) => {
+                    return Value::Float(a as f64 / b as f64)
+                }
+                BinaryOp::Div => a.checked_div(b),
+                BinaryOp::Unify | BinaryOp::Dsj => unreachable!(),
+            };
+            result.map_or_else(
+                || Value::bottom_at(format!("integer overflow in `{} {} {}`", a, symbol, b), range),
+                Value::Int,
+            )
+        }
+        (Value::Int(a), Value::Float(b)) => float(op, a as f64, b, range),
+        (Value::Float(a), Value::Int(b)) => float(op, a, b as f64, range),
+        (Value::Float(a), Value::Float(b)) => float(op, a, b, range),
+        (lhs, rhs) => Value::bottom_at(
+            format!("cannot apply `{}` to {} and {}", symbol, lhs.kind_name(), rhs.kind_name()),
+            range,
+        ),
+    }
+}
+
+fn float(op: &BinaryOp, a: f64, b: f64, range: TextRange) -> Value {
+    match op {
+        BinaryOp::Add => Value::Float(a + b),
+        BinaryOp::Sub => Value::Float(a - b),
+        BinaryOp::Mul => Value::Float(a * b),
+        BinaryOp::Div if b == 0.0 => Value::bottom_at("division by zero", range),
+        BinaryOp::Div => Value::Float(a / b),
+        BinaryOp::Unify | BinaryOp::Dsj => unreachable!(),
+    }
+}
+
+fn unary(op: &UnaryOp, value: Value) -> Value {
+    match (op, value) {
+        (_, Value::Bottom(reason, range)) => Value::Bottom(reason, range),
+        // Only meaningful inside a disjunction, where it's picked up before
+        // evaluation gets here.
+        (UnaryOp::Default, value) => value,
+        (UnaryOp::Neg, Value::Int(n)) => n.checked_neg().map_or_else(
+            || Value::bottom(format!("integer overflow in `-{}`", n)),
+            Value::Int,
+        ),
+        (UnaryOp::Neg, Value::Float(n)) => Value::Float(-n),
+        (UnaryOp::Neg, value) => {
+            Value::bottom(format!("cannot negate {}", value.kind_name()))
+        }
+    }
+}
+
+/// The operand of a constraint, which has to be a number to bound anything.
+fn number(value: Value) -> Result<f64, Value> {
+    match value {
+        Value::Int(n) => Ok(n as f64),
+        Value::Float(n) => Ok(n),
+        Value::Bottom(reason, range) => Err(Value::Bottom(reason, range)),
+        value => Err(Value::bottom(format!(
+            "cannot compare against {}",
+            value.kind_name()
+        ))),
+    }
+}
@@ -0,0 +1,592 @@
+mod constraint;
+mod dice;
+mod disjunction;
+mod enumeration;
+mod evaluator;
+mod scalar;
+mod transform;
+mod unify;
+mod validate;
+mod value;
+pub use constraint::{Bound, Constraint};
+pub use dice::Roll;
+pub use disjunction::Alternative;
+pub use enumeration::{Enum, Member};
+pub use scalar::Scalar;
+pub use unify::unify;
+pub use validate::{validate, Step, Violation};
+pub use value::Value;
+
+use evaluator::Evaluator;
+use hir::{Database, HirStmt};
+use smol_str::SmolStr;
+
+/// Evaluates every named root record, producing a struct in declaration order.
+/// Random literals are rolled with a seed of 0.
+pub fn eval(db: &Database, stmts: &[HirStmt]) -> Value {
+    Evaluator::new(db).root(stmts)
+}
+
+/// Like [`eval`], rolling random literals with `seed`, and returning every
+/// roll made. `imports` are what the module's accessors point at, by name.
+pub fn eval_seeded(
+    db: &Database,
+    stmts: &[HirStmt],
+    seed: u64,
+    imports: &[(SmolStr, Value)],
+) -> (Value, Vec<Roll>) {
+    let mut evaluator = Evaluator::new(db).seeded(seed).importing(imports);
+    let value = evaluator.root(stmts);
+    (value, evaluator.rolls)
+}
+
+/// Like [`eval`], but leaves out schemas, which describe data rather than
+/// being any. This is what gets exported.
+pub fn output(db: &Database, stmts: &[HirStmt]) -> Value {
+    Evaluator::without_schemas(db).root(stmts)
+}
+
+/// Like [`output`], rolling random literals with `seed`, and returning every
+/// roll made. `imports` are what the module's accessors point at, by name.
+pub fn output_seeded(
+    db: &Database,
+    stmts: &[HirStmt],
+    seed: u64,
+    imports: &[(SmolStr, Value)],
+) -> (Value, Vec<Roll>) {
+    let mut evaluator = Evaluator::without_schemas(db).seeded(seed).importing(imports);
+    let value = evaluator.root(stmts);
+    (value, evaluator.rolls)
+}
+
+/// Evaluates the root schema `$name`, for checking data against.
+pub fn schema(db: &Database, stmts: &[HirStmt], name: &str) -> Option<Value> {
+    Evaluator::new(db).schema(stmts, name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use text_size::TextRange;
+
+    fn check(input: &str, expected: Value) {
+        let parse = parser::parse(input);
+        let root = ast::Root::cast(parse.syntax()).unwrap();
+        let (db, stmts) = hir::lower(root);
+
+        assert_eq!(eval(&db, &stmts), expected);
+    }
+
+    fn range(start: u32, end: u32) -> TextRange {
+        TextRange::new(start.into(), end.into())
+    }
+
+    fn fields(fields: Vec<(&str, Value)>) -> Value {
+        Value::Struct(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.into(), value))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn eval_literal() {
+        check("a: 1", fields(vec![("a", Value::Int(1))]));
+    }
+
+    #[test]
+    fn eval_number_forms() {
+        check(
+            "a: 0x1F\nb: -2.5\nc: !float & 1.5\nd: 1v3",
+            fields(vec![
+                ("a", Value::Int(31)),
+                ("b", Value::Float(-2.5)),
+                ("c", Value::Float(1.5)),
+                ("d", Value::bottom("memory addresses can't be evaluated yet")),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_transforms() {
+        check(
+            "a: \"elbow grease\" -> #TitleCase\nb: std::#clamp(a -> #len, 0, 3)\nc: [3, 1, 3] -> #unique -> #sort\nd: #round(2.5) -> #abs\ne: \" x,y \" -> #trim -> #split(\",\") -> #join(\"+\")",
+            fields(vec![
+                ("a", Value::String("Elbow Grease".into())),
+                ("b", Value::Int(3)),
+                ("c", Value::List(vec![Value::Int(1), Value::Int(3)])),
+                ("d", Value::Int(3)),
+                ("e", Value::String("x+y".into())),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_transform_errors() {
+        check(
+            "a: 1 -> #nope\nb: 1 -> #clamp(2)\nc: 1 -> some::module::#abs\nd: 1 -> #trim",
+            fields(vec![
+                ("a", Value::bottom_at("unknown transform `#nope`", range(3, 13))),
+                ("b", Value::bottom_at("`#clamp` takes 3 arguments but was given 2", range(17, 31))),
+                ("c", Value::bottom_at("there's no module `some::module` to take `#abs` from", range(35, 58))),
+                ("d", Value::bottom_at("`#trim` takes a string as argument 1, not an int", range(62, 72))),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_string() {
+        check(
+            r#"name: "jimbo""#,
+            fields(vec![("name", Value::String("jimbo".into()))]),
+        );
+    }
+
+    #[test]
+    fn eval_arithmetic_precedence() {
+        check("a: 1 + 2 * 3 - 4", fields(vec![("a", Value::Int(3))]));
+    }
+
+    #[test]
+    fn eval_negation() {
+        check("a: -4 + 1", fields(vec![("a", Value::Int(-3))]));
+    }
+
+    #[test]
+    fn eval_inexact_division() {
+        check("a: 7 / 2", fields(vec![("a", Value::Float(3.5))]));
+    }
+
+    #[test]
+    fn eval_division_by_zero() {
+        check(
+            "a: 7 / (2 - 2)",
+            fields(vec![("a", Value::bottom_at("division by zero", range(3, 14)))]),
+        );
+    }
+
+    #[test]
+    fn eval_division_overflow() {
+        check(
+            "a: (0 - 9223372036854775807 - 1) / (0 - 1)",
+            fields(vec![(
+                "a",
+                Value::bottom_at("integer overflow in `-9223372036854775808 / -1`", range(3, 42)),
+            )]),
+        );
+    }
+
+    #[test]
+    fn eval_struct() {
+        check(
+            "foo: {\n  name: 4\n  age: name * 2\n}",
+            fields(vec![(
+                "foo",
+                fields(vec![("name", Value::Int(4)), ("age", Value::Int(8))]),
+            )]),
+        );
+    }
+
+    #[test]
+    fn eval_reference_to_outer_record() {
+        check(
+            "base: 10\nfoo: {\n  hp: base + 5\n}",
+            fields(vec![
+                ("base", Value::Int(10)),
+                ("foo", fields(vec![("hp", Value::Int(15))])),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_reference_before_declaration() {
+        check(
+            "a: b * 2\nb: 3",
+            fields(vec![("a", Value::Int(6)), ("b", Value::Int(3))]),
+        );
+    }
+
+    #[test]
+    fn eval_unresolved_reference() {
+        check(
+            "a: nope",
+            fields(vec![("a", Value::bottom_at("unresolved reference `nope`", range(3, 7)))]),
+        );
+    }
+
+    #[test]
+    fn eval_cyclic_reference() {
+        check(
+            "a: b\nb: a",
+            fields(vec![
+                ("a", Value::bottom("cyclic reference to `a`")),
+                ("b", Value::bottom("cyclic reference to `b`")),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_unify_schema_with_struct() {
+        check(
+            "$ent: {\n  hp: >=0\n  kind: \"goblin\"\n}\nx: $ent & { hp: 5 }",
+            fields(vec![
+                (
+                    "ent",
+                    fields(vec![
+                        ("hp", Value::Constraint(vec![Bound::GrEq(0.0).into()])),
+                        ("kind", Value::String("goblin".into())),
+                    ]),
+                ),
+                (
+                    "x",
+                    fields(vec![
+                        ("hp", Value::Int(5)),
+                        ("kind", Value::String("goblin".into())),
+                    ]),
+                ),
+            ]),
+        );
+    }
+
+    #[test]
+    fn output_leaves_out_schemas() {
+        let parse = parser::parse("$ent: {\n  hp: >=0\n}\nx: {\n  $tag: \"t\"\n  y: $ent & { hp: 5 }\n}");
+        let root = ast::Root::cast(parse.syntax()).unwrap();
+        let (db, stmts) = hir::lower(root);
+
+        assert_eq!(
+            output(&db, &stmts),
+            fields(vec![("x", fields(vec![("y", fields(vec![("hp", Value::Int(5))]))]))])
+        );
+    }
+
+    #[test]
+    fn schema_by_name() {
+        let parse = parser::parse("$ent: {\n  hp: >=0\n}\nent: 4");
+        let root = ast::Root::cast(parse.syntax()).unwrap();
+        let (db, stmts) = hir::lower(root);
+
+        assert_eq!(
+            schema(&db, &stmts, "ent"),
+            Some(fields(vec![("hp", Value::Constraint(vec![Bound::GrEq(0.0).into()]))]))
+        );
+        assert_eq!(schema(&db, &stmts, "nope"), None);
+    }
+
+    #[test]
+    fn eval_unify_violates_constraint() {
+        check(
+            "$ent: {\n  hp: >=0\n}\nx: $ent & { hp: 0 - 5 }",
+            fields(vec![
+                (
+                    "ent",
+                    fields(vec![("hp", Value::Constraint(vec![Bound::GrEq(0.0).into()]))]),
+                ),
+                (
+                    "x",
+                    fields(vec![("hp", Value::bottom_at("-5 does not satisfy `>=0`", range(14, 17)))]),
+                ),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_unify_conflicting_scalars() {
+        check(
+            "a: 1 & 2",
+            fields(vec![("a", Value::bottom("conflicting values 1 and 2"))]),
+        );
+    }
+
+    #[test]
+    fn eval_unify_narrows_constraints() {
+        check(
+            "a: >=0 & <10 & 4",
+            fields(vec![("a", Value::Int(4))]),
+        );
+    }
+
+    #[test]
+    fn eval_constrained_poly_record() {
+        check(
+            "$a: {\n  hp: >=0\n}\n$b: {\n  hp: <=100\n}\nfoo: $a & $b {\n  hp: 200\n}",
+            fields(vec![
+                (
+                    "a",
+                    fields(vec![("hp", Value::Constraint(vec![Bound::GrEq(0.0).into()]))]),
+                ),
+                (
+                    "b",
+                    fields(vec![("hp", Value::Constraint(vec![Bound::LsEq(100.0).into()]))]),
+                ),
+                (
+                    "foo",
+                    fields(vec![("hp", Value::bottom_at("200 does not satisfy `<=100`", range(30, 35)))]),
+                ),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_type_mismatch() {
+        check(
+            r#"a: "jimbo" + 1"#,
+            fields(vec![(
+                "a",
+                Value::bottom_at("cannot apply `+` to string and int", range(3, 14)),
+            )]),
+        );
+    }
+    #[test]
+    fn eval_string_disjunction() {
+        check(
+            r#"kind: "A" | "B" | "C""#,
+            fields(vec![(
+                "kind",
+                Value::Disjunction(vec![
+                    Alternative::new(Value::String("A".into()), false),
+                    Alternative::new(Value::String("B".into()), false),
+                    Alternative::new(Value::String("C".into()), false),
+                ]),
+            )]),
+        );
+    }
+
+    #[test]
+    fn eval_disjunction_narrowed_by_unification() {
+        check(
+            "$ent: {\n  kind: \"A\" | \"B\"\n}\nx: $ent & { kind: \"B\" }",
+            fields(vec![
+                (
+                    "ent",
+                    fields(vec![(
+                        "kind",
+                        Value::Disjunction(vec![
+                            Alternative::new(Value::String("A".into()), false),
+                            Alternative::new(Value::String("B".into()), false),
+                        ]),
+                    )]),
+                ),
+                ("x", fields(vec![("kind", Value::String("B".into()))])),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_disjunction_with_default() {
+        let parse = parser::parse(r#"kind: *"A" | "B""#);
+        let root = ast::Root::cast(parse.syntax()).unwrap();
+        let (db, stmts) = hir::lower(root);
+        let value = eval(&db, &stmts);
+
+        assert_eq!(value.to_string(), r#"{ kind: *"A" | "B" }"#);
+        assert_eq!(
+            value.resolved(),
+            fields(vec![("kind", Value::String("A".into()))])
+        );
+    }
+
+    #[test]
+    fn eval_optional_fields() {
+        let parse = parser::parse("$ent: {\n  hp: >=0\n  name?: \"A\" | \"B\"\n}\nx: $ent & { hp: 5 }\ny: $ent & { name: \"B\" }\nz: y.name");
+        let root = ast::Root::cast(parse.syntax()).unwrap();
+        let (db, stmts) = hir::lower(root);
+
+        assert_eq!(
+            output(&db, &stmts).to_string(),
+            r#"{ x: { hp: 5, name?: "A" | "B" }, y: { hp: >=0, name: "B" }, z: "B" }"#
+        );
+    }
+
+    #[test]
+    fn eval_disjunction_without_live_alternatives() {
+        check(
+            r#"kind: ("A" | "B") & "C""#,
+            fields(vec![(
+                "kind",
+                Value::bottom(
+                    r#"no alternative of the disjunction holds (conflicting values "A" and "C"; conflicting values "B" and "C")"#,
+                ),
+            )]),
+        );
+    }
+    #[test]
+    fn eval_multiple_of() {
+        check(
+            "a: *5 & 15\nb: *5 & 12",
+            fields(vec![
+                ("a", Value::Int(15)),
+                ("b", Value::bottom_at("12 does not satisfy `*5`", range(14, 16))),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_divisor_and_power_of() {
+        check(
+            "a: /12 & 4\nb: ^2 & 48",
+            fields(vec![
+                ("a", Value::Int(4)),
+                ("b", Value::bottom_at("48 does not satisfy `^2`", range(14, 16))),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_constraint_with_expression_bound() {
+        check(
+            "a: <5+2 & 7",
+            fields(vec![("a", Value::bottom_at("7 does not satisfy `<7`", range(3, 7)))]),
+        );
+    }
+
+    #[test]
+    fn eval_range() {
+        check(
+            "a: 0..6 & 6\nb: 0..=6 & 6",
+            fields(vec![
+                ("a", Value::bottom_at("6 does not satisfy `<6`", range(3, 7))),
+                ("b", Value::Int(6)),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_open_ranges() {
+        check(
+            "a: ..6 & 0 - 10\nb: 3.. & 2\nc: ... & 99",
+            fields(vec![
+                ("a", Value::Int(-10)),
+                ("b", Value::bottom_at("2 does not satisfy `>=3`", range(19, 22))),
+                ("c", Value::Int(99)),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_empty_range() {
+        check(
+            "a: 6..0",
+            fields(vec![(
+                "a",
+                Value::bottom("`>=6` and `<0` can never both hold"),
+            )]),
+        );
+    }
+
+    #[test]
+    fn eval_types() {
+        check(
+            "$hp: !scalar[!int & >=0]\na: $hp & 5\nb: $hp & 0 - 5\nc: !string & 4\nd: !scalar & \"x\"",
+            fields(vec![
+                ("hp", Value::Type(Scalar::Int, vec![Bound::GrEq(0.0).into()])),
+                ("a", Value::Int(5)),
+                ("b", Value::bottom_at("-5 does not satisfy `>=0`", range(20, 23))),
+                ("c", Value::bottom("conflicting kinds string and int")),
+                ("d", Value::String("x".into())),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_vector_type() {
+        check(
+            "pos: !vec[2 -> !float]\na: pos & [1, 2]\nb: pos & [1]\nc: !vec[!int -> !int]\nd: !vec[100000000000 -> !int]",
+            fields(vec![
+                (
+                    "pos",
+                    Value::List(vec![Value::Type(Scalar::Float, Vec::new()), Value::Type(Scalar::Float, Vec::new())]),
+                ),
+                ("a", Value::List(vec![Value::Float(1.0), Value::Float(2.0)])),
+                ("b", Value::bottom("lists of length 2 and 1 can't be unified")),
+                ("c", Value::bottom_at("a vector's length has to be a whole number, not !int", range(55, 73))),
+                ("d", Value::bottom_at("a vector can't be longer than 10000", range(77, 103))),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_enum() {
+        let member = |name: &str, value| Value::Member(Member { name: name.into(), value });
+        check(
+            "$tags: !enum{\n  --- #flags\n  A: 1 .> 0\n  B: 1 .> 1\n  --- #groups\n  AB: A & B\n}\na: $tags & \"B\"\nb: $tags & \"AB\"\nc: $tags & 1 & \"A\"\nd: $tags & 4",
+            fields(vec![
+                (
+                    "tags",
+                    Value::Enum(Enum {
+                        members: vec![("A".into(), 1), ("B".into(), 2)],
+                        sets: vec![("AB".into(), vec!["A".into(), "B".into()])],
+                        flags: true,
+                    }),
+                ),
+                ("a", member("B", 2)),
+                ("b", member("A|B", 3)),
+                ("c", member("A", 1)),
+                ("d", Value::bottom("4 isn't a combination of flags from !enum{A, B}")),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_imports() {
+        let lower = |input: &str| hir::lower(ast::Root::cast(parser::parse(input).syntax()).unwrap());
+
+        let (db, stmts) = lower("tools: {\n  hammer: 3\n  axe: hammer + 1\n}\nlimit: >=0");
+        let zalgo = eval(&db, &stmts);
+        let imports = vec![
+            ("axe".into(), zalgo.field("tools").and_then(|tools| tools.field("axe")).cloned().unwrap()),
+            ("limit".into(), zalgo.field("limit").cloned().unwrap().unlocated()),
+        ];
+
+        let (db, stmts) = lower("#axe: @global::zalgo::tools::axe\n#limit: @global::zalgo::limit\nx: axe * 2\ny: limit & -1");
+        assert_eq!(
+            eval_seeded(&db, &stmts, 0, &imports).0,
+            fields(vec![("x", Value::Int(8)), ("y", Value::bottom("-1 does not satisfy `>=0`"))]),
+        );
+    }
+
+    #[test]
+    fn eval_seeded_rolls() {
+        let input = "gold: 1d1000000\ncopy: gold\ndrops: [1d6, {n: 1d6}]\nboth: 1d100 + 1d100";
+        let root = ast::Root::cast(parser::parse(input).syntax()).unwrap();
+        let (db, stmts) = hir::lower(root);
+
+        let (value, rolls) = eval_seeded(&db, &stmts, 42, &[]);
+        assert_eq!(eval_seeded(&db, &stmts, 42, &[]).0, value);
+        assert_ne!(eval_seeded(&db, &stmts, 43, &[]).0.field("gold"), value.field("gold"));
+        assert_eq!(value.field("copy"), value.field("gold"));
+
+        let rolls = rolls.iter().map(|roll| (roll.path.as_str(), roll.index, roll.dice.as_str())).collect::<Vec<_>>();
+        assert_eq!(
+            rolls,
+            vec![
+                ("gold", 0, "1d1000000"),
+                ("drops[0]", 0, "1d6"),
+                ("drops[1].n", 0, "1d6"),
+                ("both", 0, "1d100"),
+                ("both", 1, "1d100"),
+            ]
+        );
+    }
+
+    #[test]
+    fn eval_field_access() {
+        check(
+            "b: {\n  c: {\n    d: 4\n  }\n}\ne: b.c::d\nf: b.x",
+            fields(vec![
+                ("b", fields(vec![("c", fields(vec![("d", Value::Int(4))]))])),
+                ("e", Value::Int(4)),
+                ("f", Value::bottom("`b` has no field `x`")),
+            ]),
+        );
+    }
+
+    #[test]
+    fn eval_sections_as_fields() {
+        check(
+            "--- #one:\na: 1\n--- #two:\nb: a",
+            fields(vec![("a", Value::Int(1)), ("b", Value::Int(1))]),
+        );
+    }
+}
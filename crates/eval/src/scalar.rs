@@ -0,0 +1,41 @@
+use crate::Value;
+use std::fmt;
+
+/// The kinds of value a type like `!int` stands for any one of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scalar {
+    Int,
+    Float,
+    String,
+}
+
+impl Scalar {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::String => "string",
+        }
+    }
+
+    pub fn is_number(self) -> bool {
+        matches!(self, Self::Int | Self::Float)
+    }
+
+    /// Whether `value` is one of these. Ints are floats too, so `!float`
+    /// takes them.
+    pub fn admits(self, value: &Value) -> bool {
+        matches!(
+            (self, value),
+            (Self::Int, Value::Int(_))
+                | (Self::Float, Value::Int(_) | Value::Float(_))
+                | (Self::String, Value::String(_))
+        )
+    }
+}
+
+impl fmt::Display for Scalar {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "!{}", self.name())
+    }
+}
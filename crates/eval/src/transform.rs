@@ -0,0 +1,397 @@
+/*
+TL;DR: Transforms, which turn values into other values.
+
+    "elbow" -> #TitleCase               → "Elbow"
+    " a,b " -> #trim -> #split(",")     → ["a", "b"]
+    15 -> #clamp(0, 10)                 → 10
+    [3, 1, 3] -> #unique -> #sort       → [1, 3]
+    "déjà" -> #len                      → 4
+
+`x -> #f(a, b)` is `#f(x, a, b)`, so whatever's piped in is the first
+argument. Every transform says how many arguments it takes and what kind of
+value each one is, and those are checked before it runs, so a transform only
+ever sees the arguments it asked for. The standard library can also be named
+as `std::#f`.
+*/
+
+use crate::Value;
+use smol_str::SmolStr;
+use text_size::TextRange;
+
+/// The kind of value a transform takes as one of its arguments.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Param {
+    String,
+    /// An int or a float.
+    Number,
+    List,
+    /// A list or a string.
+    Sequence,
+}
+
+impl Param {
+    fn accepts(self, value: &Value) -> bool {
+        match self {
+            Self::String => matches!(value, Value::String(_)),
+            Self::Number => matches!(value, Value::Int(_) | Value::Float(_)),
+            Self::List => matches!(value, Value::List(_)),
+            Self::Sequence => matches!(value, Value::List(_) | Value::String(_)),
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::String => "a string",
+            Self::Number => "a number",
+            Self::List => "a list",
+            Self::Sequence => "a list or a string",
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub(crate) struct Transform {
+    name: &'static str,
+    params: &'static [Param],
+    /// Only ever called with arguments that match `params`.
+    run: fn(&[Value]) -> Value,
+}
+
+/// Every transform that can be called, by name.
+pub(crate) struct Registry {
+    transforms: Vec<Transform>,
+}
+
+impl Registry {
+    /// The standard library.
+    pub(crate) fn std() -> Self {
+        let mut registry = Self { transforms: Vec::new() };
+
+        registry.register("lowercase", &[Param::String], |args| string(str_arg(args, 0).to_lowercase()));
+        registry.register("UPPERCASE", &[Param::String], |args| string(str_arg(args, 0).to_uppercase()));
+        registry.register("TitleCase", &[Param::String], |args| {
+            string(words(str_arg(args, 0)).iter().map(|word| capitalized(word)).collect::<Vec<_>>().join(" "))
+        });
+        registry.register("PascalCase", &[Param::String], |args| {
+            string(words(str_arg(args, 0)).iter().map(|word| capitalized(word)).collect::<String>())
+        });
+        registry.register("camelCase", &[Param::String], |args| {
+            let words = words(str_arg(args, 0));
+            let mut camel = words.first().map(|word| word.to_lowercase()).unwrap_or_default();
+            camel.extend(words.iter().skip(1).map(|word| capitalized(word)));
+            string(camel)
+        });
+        registry.register("snake_case", &[Param::String], |args| {
+            string(words(str_arg(args, 0)).iter().map(|word| word.to_lowercase()).collect::<Vec<_>>().join("_"))
+        });
+        registry.register("trim", &[Param::String], |args| string(str_arg(args, 0).trim()));
+        registry.register("replace", &[Param::String, Param::String, Param::String], |args| {
+            match str_arg(args, 1) {
+                "" => Value::bottom("`#replace` can't replace an empty string"),
+                from => string(str_arg(args, 0).replace(from, str_arg(args, 2))),
+            }
+        });
+        registry.register("split", &[Param::String, Param::String], |args| {
+            let s = str_arg(args, 0);
+            match str_arg(args, 1) {
+                // Every character on its own.
+                "" => Value::List(s.chars().map(|c| string(c.to_string())).collect()),
+                sep => Value::List(s.split(sep).map(string).collect()),
+            }
+        });
+        registry.register("join", &[Param::List, Param::String], |args| {
+            let mut parts = Vec::new();
+            for (idx, item) in list_arg(args, 0).iter().enumerate() {
+                match item {
+                    Value::String(s) => parts.push(s.as_str()),
+                    item => {
+                        return Value::bottom(format!(
+                            "`#join` joins strings, but item {} is {}",
+                            idx,
+                            described(item)
+                        ))
+                    }
+                }
+            }
+            string(parts.join(str_arg(args, 1)))
+        });
+
+        registry.register("clamp", &[Param::Number, Param::Number, Param::Number], |args| {
+            match (&args[0], &args[1], &args[2]) {
+                (_, lo, hi) if float(lo).is_nan() || float(hi).is_nan() || float(lo) > float(hi) => {
+                    Value::bottom(format!("`#clamp` can't keep a number from {} up to {}", lo, hi))
+                }
+                (Value::Int(n), Value::Int(lo), Value::Int(hi)) => Value::Int(*n.clamp(lo, hi)),
+                (n, lo, hi) => Value::Float(float(n).clamp(float(lo), float(hi))),
+            }
+        });
+        registry.register("round", &[Param::Number], |args| match &args[0] {
+            Value::Float(f) if (i64::MIN as f64..i64::MAX as f64).contains(&f.round()) => {
+                Value::Int(f.round() as i64)
+            }
+            Value::Float(f) => Value::bottom(format!("`#round` of {:?} doesn't fit in an int", f)),
+            n => n.clone(),
+        });
+        registry.register("abs", &[Param::Number], |args| match &args[0] {
+            Value::Int(n) => match n.checked_abs() {
+                Some(n) => Value::Int(n),
+                None => Value::bottom(format!("`#abs` of {} doesn't fit in an int", n)),
+            },
+            n => Value::Float(float(n).abs()),
+        });
+
+        registry.register("sort", &[Param::List], |args| {
+            let items = list_arg(args, 0);
+            let numbers = items.iter().all(|item| Param::Number.accepts(item));
+            let strings = items.iter().all(|item| Param::String.accepts(item));
+            if !numbers && !strings {
+                return Value::bottom("`#sort` sorts lists of only numbers or only strings");
+            }
+
+            let mut items = items.to_vec();
+            items.sort_by(|a, b| match (a, b) {
+                (Value::String(a), Value::String(b)) => a.cmp(b),
+                (Value::Int(a), Value::Int(b)) => a.cmp(b),
+                (a, b) => float(a).total_cmp(&float(b)),
+            });
+            Value::List(items)
+        });
+        registry.register("unique", &[Param::List], |args| {
+            let mut unique = Vec::new();
+            for item in list_arg(args, 0) {
+                if !unique.contains(item) {
+                    unique.push(item.clone());
+                }
+            }
+            Value::List(unique)
+        });
+        // Strings are as long as the characters in them, not their bytes.
+        registry.register("len", &[Param::Sequence], |args| match &args[0] {
+            Value::String(s) => Value::Int(s.chars().count() as i64),
+            _ => Value::Int(list_arg(args, 0).len() as i64),
+        });
+
+        registry
+    }
+
+    pub(crate) fn register(&mut self, name: &'static str, params: &'static [Param], run: fn(&[Value]) -> Value) {
+        self.transforms.retain(|transform| transform.name != name);
+        self.transforms.push(Transform { name, params, run });
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<Transform> {
+        self.transforms.iter().find(|transform| transform.name == name).copied()
+    }
+}
+
+impl Transform {
+    /// Runs the transform, if it was given as many arguments as it takes and
+    /// each is the kind it expects. `range` is where it was called.
+    pub(crate) fn apply(&self, args: &[Value], range: TextRange) -> Value {
+        if args.len() != self.params.len() {
+            return Value::bottom_at(
+                format!(
+                    "`#{}` takes {} but was given {}",
+                    self.name,
+                    arguments(self.params.len()),
+                    args.len()
+                ),
+                range,
+            );
+        }
+        if let Some(bottom) = args.iter().find(|arg| arg.is_bottom()) {
+            return bottom.clone();
+        }
+        for (idx, (param, arg)) in self.params.iter().zip(args).enumerate() {
+            if !param.accepts(arg) {
+                return Value::bottom_at(
+                    format!(
+                        "`#{}` takes {} as argument {}, not {}",
+                        self.name,
+                        param.name(),
+                        idx + 1,
+                        described(arg)
+                    ),
+                    range,
+                );
+            }
+        }
+
+        (self.run)(args)
+    }
+}
+
+fn arguments(n: usize) -> String {
+    match n {
+        1 => String::from("1 argument"),
+        n => format!("{} arguments", n),
+    }
+}
+
+/// A value's kind, for saying what a transform was given instead.
+fn described(value: &Value) -> String {
+    match value {
+        Value::Type(scalar, _) => format!("any {}", scalar.name()),
+        value => match value.kind_name() {
+            kind @ ("int" | "enum") => format!("an {}", kind),
+            kind => format!("a {}", kind),
+        },
+    }
+}
+
+fn string(s: impl Into<SmolStr>) -> Value {
+    Value::String(s.into())
+}
+
+fn str_arg(args: &[Value], idx: usize) -> &str {
+    match &args[idx] {
+        Value::String(s) => s,
+        _ => unreachable!("arguments are checked before a transform runs"),
+    }
+}
+
+fn list_arg(args: &[Value], idx: usize) -> &[Value] {
+    match &args[idx] {
+        Value::List(items) => items,
+        _ => unreachable!("arguments are checked before a transform runs"),
+    }
+}
+
+fn float(value: &Value) -> f64 {
+    match value {
+        Value::Int(n) => *n as f64,
+        Value::Float(f) => *f,
+        _ => unreachable!("arguments are checked before a transform runs"),
+    }
+}
+
+/// The words in `s`, which are split at anything that isn't a letter or a
+/// digit, and where the case changes, so `"HTTPServer_name"` is `HTTP`,
+/// `Server` and `name`.
+fn words(s: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    for part in s.split(|c: char| !c.is_alphanumeric()) {
+        let chars = part.chars().collect::<Vec<char>>();
+        let mut word = String::new();
+        for (idx, &c) in chars.iter().enumerate() {
+            let prev = idx.checked_sub(1).map(|prev| chars[prev]);
+            let next = chars.get(idx + 1);
+            let starts_word = c.is_uppercase()
+                && prev.is_some_and(|prev| {
+                    !prev.is_uppercase() || next.is_some_and(|next| next.is_lowercase())
+                });
+            if starts_word && !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            word.push(c);
+        }
+        if !word.is_empty() {
+            words.push(word);
+        }
+    }
+    words
+}
+
+fn capitalized(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(char::to_lowercase)).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(name: &str, args: Vec<Value>) -> Value {
+        Registry::std()
+            .get(name)
+            .unwrap()
+            .apply(&args, TextRange::new(3.into(), 10.into()))
+    }
+
+    fn s(s: &str) -> Value {
+        Value::String(s.into())
+    }
+
+    #[test]
+    fn change_case() {
+        let input = || vec![s("the HTTPServer_name")];
+
+        assert_eq!(apply("lowercase", input()), s("the httpserver_name"));
+        assert_eq!(apply("UPPERCASE", input()), s("THE HTTPSERVER_NAME"));
+        assert_eq!(apply("TitleCase", input()), s("The Http Server Name"));
+        assert_eq!(apply("PascalCase", input()), s("TheHttpServerName"));
+        assert_eq!(apply("camelCase", input()), s("theHttpServerName"));
+        assert_eq!(apply("snake_case", input()), s("the_http_server_name"));
+        assert_eq!(apply("TitleCase", vec![s("elbow")]), s("Elbow"));
+    }
+
+    #[test]
+    fn edit_strings() {
+        assert_eq!(apply("trim", vec![s("  a b ")]), s("a b"));
+        assert_eq!(apply("replace", vec![s("elbow"), s("l"), s("LL")]), s("eLLbow"));
+        assert_eq!(apply("split", vec![s("a,b,,c"), s(",")]), Value::List(vec![s("a"), s("b"), s(""), s("c")]));
+        assert_eq!(apply("split", vec![s("ab"), s("")]), Value::List(vec![s("a"), s("b")]));
+        assert_eq!(apply("join", vec![Value::List(vec![s("a"), s("b")]), s("-")]), s("a-b"));
+        assert_eq!(
+            apply("join", vec![Value::List(vec![s("a"), Value::Int(1)]), s("-")]),
+            Value::bottom("`#join` joins strings, but item 1 is an int")
+        );
+    }
+
+    #[test]
+    fn change_numbers() {
+        assert_eq!(apply("clamp", vec![Value::Int(15), Value::Int(0), Value::Int(10)]), Value::Int(10));
+        assert_eq!(apply("clamp", vec![Value::Float(-0.5), Value::Int(0), Value::Int(1)]), Value::Float(0.0));
+        assert_eq!(
+            apply("clamp", vec![Value::Int(1), Value::Int(2), Value::Int(0)]),
+            Value::bottom("`#clamp` can't keep a number from 2 up to 0")
+        );
+        assert_eq!(
+            apply("clamp", vec![Value::Int(1), Value::Float(f64::NAN), Value::Int(3)]),
+            Value::bottom("`#clamp` can't keep a number from NaN up to 3")
+        );
+        assert!(apply("round", vec![Value::Float(f64::NAN)]).is_bottom());
+        assert_eq!(apply("round", vec![Value::Float(2.5)]), Value::Int(3));
+        assert_eq!(apply("round", vec![Value::Int(7)]), Value::Int(7));
+        assert_eq!(apply("abs", vec![Value::Int(-4)]), Value::Int(4));
+        assert_eq!(apply("abs", vec![Value::Float(-0.5)]), Value::Float(0.5));
+        assert!(apply("abs", vec![Value::Int(i64::MIN)]).is_bottom());
+    }
+
+    #[test]
+    fn change_lists() {
+        let ints = |ns: &[i64]| Value::List(ns.iter().copied().map(Value::Int).collect());
+
+        assert_eq!(apply("sort", vec![ints(&[3, 1, 2])]), ints(&[1, 2, 3]));
+        assert_eq!(apply("sort", vec![Value::List(vec![s("b"), s("a")])]), Value::List(vec![s("a"), s("b")]));
+        assert!(apply("sort", vec![Value::List(vec![s("b"), Value::Int(1)])]).is_bottom());
+        assert_eq!(apply("unique", vec![ints(&[3, 1, 3, 1])]), ints(&[3, 1]));
+        assert_eq!(apply("len", vec![ints(&[3, 1, 3])]), Value::Int(3));
+        assert_eq!(apply("len", vec![s("déjà")]), Value::Int(4));
+    }
+
+    #[test]
+    fn check_arguments() {
+        let range = TextRange::new(3.into(), 10.into());
+
+        assert_eq!(
+            apply("clamp", vec![Value::Int(1)]),
+            Value::bottom_at("`#clamp` takes 3 arguments but was given 1", range)
+        );
+        assert_eq!(
+            apply("trim", vec![Value::Int(1)]),
+            Value::bottom_at("`#trim` takes a string as argument 1, not an int", range)
+        );
+        assert_eq!(
+            apply("len", vec![Value::Int(1)]),
+            Value::bottom_at("`#len` takes a list or a string as argument 1, not an int", range)
+        );
+        assert_eq!(
+            apply("abs", vec![Value::bottom("missing expression")]),
+            Value::bottom("missing expression")
+        );
+    }
+}
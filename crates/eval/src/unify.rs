@@ -0,0 +1,306 @@
+/*
+TL;DR: `&` is the meet of the value lattice.
+
+        _|_ ← conflicts fall to the bottom
+         ↑
+    1  "a"  { hp: 5 }   ← concrete values only unify with themselves
+     ↑         ↑
+    >=0     { hp: >=0 } ← constraints narrow as they're combined
+     ↑
+    !int                ← types take any value of their kind
+
+Structs are merged field by field, keeping the order fields were first seen
+in. A conflict inside a struct only bottoms out the field it happened in, so
+the rest of the struct is still around to be reported on.
+
+A type unified with a constraint keeps both, so `!int & >=0` is any int that's
+at least 0. `!float` takes ints too, which become floats.
+
+An optional field stays optional only while every side has it as optional.
+
+An enum picks a member out of a name or a number, and a member unifies with
+its own name and value.
+
+Disjunctions distribute: every pairing of alternatives is unified, and the
+pairings that fall to the bottom are dropped.
+*/
+
+use std::mem;
+
+use crate::constraint::{find_conflict, Constraint};
+use crate::disjunction::{alternatives, unify_disjunction};
+use crate::{Scalar, Value};
+
+/// Unifies two values, producing the most general value that satisfies both.
+pub fn unify(lhs: Value, rhs: Value) -> Value {
+    match (lhs, rhs) {
+        (Value::Bottom(reason, range), _) | (_, Value::Bottom(reason, range)) => Value::Bottom(reason, range),
+
+        (Value::Optional(lhs), Value::Optional(rhs)) => Value::Optional(Box::new(unify(*lhs, *rhs))),
+        // Giving an optional field a value makes it one that has to be there.
+        (Value::Optional(lhs), rhs) => unify(*lhs, rhs),
+        (lhs, Value::Optional(rhs)) => unify(lhs, *rhs),
+
+        (lhs @ Value::Disjunction(_), rhs) | (lhs, rhs @ Value::Disjunction(_)) => {
+            unify_disjunction(alternatives(lhs), alternatives(rhs))
+        }
+
+        (Value::Enum(lhs), Value::Enum(rhs)) => {
+            if lhs == rhs {
+                Value::Enum(lhs)
+            } else {
+                Value::bottom(format!("conflicting enums {} and {}", lhs, rhs))
+            }
+        }
+        (Value::Enum(ty), value) | (value, Value::Enum(ty)) => ty.member(&value).map_or_else(Value::bottom, Value::Member),
+        (Value::Member(member), value) | (value, Value::Member(member)) if member.is(&value) => Value::Member(member),
+        (Value::Member(member), value @ (Value::String(_) | Value::Int(_)))
+        | (value @ (Value::String(_) | Value::Int(_)), Value::Member(member)) => {
+            Value::bottom(format!("conflicting values {} and {}", Value::Member(member), value))
+        }
+
+        (Value::Type(lhs, lhs_constraints), Value::Type(rhs, rhs_constraints)) => {
+            if lhs != rhs {
+                return Value::bottom(format!("conflicting kinds {} and {}", lhs.name(), rhs.name()));
+            }
+            merge(lhs_constraints, rhs_constraints).map_or_else(|bottom| bottom, |merged| Value::Type(lhs, merged))
+        }
+        (Value::Type(scalar, lhs), Value::Constraint(rhs)) | (Value::Constraint(rhs), Value::Type(scalar, lhs)) => {
+            if !scalar.is_number() {
+                return Value::bottom(format!("{} can't satisfy `{}`", scalar.name(), Value::Constraint(rhs)));
+            }
+            merge(lhs, rhs).map_or_else(|bottom| bottom, |merged| Value::Type(scalar, merged))
+        }
+        (Value::Type(scalar, constraints), value) | (value, Value::Type(scalar, constraints)) => {
+            typed(scalar, &constraints, value)
+        }
+
+        (Value::Constraint(lhs), Value::Constraint(rhs)) => {
+            merge(lhs, rhs).map_or_else(|bottom| bottom, Value::Constraint)
+        }
+        (Value::Constraint(constraints), value) | (value, Value::Constraint(constraints)) => {
+            narrow(&constraints, value)
+        }
+
+        (Value::List(lhs), Value::List(rhs)) => {
+            if lhs.len() != rhs.len() {
+                return Value::bottom(format!(
+                    "lists of length {} and {} can't be unified",
+                    lhs.len(),
+                    rhs.len()
+                ));
+            }
+
+            Value::List(lhs.into_iter().zip(rhs).map(|(a, b)| unify(a, b)).collect())
+        }
+        (Value::Struct(mut lhs), Value::Struct(rhs)) => {
+            for (name, value) in rhs {
+                match lhs.iter_mut().find(|(n, _)| *n == name) {
+                    Some((_, slot)) => {
+                        let existing = mem::replace(slot, Value::Struct(Vec::new()));
+                        *slot = unify(existing, value);
+                    }
+                    None => lhs.push((name, value)),
+                }
+            }
+
+            Value::Struct(lhs)
+        }
+
+        (lhs, rhs) if lhs == rhs => lhs,
+        (lhs, rhs) if lhs.kind_name() == rhs.kind_name() => {
+            Value::bottom(format!("conflicting values {} and {}", lhs, rhs))
+        }
+        (lhs, rhs) => Value::bottom(format!(
+            "conflicting kinds {} and {}",
+            lhs.kind_name(),
+            rhs.kind_name()
+        )),
+    }
+}
+
+/// Every constraint on either side, unless two of them can never both hold.
+fn merge(mut lhs: Vec<Constraint>, rhs: Vec<Constraint>) -> Result<Vec<Constraint>, Value> {
+    for constraint in rhs {
+        if !lhs.contains(&constraint) {
+            lhs.push(constraint);
+        }
+    }
+
+    match find_conflict(&lhs) {
+        Some((a, b)) => Err(Value::bottom(format!("`{}` and `{}` can never both hold", a, b))),
+        None => Ok(lhs),
+    }
+}
+
+/// A value given a type, which has to be of the type's kind and meet any
+/// constraints that came with it.
+fn typed(scalar: Scalar, constraints: &[Constraint], value: Value) -> Value {
+    if !scalar.admits(&value) {
+        return Value::bottom(format!("conflicting kinds {} and {}", scalar.name(), value.kind_name()));
+    }
+
+    let value = match (scalar, value) {
+        (Scalar::Float, Value::Int(n)) => Value::Float(n as f64),
+        (_, value) => value,
+    };
+    if constraints.is_empty() {
+        value
+    } else {
+        narrow(constraints, value)
+    }
+}
+
+fn narrow(constraints: &[Constraint], value: Value) -> Value {
+    let n = match value {
+        Value::Int(n) => n as f64,
+        Value::Float(n) => n,
+        value => {
+            return Value::bottom(format!(
+                "{} can't satisfy `{}`",
+                value.kind_name(),
+                Value::Constraint(constraints.to_vec())
+            ))
+        }
+    };
+
+    match constraints.iter().find(|c| !c.allows(n)) {
+        Some(failed) => violation(&value, failed),
+        None => value,
+    }
+}
+
+/// A value failing a constraint, pointing at where the constraint was
+/// written when that's known.
+fn violation(value: &Value, failed: &Constraint) -> Value {
+    Value::Bottom(format!("{} does not satisfy `{}`", value, failed).into(), failed.range)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Bound;
+
+    fn check(lhs: Value, rhs: Value, expected: Value) {
+        assert_eq!(unify(lhs, rhs), expected);
+    }
+
+    #[test]
+    fn unify_equal_scalars() {
+        check(Value::Int(4), Value::Int(4), Value::Int(4));
+    }
+
+    #[test]
+    fn unify_conflicting_scalars() {
+        check(
+            Value::String("A".into()),
+            Value::String("B".into()),
+            Value::bottom(r#"conflicting values "A" and "B""#),
+        );
+    }
+
+    #[test]
+    fn unify_conflicting_kinds() {
+        check(
+            Value::Int(1),
+            Value::String("1".into()),
+            Value::bottom("conflicting kinds int and string"),
+        );
+    }
+
+    #[test]
+    fn unify_bottom_absorbs() {
+        check(
+            Value::bottom("nope"),
+            Value::Int(1),
+            Value::bottom("nope"),
+        );
+    }
+
+    #[test]
+    fn unify_narrows_constraints() {
+        check(
+            Value::Constraint(vec![Bound::GrEq(0.0).into()]),
+            Value::Constraint(vec![Bound::Ls(10.0).into()]),
+            Value::Constraint(vec![Bound::GrEq(0.0).into(), Bound::Ls(10.0).into()]),
+        );
+    }
+
+    #[test]
+    fn unify_disjoint_constraints() {
+        check(
+            Value::Constraint(vec![Bound::Gr(5.0).into()]),
+            Value::Constraint(vec![Bound::LsEq(5.0).into()]),
+            Value::bottom("`>5` and `<=5` can never both hold"),
+        );
+    }
+
+    #[test]
+    fn unify_value_with_constraint() {
+        check(
+            Value::Int(3),
+            Value::Constraint(vec![Bound::GrEq(0.0).into()]),
+            Value::Int(3),
+        );
+        check(
+            Value::Constraint(vec![Bound::GrEq(0.0).into()]),
+            Value::Int(-3),
+            Value::bottom("-3 does not satisfy `>=0`"),
+        );
+    }
+
+    #[test]
+    fn unify_structs_field_by_field() {
+        let schema = Value::Struct(vec![
+            ("name".into(), Value::String("goblin".into())),
+            ("hp".into(), Value::Constraint(vec![Bound::GrEq(0.0).into()])),
+        ]);
+        let data = Value::Struct(vec![
+            ("hp".into(), Value::Int(-1)),
+            ("speed".into(), Value::Float(1.5)),
+        ]);
+
+        check(
+            schema,
+            data,
+            Value::Struct(vec![
+                ("name".into(), Value::String("goblin".into())),
+                ("hp".into(), Value::bottom("-1 does not satisfy `>=0`")),
+                ("speed".into(), Value::Float(1.5)),
+            ]),
+        );
+    }
+
+    #[test]
+    fn unify_lists_of_different_lengths() {
+        check(
+            Value::List(vec![Value::Int(1)]),
+            Value::List(vec![Value::Int(1), Value::Int(2)]),
+            Value::bottom("lists of length 1 and 2 can't be unified"),
+        );
+    }
+
+    #[test]
+    fn unify_types() {
+        let int = |constraints: Vec<Constraint>| Value::Type(Scalar::Int, constraints);
+
+        check(int(vec![]), Value::Int(4), Value::Int(4));
+        check(Value::Type(Scalar::Float, vec![]), Value::Int(4), Value::Float(4.0));
+        check(int(vec![]), Value::Float(4.5), Value::bottom("conflicting kinds int and float"));
+        check(
+            int(vec![]),
+            Value::Constraint(vec![Bound::GrEq(0.0).into()]),
+            int(vec![Bound::GrEq(0.0).into()]),
+        );
+        check(
+            int(vec![Bound::GrEq(0.0).into()]),
+            Value::Int(-1),
+            Value::bottom("-1 does not satisfy `>=0`"),
+        );
+        check(
+            Value::Type(Scalar::String, vec![]),
+            Value::Constraint(vec![Bound::GrEq(0.0).into()]),
+            Value::bottom("string can't satisfy `>=0`"),
+        );
+    }
+}
@@ -0,0 +1,322 @@
+/*
+TL;DR: Checks data against a schema and reports everything wrong with it,
+where unifying the two would stop at the first `_|_` in each field.
+
+    $unit: {                    goblin: {
+      hp: >=0                     hp: 0 - 5
+      name: "gob" | "orc"         speed: 3
+    }                           }
+
+    `goblin.hp`: -5 does not satisfy `>=0`
+    `goblin.name` is missing
+    `goblin.speed` isn't in the schema
+
+Fields the schema has are required unless they're optional, `name?:`, or
+have a default to fall back on, and fields it doesn't have aren't allowed.
+Optional fields the data never gave a value count as left out.
+*/
+
+use crate::{unify, Constraint, Value};
+use smol_str::SmolStr;
+use std::fmt;
+
+/// One step on the way to a value: a field of a struct, or an item of a list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Step {
+    Field(SmolStr),
+    Index(usize),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    /// A field the schema has and the data doesn't.
+    Missing { path: Vec<Step> },
+    /// A field the data has and the schema doesn't.
+    Unknown { path: Vec<Step> },
+    /// A value of the wrong kind, like a string where the schema wants a number.
+    Mismatch {
+        path: Vec<Step>,
+        expected: &'static str,
+        found: &'static str,
+    },
+    /// A value of the right kind that the schema still rules out.
+    Constraint { path: Vec<Step>, reason: SmolStr },
+}
+
+impl Violation {
+    pub fn path(&self) -> &[Step] {
+        match self {
+            Self::Missing { path } | Self::Unknown { path } => path,
+            Self::Mismatch { path, .. } | Self::Constraint { path, .. } => path,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing { path } => write!(f, "`{}` is missing", written(path)),
+            Self::Unknown { path } => write!(f, "`{}` isn't in the schema", written(path)),
+            Self::Mismatch { path, expected, found } => {
+                write!(f, "`{}` should be {}, not {}", written(path), a(expected), a(found))
+            }
+            Self::Constraint { path, reason } => write!(f, "`{}`: {}", written(path), reason),
+        }
+    }
+}
+
+/// Every way `data` breaks `schema`. `path` is where the data sits, and
+/// starts the path of every violation.
+pub fn validate(schema: &Value, data: &Value, path: &[Step]) -> Vec<Violation> {
+    let mut out = Vec::new();
+    check(schema, data, path, &mut out);
+    out
+}
+
+fn check(schema: &Value, data: &Value, path: &[Step], out: &mut Vec<Violation>) {
+    let constraint = |reason: String| Violation::Constraint {
+        path: path.to_vec(),
+        reason: reason.into(),
+    };
+
+    match (schema, data) {
+        (_, Value::Bottom(reason, _)) | (Value::Bottom(reason, _), _) => out.push(constraint(reason.to_string())),
+
+        (Value::Optional(schema), data) => check(schema, data, path, out),
+        (schema, Value::Optional(data)) => check(schema, data, path, out),
+
+        (Value::Struct(expected), Value::Struct(found)) => {
+            let found = found
+                .iter()
+                .filter_map(|(name, value)| Some((name, value.provided()?)))
+                .collect::<Vec<_>>();
+
+            for (name, value) in expected {
+                match found.iter().find(|(n, _)| *n == name) {
+                    Some((_, found)) => check(value, found, &field(path, name), out),
+                    None if is_optional(value) || has_default(value) => {}
+                    None => out.push(Violation::Missing {
+                        path: field(path, name),
+                    }),
+                }
+            }
+            for (name, _) in &found {
+                if !expected.iter().any(|(n, _)| n == *name) {
+                    out.push(Violation::Unknown {
+                        path: field(path, name),
+                    });
+                }
+            }
+        }
+
+        (Value::List(expected), Value::List(found)) if expected.len() == found.len() => {
+            for (idx, (expected, found)) in expected.iter().zip(found).enumerate() {
+                check(expected, found, &step(path, Step::Index(idx)), out);
+            }
+        }
+        (Value::List(expected), Value::List(found)) => out.push(constraint(format!(
+            "a list of length {} can't be one of length {}",
+            found.len(),
+            expected.len()
+        ))),
+
+        (Value::Constraint(constraints), Value::Int(_) | Value::Float(_)) => bounds(constraints, data, path, out),
+        (Value::Constraint(_), Value::String(_) | Value::List(_) | Value::Struct(_)) => out.push(Violation::Mismatch {
+            path: path.to_vec(),
+            expected: "number",
+            found: data.kind_name(),
+        }),
+
+        (Value::Type(scalar, _), Value::Int(_) | Value::Float(_) | Value::String(_) | Value::List(_) | Value::Struct(_))
+            if !scalar.admits(data) =>
+        {
+            out.push(Violation::Mismatch {
+                path: path.to_vec(),
+                expected: scalar.name(),
+                found: data.kind_name(),
+            })
+        }
+        (Value::Type(_, constraints), Value::Int(_) | Value::Float(_)) => bounds(constraints, data, path, out),
+        (Value::Type(..), Value::String(_)) => {}
+
+        (Value::Enum(ty), Value::Int(_) | Value::String(_) | Value::Member(_)) => {
+            if let Err(reason) = ty.member(data) {
+                out.push(constraint(reason));
+            }
+        }
+        (Value::Member(member), Value::Int(_) | Value::String(_)) if member.is(data) => {}
+
+        // Alternatives and data that's incomplete itself only have to agree.
+        (Value::Disjunction(_), _)
+        | (_, Value::Constraint(_) | Value::Type(..) | Value::Enum(_) | Value::Disjunction(_)) => {
+            if let Value::Bottom(..) = unify(schema.clone(), data.clone()) {
+                out.push(constraint(format!("{} isn't allowed by `{}`", data, schema)));
+            }
+        }
+
+        (expected, found) if expected.kind_name() != found.kind_name() => out.push(Violation::Mismatch {
+            path: path.to_vec(),
+            expected: expected.kind_name(),
+            found: found.kind_name(),
+        }),
+        (expected, found) if expected != found => out.push(constraint(format!("{} should be {}", found, expected))),
+        _ => {}
+    }
+}
+
+/// Reports the first constraint a number doesn't satisfy.
+fn bounds(constraints: &[Constraint], data: &Value, path: &[Step], out: &mut Vec<Violation>) {
+    let n = match data {
+        Value::Int(n) => *n as f64,
+        Value::Float(n) => *n,
+        _ => return,
+    };
+    if let Some(broken) = constraints.iter().find(|c| !c.allows(n)) {
+        out.push(Violation::Constraint {
+            path: path.to_vec(),
+            reason: format!("{} does not satisfy `{}`", data, broken).into(),
+        });
+    }
+}
+
+/// A kind of value with its article, like "an int".
+fn a(kind: &str) -> String {
+    if kind.starts_with(['a', 'e', 'i', 'o', 'u']) {
+        format!("an {}", kind)
+    } else {
+        format!("a {}", kind)
+    }
+}
+
+fn is_optional(value: &Value) -> bool {
+    matches!(value, Value::Optional(_))
+}
+
+/// Whether a missing field can fall back on a default.
+fn has_default(value: &Value) -> bool {
+    matches!(value, Value::Disjunction(alternatives) if alternatives.iter().any(|a| a.is_default))
+}
+
+fn field(path: &[Step], name: &SmolStr) -> Vec<Step> {
+    step(path, Step::Field(name.clone()))
+}
+
+fn step(path: &[Step], step: Step) -> Vec<Step> {
+    let mut path = path.to_vec();
+    path.push(step);
+    path
+}
+
+/// A path the way `Value::bottoms` writes them, like `units.goblin[2]`.
+fn written(path: &[Step]) -> String {
+    let mut out = String::new();
+    for step in path {
+        match step {
+            Step::Field(name) if out.is_empty() => out.push_str(name),
+            Step::Field(name) => {
+                out.push('.');
+                out.push_str(name);
+            }
+            Step::Index(idx) => out.push_str(&format!("[{}]", idx)),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(schema: &str, data: &str, expected: &[&str]) {
+        let lower = |text: &str| {
+            let root = ast::Root::cast(parser::parse(text).syntax()).unwrap();
+            hir::lower(root)
+        };
+        let (db, stmts) = lower(schema);
+        let schema = crate::schema(&db, &stmts, "unit").unwrap();
+        let (db, stmts) = lower(data);
+        let data = crate::output(&db, &stmts).resolved();
+
+        let found = match &data {
+            Value::Struct(records) => records
+                .iter()
+                .flat_map(|(name, value)| validate(&schema, value, &[Step::Field(name.clone())]))
+                .map(|violation| violation.to_string())
+                .collect::<Vec<String>>(),
+            _ => unreachable!(),
+        };
+        assert_eq!(found, expected);
+    }
+
+    #[test]
+    fn valid_data() {
+        check("$unit: {\n  hp: >=0\n  name: \"gob\" | \"orc\"\n}", "goblin: {\n  hp: 5\n  name: \"gob\"\n}", &[]);
+    }
+
+    #[test]
+    fn reports_every_violation() {
+        check(
+            "$unit: {\n  hp: >=0 & <100\n  name: \"gob\" | \"orc\"\n  stats: {\n    str: >=1\n    kind: \"melee\"\n  }\n}",
+            "goblin: {\n  hp: 0 - 5\n  speed: 3\n  stats: {\n    str: \"lots\"\n    kind: \"ranged\"\n  }\n}\norc: {\n  hp: 200\n  name: \"elf\"\n}",
+            &[
+                "`goblin.hp`: -5 does not satisfy `>=0`",
+                "`goblin.name` is missing",
+                "`goblin.stats.str` should be a number, not a string",
+                "`goblin.stats.kind`: \"ranged\" should be \"melee\"",
+                "`goblin.speed` isn't in the schema",
+                "`orc.hp`: 200 does not satisfy `<100`",
+                "`orc.name`: \"elf\" isn't allowed by `\"gob\" | \"orc\"`",
+                "`orc.stats` is missing",
+            ],
+        );
+    }
+
+    #[test]
+    fn fields_with_defaults_can_be_left_out() {
+        check("$unit: {\n  hp: >=0\n  kind: *\"melee\" | \"ranged\"\n}", "goblin: {\n  hp: 1\n}", &[]);
+    }
+
+    #[test]
+    fn optional_fields_can_be_left_out() {
+        check(
+            "$unit: {\n  hp: >=0\n  name?: \"gob\" | \"orc\"\n}",
+            "goblin: {\n  hp: 1\n}\norc: {\n  hp: 2\n  name: \"elf\"\n}\nelf: {\n  hp: 3\n  name?: >=0\n}",
+            &["`orc.name`: \"elf\" isn't allowed by `\"gob\" | \"orc\"`"],
+        );
+    }
+
+    #[test]
+    fn typed_fields() {
+        check(
+            "$unit: {\n  hp: !int & >=0\n  speed: !float\n  name: !string\n  pos: !vec[2 -> !float]\n}",
+            "goblin: {\n  hp: 5\n  speed: 2\n  name: \"gob\"\n  pos: [1, 2]\n}\norc: {\n  hp: 0 - 1\n  speed: \"fast\"\n  name: 3\n  pos: [1]\n}",
+            &[
+                "`orc.hp`: -1 does not satisfy `>=0`",
+                "`orc.speed` should be a float, not a string",
+                "`orc.name` should be a string, not an int",
+                "`orc.pos`: a list of length 1 can't be one of length 2",
+            ],
+        );
+    }
+
+    #[test]
+    fn enum_fields() {
+        check(
+            "$unit: {\n  color: !enum{\n    Red: 0\n    Blue: 1\n  }\n}",
+            "goblin: {\n  color: \"Red\"\n}\norc: {\n  color: \"Green\"\n}\nelf: {\n  color: 1\n}",
+            &["`orc.color`: \"Green\" isn't a member of !enum{Red, Blue}"],
+        );
+    }
+
+    #[test]
+    fn kind_mismatches() {
+        check(
+            "$unit: {\n  name: \"gob\"\n  stats: {\n    hp: 1\n  }\n}",
+            "goblin: {\n  name: 4\n  stats: 3\n}",
+            &[
+                "`goblin.name` should be a string, not an int",
+                "`goblin.stats` should be a struct, not an int",
+            ],
+        );
+    }
+}
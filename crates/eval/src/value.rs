@@ -0,0 +1,320 @@
+use crate::disjunction::{resolve_defaults, Alternative};
+use crate::{Constraint, Enum, Member, Scalar};
+use smol_str::SmolStr;
+use std::fmt;
+use text_size::TextRange;
+
+/// TL;DR: The concrete result of evaluating a HIR expression or record.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Int(i64),
+    Float(f64),
+    String(SmolStr),
+    List(Vec<Value>),
+    /// Fields are kept in declaration order.
+    Struct(Vec<(SmolStr, Value)>),
+    /// An incomplete number, narrowed down by every constraint it was unified with.
+    Constraint(Vec<Constraint>),
+    /// Any value of a built-in type, like `!int`. Number types can be narrowed
+    /// by constraints too, as in `!int & >=0`.
+    Type(Scalar, Vec<Constraint>),
+    /// `!enum{ ... }`, which takes one of its members.
+    Enum(Enum),
+    /// A member of an enum, or a combination of its flags.
+    Member(Member),
+    /// `a | b`, holding every alternative that hasn't been ruled out yet.
+    Disjunction(Vec<Alternative>),
+    /// A struct field declared `name?:`, which can be left out. Unifying it
+    /// with a value that isn't optional makes it an ordinary field.
+    Optional(Box<Value>),
+    /// `_|_`, with a reason explaining how we got here, and where in the
+    /// source it happened when that's known.
+    Bottom(SmolStr, Option<TextRange>),
+}
+
+impl Value {
+    pub fn bottom(reason: impl Into<SmolStr>) -> Self {
+        Self::Bottom(reason.into(), None)
+    }
+
+    /// A `_|_` caused by what's written at `range`.
+    pub fn bottom_at(reason: impl Into<SmolStr>, range: TextRange) -> Self {
+        Self::Bottom(reason.into(), Some(range))
+    }
+
+    pub fn is_bottom(&self) -> bool {
+        matches!(self, Self::Bottom(..))
+    }
+
+    pub fn kind_name(&self) -> &'static str {
+        match self {
+            Self::Int(_) => "int",
+            Self::Float(_) => "float",
+            Self::String(_) => "string",
+            Self::List(_) => "list",
+            Self::Struct(_) => "struct",
+            Self::Constraint(_) => "constraint",
+            Self::Type(scalar, _) => scalar.name(),
+            Self::Enum(_) | Self::Member(_) => "enum",
+            Self::Disjunction(_) => "disjunction",
+            Self::Optional(value) => value.kind_name(),
+            Self::Bottom(..) => "bottom",
+        }
+    }
+
+    /// Looks up a field by name, if this value is a struct.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        match self {
+            Self::Struct(fields) => fields
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// What a struct field holds, or `None` for an optional field that was
+    /// never given a single value, which is as good as not being there.
+    pub fn provided(&self) -> Option<&Value> {
+        match self {
+            Self::Optional(value) if value.is_concrete() => Some(value),
+            Self::Optional(_) => None,
+            value => Some(value),
+        }
+    }
+
+    /// Whether this is one value all the way down, with no constraints,
+    /// disjunctions or `_|_` left in it.
+    pub fn is_concrete(&self) -> bool {
+        match self {
+            Self::Int(_) | Self::Float(_) | Self::String(_) | Self::Member(_) => true,
+            Self::List(items) => items.iter().all(Value::is_concrete),
+            Self::Struct(fields) => fields.iter().all(|(_, value)| value.provided().is_none_or(Value::is_concrete)),
+            Self::Optional(value) => value.is_concrete(),
+            Self::Constraint(_) | Self::Type(..) | Self::Enum(_) | Self::Disjunction(_) | Self::Bottom(..) => false,
+        }
+    }
+
+    /// Picks the default alternative of every disjunction that has exactly
+    /// one, leaving ambiguous disjunctions as they are.
+    pub fn resolved(self) -> Value {
+        resolve_defaults(self)
+    }
+
+    /// The same value without any of the places in the source it came from,
+    /// for using it in a module those places aren't in.
+    pub fn unlocated(self) -> Value {
+        let unlocated = |constraints: Vec<Constraint>| {
+            constraints
+                .into_iter()
+                .map(|constraint| Constraint { range: None, ..constraint })
+                .collect()
+        };
+
+        match self {
+            Self::List(items) => Self::List(items.into_iter().map(Value::unlocated).collect()),
+            Self::Struct(fields) => {
+                Self::Struct(fields.into_iter().map(|(name, value)| (name, value.unlocated())).collect())
+            }
+            Self::Constraint(constraints) => Self::Constraint(unlocated(constraints)),
+            Self::Type(scalar, constraints) => Self::Type(scalar, unlocated(constraints)),
+            Self::Disjunction(alternatives) => Self::Disjunction(
+                alternatives
+                    .into_iter()
+                    .map(|alternative| Alternative::new(alternative.value.unlocated(), alternative.is_default))
+                    .collect(),
+            ),
+            Self::Optional(value) => Self::Optional(Box::new(value.unlocated())),
+            Self::Bottom(reason, _) => Self::Bottom(reason, None),
+            value @ (Self::Int(_) | Self::Float(_) | Self::String(_) | Self::Enum(_) | Self::Member(_)) => value,
+        }
+    }
+
+    /// Every `_|_` in the value, with the path of fields and list indices
+    /// that leads to it, like `units.goblin[2]`, and where it happened.
+    pub fn bottoms(&self) -> Vec<(String, &SmolStr, Option<TextRange>)> {
+        let mut out = Vec::new();
+        collect_bottoms(self, String::new(), &mut out);
+        out
+    }
+}
+
+fn collect_bottoms<'a>(
+    value: &'a Value,
+    path: String,
+    out: &mut Vec<(String, &'a SmolStr, Option<TextRange>)>,
+) {
+    match value {
+        Value::Bottom(reason, range) => out.push((path, reason, *range)),
+        Value::Struct(fields) => {
+            for (name, value) in fields {
+                let path = if path.is_empty() {
+                    name.to_string()
+                } else {
+                    format!("{}.{}", path, name)
+                };
+                collect_bottoms(value, path, out);
+            }
+        }
+        Value::List(items) => {
+            for (idx, item) in items.iter().enumerate() {
+                collect_bottoms(item, format!("{}[{}]", path, idx), out);
+            }
+        }
+        Value::Optional(value) => collect_bottoms(value, path, out),
+        // Failed alternatives have already been dropped from disjunctions.
+        Value::Int(_)
+        | Value::Float(_)
+        | Value::String(_)
+        | Value::Constraint(_)
+        | Value::Type(..)
+        | Value::Enum(_)
+        | Value::Member(_)
+        | Value::Disjunction(_) => {}
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Int(n) => write!(f, "{}", n),
+            Self::Float(n) => write!(f, "{:?}", n),
+            Self::String(s) => write!(f, "{:?}", s.as_str()),
+            Self::List(items) => {
+                write!(f, "[")?;
+                for (idx, item) in items.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", item)?;
+                }
+                write!(f, "]")
+            }
+            Self::Struct(fields) => {
+                write!(f, "{{")?;
+                for (idx, (name, value)) in fields.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    match value {
+                        Self::Optional(value) => write!(f, " {}?: {}", name, value)?,
+                        value => write!(f, " {}: {}", name, value)?,
+                    }
+                }
+                if fields.is_empty() {
+                    write!(f, "}}")
+                } else {
+                    write!(f, " }}")
+                }
+            }
+            // No bounds at all, so any number will do.
+            Self::Constraint(constraints) if constraints.is_empty() => write!(f, "..."),
+            Self::Constraint(constraints) => {
+                for (idx, constraint) in constraints.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, " & ")?;
+                    }
+                    write!(f, "{}", constraint)?;
+                }
+                Ok(())
+            }
+            Self::Type(scalar, constraints) => {
+                write!(f, "{}", scalar)?;
+                for constraint in constraints {
+                    write!(f, " & {}", constraint)?;
+                }
+                Ok(())
+            }
+            Self::Enum(ty) => write!(f, "{}", ty),
+            Self::Member(member) => write!(f, "{}", member.name),
+            Self::Disjunction(alternatives) => {
+                for (idx, alternative) in alternatives.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, " | ")?;
+                    }
+                    if alternative.is_default {
+                        write!(f, "*")?;
+                    }
+                    write!(f, "{}", alternative.value)?;
+                }
+                Ok(())
+            }
+            Self::Optional(value) => write!(f, "{}", value),
+            Self::Bottom(reason, _) => write!(f, "_|_ // {}", reason),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Bound;
+
+    #[test]
+    fn display_nested_struct() {
+        let value = Value::Struct(vec![
+            ("name".into(), Value::String("jimbo".into())),
+            ("stats".into(), Value::List(vec![Value::Int(1), Value::Float(2.5)])),
+        ]);
+
+        assert_eq!(
+            value.to_string(),
+            r#"{ name: "jimbo", stats: [1, 2.5] }"#
+        );
+    }
+
+    #[test]
+    fn display_constraint() {
+        let value = Value::Constraint(vec![Bound::GrEq(0.0).into(), Bound::Ls(10.0).into()]);
+        assert_eq!(value.to_string(), ">=0 & <10");
+    }
+
+    #[test]
+    fn display_type() {
+        assert_eq!(Value::Type(Scalar::String, Vec::new()).to_string(), "!string");
+        assert_eq!(
+            Value::Type(Scalar::Int, vec![Bound::GrEq(0.0).into()]).to_string(),
+            "!int & >=0"
+        );
+    }
+
+    #[test]
+    fn display_disjunction() {
+        let value = Value::Disjunction(vec![
+            Alternative::new(Value::String("A".into()), true),
+            Alternative::new(Value::String("B".into()), false),
+        ]);
+        assert_eq!(value.to_string(), r#"*"A" | "B""#);
+    }
+
+    #[test]
+    fn bottoms_with_paths() {
+        let value = Value::Struct(vec![
+            ("a".into(), Value::Int(1)),
+            (
+                "b".into(),
+                Value::Struct(vec![(
+                    "c".into(),
+                    Value::List(vec![
+                        Value::Int(2),
+                        Value::bottom_at("division by zero", TextRange::new(3.into(), 8.into())),
+                    ]),
+                )]),
+            ),
+        ]);
+
+        let bottoms = value.bottoms();
+        assert_eq!(bottoms.len(), 1);
+        assert_eq!(bottoms[0].0, "b.c[1]");
+        assert_eq!(bottoms[0].1, "division by zero");
+        assert_eq!(bottoms[0].2, Some(TextRange::new(3.into(), 8.into())));
+    }
+
+    #[test]
+    fn display_bottom() {
+        assert_eq!(
+            Value::bottom("division by zero").to_string(),
+            "_|_ // division by zero"
+        );
+    }
+}
@@ -0,0 +1,188 @@
+use crate::{field, index, provided, quoted, unexported, ExportError};
+use eval::Value;
+
+const INDENT: &str = "  ";
+
+/// Writes a value as pretty-printed JSON, or finds the first part of it that
+/// isn't complete.
+pub fn json(value: &Value) -> Result<String, ExportError> {
+    let mut out = String::new();
+    write(value, "", 0, &mut out)?;
+    out.push('\n');
+    Ok(out)
+}
+
+fn write(value: &Value, path: &str, depth: usize, out: &mut String) -> Result<(), ExportError> {
+    match value {
+        Value::Int(n) => out.push_str(&n.to_string()),
+        Value::Float(n) if n.is_finite() => out.push_str(&format!("{:?}", n)),
+        Value::String(s) => out.push_str(&quoted(s)),
+        Value::Member(member) => out.push_str(&quoted(&member.name)),
+        Value::List(items) if items.is_empty() => out.push_str("[]"),
+        Value::List(items) => {
+            out.push('[');
+            for (idx, item) in items.iter().enumerate() {
+                member(idx, depth, out);
+                write(item, &index(path, idx), depth + 1, out)?;
+            }
+            close(']', depth, out);
+        }
+        Value::Struct(fields) => {
+            let fields = provided(fields);
+            if fields.is_empty() {
+                out.push_str("{}");
+                return Ok(());
+            }
+
+            out.push('{');
+            for (idx, (name, value)) in fields.into_iter().enumerate() {
+                member(idx, depth, out);
+                out.push_str(&quoted(name));
+                out.push_str(": ");
+                write(value, &field(path, name), depth + 1, out)?;
+            }
+            close('}', depth, out);
+        }
+        Value::Float(_)
+        | Value::Constraint(_)
+        | Value::Type(..)
+        | Value::Enum(_)
+        | Value::Disjunction(_)
+        | Value::Optional(_)
+        | Value::Bottom(..) => {
+            return Err(unexported(value, path))
+        }
+    }
+    Ok(())
+}
+
+/// Starts the line of a list item or struct field.
+fn member(idx: usize, depth: usize, out: &mut String) {
+    if idx > 0 {
+        out.push(',');
+    }
+    out.push('\n');
+    out.push_str(&INDENT.repeat(depth + 1));
+}
+
+fn close(bracket: char, depth: usize, out: &mut String) {
+    out.push('\n');
+    out.push_str(&INDENT.repeat(depth));
+    out.push(bracket);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{export, Enums, Format};
+    use eval::{Bound, Constraint, Member};
+
+    fn fields(fields: Vec<(&str, Value)>) -> Value {
+        Value::Struct(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.into(), value))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn keeps_field_order() {
+        let value = fields(vec![
+            ("zed", Value::Int(1)),
+            ("alpha", Value::Float(2.5)),
+            ("name", Value::String("jimbo".into())),
+        ]);
+        assert_eq!(
+            json(&value).unwrap(),
+            "{\n  \"zed\": 1,\n  \"alpha\": 2.5,\n  \"name\": \"jimbo\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn nests_lists_and_structs() {
+        let value = fields(vec![
+            (
+                "goblin",
+                fields(vec![
+                    ("tags", Value::List(vec![Value::String("small".into()), Value::Int(3)])),
+                    ("empty", Value::List(vec![])),
+                ]),
+            ),
+            ("nothing", fields(vec![])),
+        ]);
+        assert_eq!(
+            json(&value).unwrap(),
+            "{\n  \"goblin\": {\n    \"tags\": [\n      \"small\",\n      3\n    ],\n    \"empty\": []\n  },\n  \"nothing\": {}\n}\n"
+        );
+    }
+
+    #[test]
+    fn escapes_strings() {
+        let value = fields(vec![("quote", Value::String("say \"hi\"\\\n\u{1}".into()))]);
+        assert_eq!(
+            json(&value).unwrap(),
+            "{\n  \"quote\": \"say \\\"hi\\\"\\\\\\n\\u0001\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn refuses_bottom() {
+        let value = fields(vec![(
+            "units",
+            fields(vec![(
+                "goblin",
+                Value::List(vec![Value::Int(1), Value::bottom("division by zero")]),
+            )]),
+        )]);
+        let error = json(&value).unwrap_err();
+        assert_eq!(
+            error,
+            ExportError::Bottom {
+                path: String::from("units.goblin[1]"),
+                reason: "division by zero".into(),
+            }
+        );
+        assert_eq!(error.to_string(), "`units.goblin[1]` is _|_: division by zero");
+    }
+
+    #[test]
+    fn refuses_incomplete_values() {
+        let value = fields(vec![("hp", Value::Constraint(vec![Constraint::from(Bound::GrEq(0.0))]))]);
+        assert_eq!(
+            json(&value).unwrap_err().to_string(),
+            "`hp` is incomplete: `>=0` isn't a single value"
+        );
+        assert!(json(&fields(vec![("x", Value::Float(f64::INFINITY))])).is_err());
+    }
+
+    #[test]
+    fn leaves_out_optional_fields_without_values() {
+        let optional = |value| Value::Optional(Box::new(value));
+        let value = fields(vec![
+            ("hp", Value::Int(5)),
+            ("name", optional(Value::Constraint(vec![Constraint::from(Bound::GrEq(0.0))]))),
+            ("speed", optional(Value::Int(3))),
+            ("stats", fields(vec![("str", optional(Value::bottom("nope")))])),
+        ]);
+        assert_eq!(
+            json(&value).unwrap(),
+            "{\n  \"hp\": 5,\n  \"speed\": 3,\n  \"stats\": {}\n}\n"
+        );
+    }
+
+    #[test]
+    fn writes_enum_members_by_name_or_value() {
+        let tag = Value::Member(Member {
+            name: "A|C".into(),
+            value: 5,
+        });
+        let value = fields(vec![("tags", Value::List(vec![tag]))]);
+
+        assert_eq!(json(&value).unwrap(), "{\n  \"tags\": [\n    \"A|C\"\n  ]\n}\n");
+        assert_eq!(
+            export(&value, Format::Json, Enums::Values).unwrap(),
+            "{\n  \"tags\": [\n    5\n  ]\n}\n"
+        );
+    }
+}
@@ -0,0 +1,184 @@
+/*
+TL;DR: Evaluated values out to JSON, YAML and TOML.
+
+    $unit: { hp: >=0 }
+    goblin: $unit & {
+      hp: 5
+      name: "gob"
+    }
+
+exports as
+
+    {                          goblin:                  [goblin]
+      "goblin": {                hp: 5                  hp = 5
+        "hp": 5,                 name: gob              name = "gob"
+        "name": "gob"
+      }
+    }
+
+Fields keep the order they were declared in. Schemas aren't data, so they're
+left out, which `eval::output` already takes care of. Anything that isn't one
+concrete value, like `_|_`, `>=0` or an ambiguous `a | b`, stops the export
+rather than being written out as something it isn't. The exception is an
+optional field, `name?:`, that never got a value, which is just left out.
+
+Enum members are written by name, like `"A|C"`, or as the ints they stand
+for, depending on `Enums`.
+
+TOML is the pickiest of the three. A document has to be a table, arrays can't
+mix kinds of values, and a table's plain values have to come before its
+sub-tables, so those are moved up. Lists of structs become arrays of tables,
+`[[goblin.attacks]]`, except inside other arrays, where they're inline.
+*/
+
+mod json;
+mod toml;
+mod yaml;
+
+pub use json::json;
+pub use toml::toml;
+pub use yaml::yaml;
+
+use eval::Value;
+use smol_str::SmolStr;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+    Toml,
+}
+
+/// How enum members are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Enums {
+    /// As strings, like `"A|C"`, which is what the writers do by themselves.
+    #[default]
+    Names,
+    /// As the ints they stand for, like `5`.
+    Values,
+}
+
+/// Writes a value in the given format.
+pub fn export(value: &Value, format: Format, enums: Enums) -> Result<String, ExportError> {
+    let value = &members(value, enums);
+    match format {
+        Format::Json => json(value),
+        Format::Yaml => yaml(value),
+        Format::Toml => toml(value),
+    }
+}
+
+/// Swaps every enum member for its name or its value, so TOML sees them as
+/// the strings or ints they'll be written as.
+fn members(value: &Value, enums: Enums) -> Value {
+    match value {
+        Value::Member(member) => match enums {
+            Enums::Names => Value::String(member.name.clone()),
+            // Lowering keeps members small enough to be ints.
+            Enums::Values => Value::Int(member.value as i64),
+        },
+        Value::List(items) => Value::List(items.iter().map(|item| members(item, enums)).collect()),
+        Value::Struct(fields) => Value::Struct(
+            fields
+                .iter()
+                .map(|(name, value)| (name.clone(), members(value, enums)))
+                .collect(),
+        ),
+        Value::Optional(value) => Value::Optional(Box::new(members(value, enums))),
+        value => value.clone(),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExportError {
+    /// A value that failed to evaluate.
+    Bottom { path: String, reason: SmolStr },
+    /// A constraint or disjunction that hasn't been narrowed down to one value.
+    Incomplete { path: String, value: String },
+    /// A value the format has no way of writing.
+    Unrepresentable { path: String, reason: String },
+}
+
+impl fmt::Display for ExportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Bottom { path, reason } => write!(f, "{} is _|_: {}", at(path), reason),
+            Self::Incomplete { path, value } => {
+                write!(f, "{} is incomplete: `{}` isn't a single value", at(path), value)
+            }
+            Self::Unrepresentable { path, reason } => write!(f, "{} can't be exported: {}", at(path), reason),
+        }
+    }
+}
+
+impl std::error::Error for ExportError {}
+
+fn at(path: &str) -> String {
+    if path.is_empty() {
+        String::from("the top level")
+    } else {
+        format!("`{}`", path)
+    }
+}
+
+/// Why a value that isn't a plain int, float, string, list or struct can't
+/// be exported.
+fn unexported(value: &Value, path: &str) -> ExportError {
+    let path = path.to_string();
+    match value {
+        Value::Bottom(reason, _) => ExportError::Bottom {
+            path,
+            reason: reason.clone(),
+        },
+        Value::Float(n) => ExportError::Unrepresentable {
+            path,
+            reason: format!("`{}` isn't a finite number", n),
+        },
+        _ => ExportError::Incomplete {
+            path,
+            value: value.to_string(),
+        },
+    }
+}
+
+/// A double quoted string. JSON, YAML and TOML all read these escapes the
+/// same way.
+fn quoted(s: &str) -> String {
+    let mut out = String::from('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// A struct's fields, bar the optional ones that were never given a value.
+fn provided(fields: &[(SmolStr, Value)]) -> Vec<(&SmolStr, &Value)> {
+    fields
+        .iter()
+        .filter_map(|(name, value)| Some((name, value.provided()?)))
+        .collect()
+}
+
+/// The path to a field, written like `Value::bottoms` writes them.
+fn field(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_string()
+    } else {
+        format!("{}.{}", path, name)
+    }
+}
+
+fn index(path: &str, idx: usize) -> String {
+    format!("{}[{}]", path, idx)
+}
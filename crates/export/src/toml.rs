@@ -0,0 +1,219 @@
+use crate::{field, index, provided, quoted, unexported, ExportError};
+use eval::Value;
+use smol_str::SmolStr;
+
+/// Writes a struct as a TOML document, or finds the first part of it that's
+/// incomplete or that TOML can't hold.
+pub fn toml(value: &Value) -> Result<String, ExportError> {
+    let fields = match value {
+        Value::Struct(fields) => fields,
+        _ => {
+            return Err(ExportError::Unrepresentable {
+                path: String::new(),
+                reason: format!("TOML documents are tables, not {}s", value.kind_name()),
+            })
+        }
+    };
+
+    let mut out = String::new();
+    table(&provided(fields), "", &[], &mut out)?;
+    Ok(out)
+}
+
+/// Writes the plain values of a table under whatever header's already been
+/// written, then each of its sub-tables under their own headers.
+fn table(fields: &[(&SmolStr, &Value)], path: &str, keys: &[&str], out: &mut String) -> Result<(), ExportError> {
+    // Anything after the next header belongs to that header's table, so the
+    // plain values go first.
+    for &(name, value) in fields {
+        if !is_table(value) && !is_table_array(value) {
+            out.push_str(&format!("{} = {}\n", key(name), inline(value, &field(path, name))?));
+        }
+    }
+
+    for &(name, value) in fields {
+        let path = field(path, name);
+        let keys = [keys, &[name.as_str()]].concat();
+        match value {
+            Value::Struct(fields) => {
+                header(&format!("[{}]", dotted(&keys)), out);
+                table(&provided(fields), &path, &keys, out)?;
+            }
+            Value::List(items) if is_table_array(value) => {
+                for (idx, item) in items.iter().enumerate() {
+                    if let Value::Struct(fields) = item {
+                        header(&format!("[[{}]]", dotted(&keys)), out);
+                        table(&provided(fields), &index(&path, idx), &keys, out)?;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn header(header: &str, out: &mut String) {
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out.push_str(header);
+    out.push('\n');
+}
+
+fn is_table(value: &Value) -> bool {
+    matches!(value, Value::Struct(_))
+}
+
+/// A list of structs, which gets written as a `[[header]]` per struct.
+fn is_table_array(value: &Value) -> bool {
+    match value {
+        Value::List(items) => !items.is_empty() && items.iter().all(is_table),
+        _ => false,
+    }
+}
+
+/// A value on the right of an `=`, or inside an array.
+fn inline(value: &Value, path: &str) -> Result<String, ExportError> {
+    Ok(match value {
+        Value::Int(n) => n.to_string(),
+        Value::Float(n) if n.is_nan() => String::from("nan"),
+        Value::Float(n) if n.is_infinite() => String::from(if *n > 0.0 { "inf" } else { "-inf" }),
+        Value::Float(n) => format!("{:?}", n),
+        Value::String(s) => quoted(s),
+        Value::Member(member) => quoted(&member.name),
+        Value::List(items) => {
+            let mut written = Vec::new();
+            for (idx, item) in items.iter().enumerate() {
+                written.push(inline(item, &index(path, idx))?);
+                if item.kind_name() != items[0].kind_name() {
+                    return Err(ExportError::Unrepresentable {
+                        path: path.to_string(),
+                        reason: format!(
+                            "TOML arrays can't mix kinds of values, like the {} and {} here",
+                            items[0].kind_name(),
+                            item.kind_name()
+                        ),
+                    });
+                }
+            }
+            format!("[{}]", written.join(", "))
+        }
+        Value::Struct(fields) if provided(fields).is_empty() => String::from("{}"),
+        Value::Struct(fields) => {
+            let mut written = Vec::new();
+            for (name, value) in provided(fields) {
+                written.push(format!("{} = {}", key(name), inline(value, &field(path, name))?));
+            }
+            format!("{{ {} }}", written.join(", "))
+        }
+        Value::Constraint(_)
+        | Value::Type(..)
+        | Value::Enum(_)
+        | Value::Disjunction(_)
+        | Value::Optional(_)
+        | Value::Bottom(..) => {
+            return Err(unexported(value, path))
+        }
+    })
+}
+
+fn dotted(keys: &[&str]) -> String {
+    keys.iter().map(|name| key(name)).collect::<Vec<String>>().join(".")
+}
+
+/// A key bare if TOML allows it, otherwise quoted.
+fn key(name: &str) -> String {
+    if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        name.to_string()
+    } else {
+        quoted(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(fields: Vec<(&str, Value)>) -> Value {
+        Value::Struct(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.into(), value))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn nests_tables() {
+        let value = fields(vec![
+            ("title", Value::String("units".into())),
+            (
+                "goblin",
+                fields(vec![
+                    ("stats", fields(vec![("hp", Value::Int(5))])),
+                    ("speed", Value::Float(1.5)),
+                    ("tags", Value::List(vec![Value::String("small".into())])),
+                ]),
+            ),
+            ("version", Value::Int(2)),
+        ]);
+        assert_eq!(
+            toml(&value).unwrap(),
+            "title = \"units\"\nversion = 2\n\n[goblin]\nspeed = 1.5\ntags = [\"small\"]\n\n[goblin.stats]\nhp = 5\n"
+        );
+    }
+
+    #[test]
+    fn lists_of_structs_are_arrays_of_tables() {
+        let value = fields(vec![(
+            "attacks",
+            Value::List(vec![
+                fields(vec![("name", Value::String("bite".into())), ("on hit", fields(vec![("dmg", Value::Int(2))]))]),
+                fields(vec![("name", Value::String("claw".into()))]),
+            ]),
+        )]);
+        assert_eq!(
+            toml(&value).unwrap(),
+            "[[attacks]]\nname = \"bite\"\n\n[attacks.\"on hit\"]\ndmg = 2\n\n[[attacks]]\nname = \"claw\"\n"
+        );
+    }
+
+    #[test]
+    fn structs_inside_arrays_are_inline() {
+        let value = fields(vec![(
+            "grid",
+            Value::List(vec![Value::List(vec![fields(vec![("x", Value::Int(1))]), fields(vec![])])]),
+        )]);
+        assert_eq!(toml(&value).unwrap(), "grid = [[{ x = 1 }, {}]]\n");
+    }
+
+    #[test]
+    fn refuses_heterogeneous_arrays() {
+        let value = fields(vec![(
+            "goblin",
+            fields(vec![("mixed", Value::List(vec![Value::Int(1), Value::String("two".into())]))]),
+        )]);
+        assert_eq!(
+            toml(&value).unwrap_err().to_string(),
+            "`goblin.mixed` can't be exported: TOML arrays can't mix kinds of values, like the int and string here"
+        );
+
+        let value = fields(vec![("mixed", Value::List(vec![fields(vec![]), Value::Int(1)]))]);
+        assert!(toml(&value).is_err());
+    }
+
+    #[test]
+    fn refuses_top_level_scalars() {
+        assert_eq!(
+            toml(&Value::Int(3)).unwrap_err().to_string(),
+            "the top level can't be exported: TOML documents are tables, not ints"
+        );
+    }
+
+    #[test]
+    fn refuses_bottom() {
+        let value = fields(vec![("a", Value::List(vec![Value::bottom("division by zero")]))]);
+        assert_eq!(toml(&value).unwrap_err().to_string(), "`a[0]` is _|_: division by zero");
+    }
+}
@@ -0,0 +1,181 @@
+use crate::{field, index, provided, quoted, unexported, ExportError};
+use eval::Value;
+
+const INDENT: &str = "  ";
+
+/// Words YAML reads as something other than a string when they're unquoted.
+const RESERVED: [&str; 9] = ["true", "false", "yes", "no", "on", "off", "null", "y", "n"];
+
+/// Writes a value as block style YAML, or finds the first part of it that
+/// isn't complete.
+pub fn yaml(value: &Value) -> Result<String, ExportError> {
+    let mut out = String::new();
+    if is_block(value) {
+        block(value, "", 0, false, &mut out)?;
+    } else {
+        out.push_str(&scalar(value, "")?);
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+/// Writes a struct or list a line per member. With `inline`, the first line
+/// carries on from a `- ` that's already been written.
+fn block(value: &Value, path: &str, depth: usize, inline: bool, out: &mut String) -> Result<(), ExportError> {
+    let members: Vec<(String, String, &Value)> = match value {
+        Value::Struct(fields) => provided(fields)
+            .into_iter()
+            .map(|(name, value)| (format!("{}:", key(name)), field(path, name), value))
+            .collect(),
+        Value::List(items) => items
+            .iter()
+            .enumerate()
+            .map(|(idx, item)| (String::from("-"), index(path, idx), item))
+            .collect(),
+        _ => unreachable!("only structs and lists are written as blocks"),
+    };
+
+    for (idx, (lead, path, value)) in members.into_iter().enumerate() {
+        if idx > 0 || !inline {
+            out.push_str(&INDENT.repeat(depth));
+        }
+        out.push_str(&lead);
+
+        match value {
+            // Whatever's under a `-` starts on the same line as it.
+            _ if is_block(value) && lead == "-" => {
+                out.push(' ');
+                block(value, &path, depth + 1, true, out)?;
+            }
+            _ if is_block(value) => {
+                out.push('\n');
+                block(value, &path, depth + 1, false, out)?;
+            }
+            _ => {
+                out.push(' ');
+                out.push_str(&scalar(value, &path)?);
+                out.push('\n');
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_block(value: &Value) -> bool {
+    match value {
+        Value::Struct(fields) => !provided(fields).is_empty(),
+        Value::List(items) => !items.is_empty(),
+        _ => false,
+    }
+}
+
+fn scalar(value: &Value, path: &str) -> Result<String, ExportError> {
+    Ok(match value {
+        Value::Int(n) => n.to_string(),
+        Value::Float(n) if n.is_nan() => String::from(".nan"),
+        Value::Float(n) if n.is_infinite() => String::from(if *n > 0.0 { ".inf" } else { "-.inf" }),
+        Value::Float(n) => format!("{:?}", n),
+        Value::String(s) => key(s),
+        Value::Member(member) => key(&member.name),
+        Value::List(_) => String::from("[]"),
+        Value::Struct(_) => String::from("{}"),
+        Value::Constraint(_)
+        | Value::Type(..)
+        | Value::Enum(_)
+        | Value::Disjunction(_)
+        | Value::Optional(_)
+        | Value::Bottom(..) => {
+            return Err(unexported(value, path))
+        }
+    })
+}
+
+/// A string as is if YAML would read it back as the same string, otherwise
+/// quoted.
+fn key(s: &str) -> String {
+    let plain = s.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ' '))
+        && !s.ends_with(' ')
+        && !RESERVED.contains(&s.to_ascii_lowercase().as_str());
+
+    if plain {
+        s.to_string()
+    } else {
+        quoted(s)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eval::{Bound, Constraint};
+
+    fn fields(fields: Vec<(&str, Value)>) -> Value {
+        Value::Struct(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.into(), value))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn nests_structs_and_lists() {
+        let value = fields(vec![
+            (
+                "goblin",
+                fields(vec![
+                    ("hp", Value::Int(5)),
+                    ("speed", Value::Float(1.5)),
+                    ("tags", Value::List(vec![Value::String("small".into())])),
+                    ("loot", Value::List(vec![])),
+                ]),
+            ),
+            ("empty", fields(vec![])),
+        ]);
+        assert_eq!(
+            yaml(&value).unwrap(),
+            "goblin:\n  hp: 5\n  speed: 1.5\n  tags:\n    - small\n  loot: []\nempty: {}\n"
+        );
+    }
+
+    #[test]
+    fn lists_of_structs_and_lists() {
+        let value = fields(vec![(
+            "attacks",
+            Value::List(vec![
+                fields(vec![("name", Value::String("bite".into())), ("dmg", Value::Int(2))]),
+                Value::List(vec![Value::Int(1), Value::Int(2)]),
+            ]),
+        )]);
+        assert_eq!(
+            yaml(&value).unwrap(),
+            "attacks:\n  - name: bite\n    dmg: 2\n  - - 1\n    - 2\n"
+        );
+    }
+
+    #[test]
+    fn quotes_strings_that_would_read_back_differently() {
+        let value = fields(vec![
+            ("plain", Value::String("green goblin".into())),
+            ("word", Value::String("yes".into())),
+            ("number", Value::String("12".into())),
+            ("colon", Value::String("a: b".into())),
+            ("odd key!", Value::String("".into())),
+        ]);
+        assert_eq!(
+            yaml(&value).unwrap(),
+            "plain: green goblin\nword: \"yes\"\nnumber: \"12\"\ncolon: \"a: b\"\n\"odd key!\": \"\"\n"
+        );
+    }
+
+    #[test]
+    fn refuses_incomplete_values() {
+        let value = fields(vec![("hp", Value::Constraint(vec![Constraint::from(Bound::GrEq(0.0))]))]);
+        assert_eq!(
+            yaml(&value).unwrap_err().to_string(),
+            "`hp` is incomplete: `>=0` isn't a single value"
+        );
+        assert_eq!(yaml(&Value::Int(3)).unwrap(), "3\n");
+    }
+}
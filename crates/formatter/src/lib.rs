@@ -0,0 +1,161 @@
+/*
+TL;DR: The canonical layout of a Vada file, which `vada fmt` writes.
+
+    a : 1
+    outerRecord:{name:"jimbo"innerRecord:{age:12}} // hi
+
+becomes
+
+    a: 1
+    outerRecord: {
+      name: "jimbo"
+      innerRecord: {age: 12}
+    } // hi
+
+One space after `:`, `,` and `;` and around binary operators, none inside
+brackets or after prefixes like `$` and `-`. A struct with more than one
+member, or a line break inside, gets a member per line indented two spaces,
+and so do the bodies of sections. Line breaks inside expressions and
+comments stay where they are, blank lines are squeezed to one, and lines
+longer than 80 columns are broken before each `~>`.
+
+Formatting never changes what a file parses to, and formatting twice changes
+nothing the second time. Where either would fail, the file is left as it
+was, bar tabs and carriage returns, which Vada doesn't count as whitespace.
+*/
+
+mod print;
+
+use lexer::{Lexer, TokenKind};
+use rowan::{NodeOrToken, WalkEvent};
+use syntax::SyntaxKind;
+
+pub fn format(text: &str) -> String {
+    let text = untab(text);
+    let formatted = print::print(&parser::parse(&text).syntax());
+
+    // Broken files can parse the same and still lay out differently the
+    // second time round, so those are left alone too.
+    if shape(&formatted) == shape(&text) && print::print(&parser::parse(&formatted).syntax()) == formatted {
+        formatted
+    } else {
+        text
+    }
+}
+
+/// Swaps tabs for spaces and drops carriage returns, outside strings and
+/// comments.
+fn untab(text: &str) -> String {
+    Lexer::new(text)
+        .map(|token| {
+            if token.kind == TokenKind::Error && token.text.chars().all(|c| c == '\t' || c == '\r') {
+                token.text.replace('\t', " ").replace('\r', "")
+            } else {
+                token.text.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Everything formatting mustn't change: the nodes, the tokens that aren't
+/// whitespace, and the tokens errors were found at.
+fn shape(text: &str) -> (Vec<String>, Vec<String>) {
+    let parse = parser::parse(text);
+
+    let tree = parse
+        .syntax()
+        .preorder_with_tokens()
+        .filter_map(|event| match event {
+            WalkEvent::Enter(NodeOrToken::Node(node)) => Some(format!("{:?}", node.kind())),
+            WalkEvent::Leave(NodeOrToken::Node(_)) => Some(String::from(")")),
+            WalkEvent::Enter(NodeOrToken::Token(token)) if token.kind() != SyntaxKind::Whitespace => {
+                Some(format!("{:?} {}", token.kind(), token.text().trim_end()))
+            }
+            _ => None,
+        })
+        .collect();
+
+    let errors = parse
+        .errors()
+        .iter()
+        .map(|error| text[error.range()].to_string())
+        .collect();
+
+    (tree, errors)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(text: &str, expected: &str) {
+        let formatted = format(text);
+        assert_eq!(formatted, expected);
+        assert_eq!(format(&formatted), formatted, "formatting again changed it");
+    }
+
+    #[test]
+    fn format_trims_trailing_whitespace() {
+        assert_eq!(format("a: 1   \nb: 2\t\n\n\n"), "a: 1\nb: 2\n");
+    }
+
+    #[test]
+    fn format_adds_final_newline() {
+        assert_eq!(format("a: 1"), "a: 1\n");
+    }
+
+    #[test]
+    fn normalizes_spacing() {
+        check("a : 1\nb:hp+1*( 2-3 )\n", "a: 1\nb: hp + 1 * (2 - 3)\n");
+        check("l: [1,2 , 3]\nr: $int & 0..300\n", "l: [1, 2, 3]\nr: $int & 0..300\n");
+        check("x:@zalgo.hp\nn:  -10\n", "x: @zalgo.hp\nn: -10\n");
+        check("r: 0..=5\nd: @self.A..=@self.D\n", "r: 0..=5\nd: @self.A..=@self.D\n");
+    }
+
+    #[test]
+    fn breaks_structs_into_lines() {
+        check(
+            "outerRecord:{name:\"jimbo\"innerRecord:{age:12}}",
+            "outerRecord: {\n  name: \"jimbo\"\n  innerRecord: {age: 12}\n}\n",
+        );
+        check(
+            "stats: {hp: 5,\nmax: hp}\n",
+            "stats: {\n  hp: 5,\n  max: hp\n}\n",
+        );
+        check("goblin: $unit & { hp: 5 }\n", "goblin: $unit & {hp: 5}\n");
+    }
+
+    #[test]
+    fn indents_sections() {
+        check("--- #s:\nsec: 1\n---\nb: 2\n", "--- #s:\n  sec: 1\n---\n  b: 2\n");
+    }
+
+    #[test]
+    fn keeps_comments() {
+        check(
+            "// units\n\n\n$unit: {\n// health\n  hp: 5 // at least\n\n\n  max: hp   // or more\n// done\n}\n",
+            "// units\n\n$unit: {\n  // health\n  hp: 5 // at least\n\n  max: hp // or more\n  // done\n}\n",
+        );
+    }
+
+    #[test]
+    fn breaks_long_pipelines() {
+        check(
+            "units: everything ~> filter_by_faction ~> sort_by_hit_points ~> take_the_first_few\n",
+            "units: everything\n  ~> filter_by_faction\n  ~> sort_by_hit_points\n  ~> take_the_first_few\n",
+        );
+        check("units: all ~> sort\n", "units: all ~> sort\n");
+    }
+
+    #[test]
+    fn keeps_line_breaks_in_expressions() {
+        check("a: 1 +\n\n      2\nb: [1,\n2]\n", "a: 1 +\n  2\nb: [1,\n  2]\n");
+    }
+
+    #[test]
+    fn formats_empty_files() {
+        check("", "");
+        check("\n\n", "");
+        check("// just this", "// just this\n");
+    }
+}
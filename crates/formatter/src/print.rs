@@ -0,0 +1,351 @@
+use lexer::Lexer;
+use std::collections::HashMap;
+use syntax::{SyntaxKind, SyntaxNode, SyntaxToken};
+
+const INDENT: &str = "  ";
+const MAX_WIDTH: usize = 80;
+
+/// Nodes that get a line of their own inside the root, a section, or a
+/// struct that's laid out over several lines.
+const MEMBERS: [SyntaxKind; 6] = [
+    SyntaxKind::Record,
+    SyntaxKind::AnonymousRecord,
+    SyntaxKind::InlinedRecord,
+    SyntaxKind::Section,
+    SyntaxKind::Accessor,
+    SyntaxKind::Directive,
+];
+
+/// What goes in front of a piece of output.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Break {
+    None,
+    Space,
+    /// A new line indented `depth` times, after a blank one if `blank`.
+    Line { depth: usize, blank: bool },
+}
+
+/// A token or comment, and what goes in front of it.
+#[derive(Debug)]
+struct Piece {
+    before: Break,
+    text: String,
+    /// For a `~>`, how deep the line it moves to is indented if its line is
+    /// too long.
+    pipe: Option<usize>,
+}
+
+pub(crate) fn print(root: &SyntaxNode) -> String {
+    let mut printer = Printer {
+        text: root.text().to_string(),
+        broken: HashMap::new(),
+    };
+    let pieces = printer.pieces(root);
+    render(pieces)
+}
+
+struct Printer {
+    text: String,
+    /// Whether each struct seen so far is laid out over several lines.
+    broken: HashMap<SyntaxNode, bool>,
+}
+
+impl Printer {
+    fn pieces(&mut self, root: &SyntaxNode) -> Vec<Piece> {
+        let tokens = root
+            .descendants_with_tokens()
+            .filter_map(|element| element.into_token())
+            .collect::<Vec<SyntaxToken>>();
+
+        let mut pieces = Vec::new();
+        let mut prev: Option<&SyntaxToken> = None;
+        let mut newlines = 0;
+        let mut spaced = false;
+        let mut opened = false;
+
+        for (idx, token) in tokens.iter().enumerate() {
+            let before = match token.kind() {
+                SyntaxKind::Whitespace => {
+                    newlines += token.text().matches('\n').count();
+                    spaced = true;
+                    continue;
+                }
+                // Comments on a line of their own are indented like what
+                // follows them, the rest stay at the end of their line.
+                SyntaxKind::Comment => {
+                    let next = tokens[idx + 1..].iter().find(|token| !is_trivia(token.kind()));
+                    if pieces.is_empty() {
+                        Break::None
+                    } else if newlines == 0 {
+                        Break::Space
+                    } else {
+                        Break::Line {
+                            depth: next.map_or(0, |next| self.comment_depth(next)),
+                            blank: newlines > 1 && !opened,
+                        }
+                    }
+                }
+                _ => match prev {
+                    _ if pieces.is_empty() => Break::None,
+                    None => Break::Line {
+                        depth: self.depth(token),
+                        blank: newlines > 1,
+                    },
+                    Some(prev) => self.between(prev, token, newlines, spaced, opened),
+                },
+            };
+
+            let comment = token.kind() == SyntaxKind::Comment;
+            pieces.push(Piece {
+                before,
+                text: if comment { token.text().trim_end().to_string() } else { token.text().to_string() },
+                pipe: self.pipe(token),
+            });
+
+            if !comment {
+                prev = Some(token);
+            }
+            opened = token.kind() == SyntaxKind::LBrace;
+            newlines = 0;
+            spaced = false;
+        }
+
+        pieces
+    }
+
+    /// What goes between two tokens that aren't trivia. Members start lines,
+    /// line breaks that were already there stay, and the rest is spacing.
+    fn between(&mut self, prev: &SyntaxToken, token: &SyntaxToken, newlines: usize, spaced: bool, opened: bool) -> Break {
+        let depth = self.depth(token);
+
+        if self.closes(token) {
+            return Break::Line { depth, blank: false };
+        }
+        if self.starts_member(token) {
+            return Break::Line {
+                depth,
+                blank: newlines > 1 && !opened,
+            };
+        }
+        if newlines > 0 {
+            return Break::Line {
+                depth: depth + 1,
+                blank: false,
+            };
+        }
+
+        if spacing(prev, token, spaced) || !lexes_apart(prev.text(), token.text()) {
+            Break::Space
+        } else {
+            Break::None
+        }
+    }
+
+    /// How many structs and sections laid out over lines hold `token`,
+    /// leaving out the braces and markers that open and close them.
+    fn depth(&mut self, token: &SyntaxToken) -> usize {
+        token
+            .parent()
+            .ancestors()
+            .filter(|node| match node.kind() {
+                SyntaxKind::Struct => self.broken(node) && !own_brace(token, node),
+                SyntaxKind::Section => !own_brace(token, node) && !in_header(token, node),
+                _ => false,
+            })
+            .count()
+    }
+
+    /// A comment on its own line is indented like the member that follows
+    /// it, or like the members before a closing brace.
+    fn comment_depth(&mut self, next: &SyntaxToken) -> usize {
+        let depth = self.depth(next);
+        if self.closes(next) {
+            depth + 1
+        } else if self.starts_member(next) {
+            depth
+        } else {
+            depth + 1
+        }
+    }
+
+    fn starts_member(&mut self, token: &SyntaxToken) -> bool {
+        let mut node = token.parent();
+        while node.first_token().as_ref() == Some(token) {
+            let parent = match node.parent() {
+                Some(parent) => parent,
+                None => return false,
+            };
+
+            let in_lines = match parent.kind() {
+                SyntaxKind::Root | SyntaxKind::Section => true,
+                SyntaxKind::Struct => self.broken(&parent),
+                _ => false,
+            };
+            if in_lines && MEMBERS.contains(&node.kind()) {
+                return true;
+            }
+            node = parent;
+        }
+        false
+    }
+
+    /// Whether `token` is the `}` of a struct or section laid out over lines.
+    fn closes(&mut self, token: &SyntaxToken) -> bool {
+        let parent = token.parent();
+        token.kind() == SyntaxKind::RBrace
+            && match parent.kind() {
+                SyntaxKind::Struct => self.broken(&parent),
+                SyntaxKind::Section => true,
+                _ => false,
+            }
+    }
+
+    /// A struct is laid out over lines if it was already, or if it has more
+    /// than one member.
+    fn broken(&mut self, node: &SyntaxNode) -> bool {
+        if let Some(broken) = self.broken.get(node) {
+            return *broken;
+        }
+
+        let start = node.text_range().start();
+        let end = node
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .find(|token| token.kind() == SyntaxKind::RBrace)
+            .map_or(node.text_range().end(), |token| token.text_range().end());
+        let members = node.children().filter(|child| MEMBERS.contains(&child.kind())).count();
+
+        let broken = self.text[usize::from(start)..usize::from(end)].contains('\n') || members > 1;
+        self.broken.insert(node.clone(), broken);
+        broken
+    }
+
+    fn pipe(&mut self, token: &SyntaxToken) -> Option<usize> {
+        (token.kind() == SyntaxKind::RSquiggleArrow && token.parent().kind() == SyntaxKind::InfixExpr)
+            .then(|| self.depth(token) + 1)
+    }
+}
+
+/// Whether there's a space between two tokens on the same line. Where
+/// nothing here has an opinion, whatever was there stays.
+fn spacing(prev: &SyntaxToken, next: &SyntaxToken, spaced: bool) -> bool {
+    use SyntaxKind::*;
+
+    let (prev_kind, next_kind) = (prev.kind(), next.kind());
+    match (prev_kind, next_kind) {
+        (_, Comma | Semicolon | RParen | RBrack | Colon | QMark) => false,
+        (LParen | LBrack, _) => false,
+        // Structs on one line are hugged by their braces.
+        (LBrace, _) | (_, RBrace) => false,
+        (Comma | Semicolon | Colon, _) => true,
+        (Dot | ColonColon | DotDot, _) | (_, Dot | ColonColon | DotDot) => false,
+        (DollarSign | Octothorpe | At | Bang, _) => false,
+        (Equals, _) if closes_range(prev) => false,
+        (Ident, LParen | LBrack) => false,
+        _ if is_prefix_op(prev) => false,
+        _ if is_infix_op(prev) || is_infix_op(next) => true,
+        (RAngleBrack | LSquiggleArrow, _) => true,
+        _ => spaced,
+    }
+}
+
+/// The `=` of `..=`, which hugs the bound after it like `..` does.
+fn closes_range(token: &SyntaxToken) -> bool {
+    token.prev_token().is_some_and(|prev| prev.kind() == SyntaxKind::DotDot)
+}
+
+fn is_infix_op(token: &SyntaxToken) -> bool {
+    token.parent().kind() == SyntaxKind::InfixExpr
+        && !matches!(
+            token.kind(),
+            SyntaxKind::LParen | SyntaxKind::RParen | SyntaxKind::LBrack | SyntaxKind::RBrack
+        )
+}
+
+/// The operator of a prefix expression, like the `-` in `-10`.
+fn is_prefix_op(token: &SyntaxToken) -> bool {
+    let parent = token.parent();
+    parent.kind() == SyntaxKind::PrefixExpr && parent.first_token().as_ref() == Some(token)
+}
+
+fn is_trivia(kind: SyntaxKind) -> bool {
+    matches!(kind, SyntaxKind::Whitespace | SyntaxKind::Comment)
+}
+
+fn own_brace(token: &SyntaxToken, node: &SyntaxNode) -> bool {
+    &token.parent() == node
+        && matches!(
+            token.kind(),
+            SyntaxKind::LBrace | SyntaxKind::RBrace | SyntaxKind::SectionMarker
+        )
+}
+
+/// Whether `token` is in the `--- #name:` line that starts a section.
+fn in_header(token: &SyntaxToken, section: &SyntaxNode) -> bool {
+    section
+        .children()
+        .find(|child| MEMBERS.contains(&child.kind()))
+        .is_none_or(|member| token.text_range().start() < member.text_range().start())
+}
+
+/// Whether two tokens with nothing between them still lex as themselves.
+fn lexes_apart(prev: &str, next: &str) -> bool {
+    let joined = format!("{}{}", prev, next);
+    let mut tokens = Lexer::new(&joined).map(|token| token.text);
+    tokens.next() == Some(prev) && tokens.next() == Some(next) && tokens.next().is_none()
+}
+
+/// Lays the pieces out, breaking lines that are too long before their `~>`s.
+fn render(mut pieces: Vec<Piece>) -> String {
+    let starts = pieces
+        .iter()
+        .enumerate()
+        .filter(|(idx, piece)| *idx == 0 || matches!(piece.before, Break::Line { .. }))
+        .map(|(idx, _)| idx)
+        .collect::<Vec<usize>>();
+
+    for (n, &start) in starts.iter().enumerate() {
+        let end = starts.get(n + 1).copied().unwrap_or(pieces.len());
+        let line = &mut pieces[start..end];
+        if width(line) <= MAX_WIDTH {
+            continue;
+        }
+
+        for piece in line.iter_mut().skip(1) {
+            if let (Some(depth), Break::Space) = (piece.pipe, piece.before) {
+                piece.before = Break::Line { depth, blank: false };
+            }
+        }
+    }
+
+    let mut out = String::new();
+    for piece in &pieces {
+        match piece.before {
+            Break::None => {}
+            Break::Space => out.push(' '),
+            Break::Line { depth, blank } => {
+                out.push_str(if blank { "\n\n" } else { "\n" });
+                out.push_str(&INDENT.repeat(depth));
+            }
+        }
+        out.push_str(&piece.text);
+    }
+
+    if !out.is_empty() {
+        out.push('\n');
+    }
+    out
+}
+
+fn width(line: &[Piece]) -> usize {
+    line.iter()
+        .map(|piece| {
+            let before = match piece.before {
+                Break::None => 0,
+                Break::Space => 1,
+                Break::Line { depth, .. } => depth * INDENT.len(),
+            };
+            before + piece.text.chars().count()
+        })
+        .sum()
+}
@@ -1,17 +1,31 @@
-use std::panic;
+use std::ops::Index;
 
-use crate::{BinaryOp, HirExpr, HirRecord, HirStmt, UnaryOp};
+use crate::{BinaryOp, ConstraintOp, Enum, ExprIdx, HirExpr, HirRecord, HirStmt, LoweringDiagnostic, Primitive, SourceMap, UnaryOp};
+use ast::Number;
 use la_arena::Arena;
 use smol_str::SmolStr;
-use syntax::SyntaxKind;
+use syntax::{SyntaxElement, SyntaxKind, SyntaxNodePtr, SyntaxToken};
+use text_size::TextRange;
 
 #[derive(Debug, PartialEq, Default)]
 pub struct Database {
     exprs: Arena<HirExpr>,
+    diagnostics: Vec<LoweringDiagnostic>,
+    source_map: SourceMap,
 }
 
 impl Database {
+    pub fn source_map(&self) -> &SourceMap {
+        &self.source_map
+    }
+
+    /// Everything lowering had to replace with `Missing`, in source order.
+    pub fn diagnostics(&self) -> &[LoweringDiagnostic] {
+        &self.diagnostics
+    }
+
     pub(crate) fn lower_stmt(&mut self, ast: ast::Stmt) -> Option<HirStmt> {
+        let range = ast.text_range();
         let result = match ast {
 
             ast::Stmt::Record(ast) => HirStmt::Record(self.lower_record(Some(ast))),
@@ -20,10 +34,20 @@ impl Database {
 
             ast::Stmt::Expr(ast) => HirStmt::Expr(self.lower_expr(Some(ast))),
 
+            ast::Stmt::Section(ast) => HirStmt::Record(self.lower_section(ast)),
+
             ast::Stmt::Accessor(ast) => {
+                let name = match ast.name() {
+                    Some(name) => name,
+                    None => {
+                        self.diagnostics.push(LoweringDiagnostic::UnnamedAccessor { range });
+                        return Some(HirStmt::Record(HirRecord::Missing));
+                    }
+                };
+
                 HirStmt::Accessor { 
-                    name: ast.name()?.text().into(),
-                    source: ast.path().unwrap().name().into_iter()
+                    name: name.text().into(),
+                    source: ast.source().into_iter()
                         .map(|n| n.text().into())
                         .collect::<Vec<SmolStr>>()
                     // source: ast.value().iter()
@@ -32,9 +56,9 @@ impl Database {
                 }
             },
 
-            _ => {
-                println!("Well that was unexpected");
-                panic!()
+            ast::Stmt::ImportStmt(_) | ast::Stmt::AnonRecord(_) | ast::Stmt::Struct(_) => {
+                self.diagnostics.push(LoweringDiagnostic::UnsupportedStmt { range });
+                HirStmt::Record(HirRecord::Missing)
             }
         };
 
@@ -43,25 +67,28 @@ impl Database {
 
     pub(crate) fn lower_record(&mut self, ast: Option<ast::Record>) -> HirRecord {
         if let Some(ast) = ast {
+            let id = self.source_map.alloc_record(SyntaxNodePtr::new(ast.syntax()));
             match ast {
                 ast::Record::Mono(ast) => {
                     return HirRecord::Mono { 
+                        id,
                         name: ast.name(),
                         value: self.lower_expr(ast.value()),
                         constraint: Some(self.lower_expr(ast.constraint())),
-                        scope: Some(self.lower_expr(ast.scope())),
-                        schema: false,
+                        scope: Some(self.lower_scope(ast.scope())),
+                        schema: ast.is_schema(),
+                        optional: ast.is_optional(),
                     };
                 },
                 ast::Record::Poly(ast) => {
                     return HirRecord::Poly { 
+                        id,
                         name: ast.name(),
-                        value: ast.value().into_iter()
-                            .filter_map(|v| Some(self.lower_record(Some(v))))
-                            .collect::<Vec<HirRecord>>(),
+                        value: self.lower_members(ast.members()),
                         constraint: Some(self.lower_expr(ast.constraint())),
-                        scope: Some(self.lower_expr(ast.scope())),
+                        scope: Some(self.lower_scope(ast.scope())),
                         schema: ast.is_schema(),
+                        optional: ast.is_optional(),
                     };
                 },
                 // _ => {return HirRecord::Missing}
@@ -72,62 +99,557 @@ impl Database {
         }
     }
 
+    fn lower_section(&mut self, ast: ast::Section) -> HirRecord {
+        HirRecord::Section {
+            id: self.source_map.alloc_record(SyntaxNodePtr::new(ast.syntax())),
+            name: ast.name(),
+            value: self.lower_members(ast.members()),
+            scope: Some(self.lower_scope(ast.scope())),
+        }
+    }
+
+    fn lower_members(&mut self, members: Vec<ast::Member>) -> Vec<HirRecord> {
+        members.into_iter()
+            .map(|member| match member {
+                ast::Member::Record(ast) => self.lower_record(Some(ast)),
+                ast::Member::Section(ast) => self.lower_section(ast),
+            })
+            .collect()
+    }
+
+    fn lower_scope(&mut self, ast: Option<ast::ScopeBlock>) -> HirExpr {
+        match ast {
+            Some(ast) => HirExpr::Scope {
+                sources: ast.sources().into_iter()
+                    .map(|tokens| tokens.iter().map(|t| t.text().into()).collect())
+                    .collect(),
+            },
+            None => HirExpr::Missing,
+        }
+    }
+
     pub(crate) fn lower_expr(&mut self, ast: Option<ast::Expr>) -> HirExpr {
         if let Some(ast) = ast {
             match ast {
                 ast::Expr::BinaryExpr(ast) => self.lower_binary(ast),
-                ast::Expr::Literal(ast) => HirExpr::Literal { n: ast.parse() },
+                ast::Expr::Literal(ast) => self.lower_literal(ast),
                 ast::Expr::ParenExpr(ast) => self.lower_expr(ast.expr()),
                 ast::Expr::UnaryExpr(ast) => self.lower_unary(ast),
                 ast::Expr::Ref(ast) => self.lower_variable_ref(ast),
                 ast::Expr::List(ast) => self.lower_list(ast),
+                ast::Expr::Struct(ast) => self.lower_struct(ast),
+                ast::Expr::ScopeBlock(ast) => self.lower_scope(Some(ast)),
+                ast::Expr::TypePrimitive(ast) => self.lower_type(ast),
+                ast::Expr::Transform(ast) => {
+                    let range = ast.text_range();
+                    self.lower_transform(Vec::new(), ast, None, range)
+                }
             }
         } else {
             HirExpr::Missing
         }
     }
 
+    fn lower_literal(&mut self, ast: ast::Literal) -> HirExpr {
+        match ast.string() {
+            Some(Ok(s)) => return HirExpr::String { s },
+            // Validation reports the escapes that aren't ones, and the
+            // numbers that don't fit in a u64.
+            Some(Err(_)) => return HirExpr::Missing,
+            None => {}
+        }
+
+        match ast.number() {
+            Some(Number::Int(n)) => self.int(n, false, ast.text_range()),
+            Some(Number::Float(f)) => HirExpr::Float { f },
+            Some(Number::Address { segment, offset }) => HirExpr::Address { segment, offset },
+            Some(Number::RandInt { count, sides }) => HirExpr::RandInt { count, sides },
+            Some(Number::RandFloat { low, high }) => HirExpr::RandFloat { low, high },
+            None => HirExpr::Missing,
+        }
+    }
+
+    /// An int literal with its sign, which can be one further from zero when
+    /// it's negative.
+    fn int(&mut self, n: u64, negative: bool, range: TextRange) -> HirExpr {
+        let n = if negative {
+            0i64.checked_sub_unsigned(n)
+        } else {
+            i64::try_from(n).ok()
+        };
+
+        match n {
+            Some(n) => HirExpr::Int { n },
+            None => {
+                self.diagnostics.push(LoweringDiagnostic::IntOutOfRange { range });
+                HirExpr::Missing
+            }
+        }
+    }
+
     fn lower_binary(&mut self, ast: ast::BinaryExpr) -> HirExpr {
-        println!("HERE: {:?}", ast.op());
-        let op = match ast.op().unwrap().kind() {
+        let op = match ast.op() {
+            Some(op) => op,
+            None => return self.missing_operator(ast.text_range()),
+        };
+
+        let op = match op.kind() {
             SyntaxKind::Plus      => BinaryOp::Add,
             SyntaxKind::Minus     => BinaryOp::Sub,
             SyntaxKind::Star      => BinaryOp::Mul,
             SyntaxKind::Slash     => BinaryOp::Div,
             SyntaxKind::Ampersand => BinaryOp::Unify,
-            _ => unreachable!(),
+            SyntaxKind::Bar       => BinaryOp::Dsj,
+            SyntaxKind::DotDot    => {
+                let start = self.lower_bound(ast.lhs());
+                let end = self.lower_bound(ast.rhs());
+                return HirExpr::Range {
+                    start,
+                    end,
+                    inclusive: ast.is_inclusive(),
+                    range: ast.text_range(),
+                };
+            }
+            SyntaxKind::Dot | SyntaxKind::ColonColon => return self.lower_path(ast),
+            // `x -> #f(a)` is `#f(x, a)`.
+            SyntaxKind::RArrow => {
+                let target = match ast.rhs() {
+                    Some(ast::Expr::Transform(transform)) => Some((Vec::new(), transform)),
+                    Some(ast::Expr::BinaryExpr(rhs)) => transform_path(&rhs),
+                    _ => None,
+                };
+                return match target {
+                    Some((path, transform)) => self.lower_transform(path, transform, ast.lhs(), ast.text_range()),
+                    None => self.unsupported_operator(op.text(), ast.text_range()),
+                };
+            }
+            _ => return self.unsupported_operator(op.text(), ast.text_range()),
         };
 
-        let lhs = self.lower_expr(ast.lhs());
-        let rhs = self.lower_expr(ast.rhs());
+        let (lhs_ptr, rhs_ptr) = (ptr(&ast.lhs()), ptr(&ast.rhs()));
+        let (lhs, rhs) = if op == BinaryOp::Dsj {
+            (self.lower_alternative(ast.lhs()), self.lower_alternative(ast.rhs()))
+        } else {
+            (self.lower_expr(ast.lhs()), self.lower_expr(ast.rhs()))
+        };
+
+        // A leading scope block is kept on the record, not in its value.
+        if op == BinaryOp::Unify && matches!(lhs, HirExpr::Scope { .. }) {
+            return rhs;
+        }
 
         HirExpr::Binary {
             op,
-            lhs: self.exprs.alloc(lhs),
-            rhs: self.exprs.alloc(rhs),
+            lhs: self.alloc(lhs, lhs_ptr),
+            rhs: self.alloc(rhs, rhs_ptr),
+            range: ast.text_range(),
+        }
+    }
+
+    /// A `*` directly in front of a disjunct marks it as the default, rather
+    /// than being a multiple-of constraint.
+    fn lower_alternative(&mut self, ast: Option<ast::Expr>) -> HirExpr {
+        if let Some(ast::Expr::UnaryExpr(unary)) = &ast {
+            if unary.op().map(|op| op.kind()) == Some(SyntaxKind::Star) {
+                let expr_ptr = ptr(&unary.expr());
+                let expr = self.lower_expr(unary.expr());
+                return HirExpr::Unary {
+                    op: UnaryOp::Default,
+                    expr: self.alloc(expr, expr_ptr),
+                };
+            }
         }
+
+        self.lower_expr(ast)
     }
 
     fn lower_unary(&mut self, ast: ast::UnaryExpr) -> HirExpr {
-        let op = match ast.op().unwrap().kind() {
-            SyntaxKind::Minus => UnaryOp::Neg,
-            _ => unreachable!(),
+        let op = match ast.op() {
+            Some(op) => op,
+            None => return self.missing_operator(ast.text_range()),
         };
 
+        let op = match op.kind() {
+            SyntaxKind::Minus => {
+                if let Some(ast::Expr::Literal(literal)) = ast.expr() {
+                    if let Some(Number::Int(n)) = literal.number() {
+                        return self.int(n, true, ast.text_range());
+                    }
+                }
+                UnaryOp::Neg
+            }
+            SyntaxKind::DotDot => {
+                let end = self.lower_bound(ast.expr());
+                return HirExpr::Range { start: None, end, inclusive: false, range: ast.text_range() };
+            }
+            // Only a bare `...` is a range, spreads aren't lowered yet.
+            SyntaxKind::DotDotDot if ast.expr().is_none() => {
+                return HirExpr::Range { start: None, end: None, inclusive: false, range: ast.text_range() };
+            }
+            _ => return self.lower_constraint(ast, op),
+        };
+
+        let expr_ptr = ptr(&ast.expr());
         let expr = self.lower_expr(ast.expr());
 
         HirExpr::Unary {
             op,
-            expr: self.exprs.alloc(expr),
+            expr: self.alloc(expr, expr_ptr),
+        }
+    }
+
+    fn lower_constraint(&mut self, ast: ast::UnaryExpr, op: SyntaxToken) -> HirExpr {
+        let op = match op.kind() {
+            SyntaxKind::RAngleBrack  => ConstraintOp::Gr,
+            SyntaxKind::GreaterEqual => ConstraintOp::GrEq,
+            SyntaxKind::LAngleBrack  => ConstraintOp::Ls,
+            SyntaxKind::LessEqual    => ConstraintOp::LsEq,
+            SyntaxKind::Star         => ConstraintOp::MultipleOf,
+            SyntaxKind::Slash        => ConstraintOp::DivisorOf,
+            SyntaxKind::Carrot       => ConstraintOp::PowerOf,
+            _ => return self.unsupported_operator(op.text(), ast.text_range()),
+        };
+        let expr_ptr = ptr(&ast.expr());
+        let expr = self.lower_expr(ast.expr());
+
+        HirExpr::Constraint {
+            op,
+            expr: self.alloc(expr, expr_ptr),
+            range: ast.text_range(),
+        }
+    }
+
+    /// Lowers one end of a range, which is left open if it wasn't written.
+    fn lower_bound(&mut self, ast: Option<ast::Expr>) -> Option<ExprIdx> {
+        let expr_ptr = ptr(&ast);
+        let expr = self.lower_expr(Some(ast?));
+        Some(self.alloc(expr, expr_ptr))
+    }
+
+    /// `a.b::c` is a single reference, as long as every segment is a name.
+    fn lower_path(&mut self, ast: ast::BinaryExpr) -> HirExpr {
+        if let Some((path, transform)) = transform_path(&ast) {
+            return self.lower_transform(path, transform, None, ast.text_range());
+        }
+
+        let mut names = Vec::new();
+        if !path_segments(ast.lhs(), &mut names) || !path_segments(ast.rhs(), &mut names) {
+            return self.expected_name(ast.text_range());
+        }
+
+        let mut names = names.into_iter();
+        match names.next() {
+            Some(var) => HirExpr::Ref {
+                var,
+                fields: names.collect(),
+                range: ast.text_range(),
+            },
+            None => self.expected_name(ast.text_range()),
         }
     }
 
+    fn lower_transform(
+        &mut self,
+        path: Vec<SmolStr>,
+        ast: ast::Transform,
+        input: Option<ast::Expr>,
+        range: TextRange,
+    ) -> HirExpr {
+        let name = match ast.name() {
+            Some(name) => name.text().into(),
+            None => return self.expected_name(range),
+        };
+        let args = input.into_iter()
+            .chain(ast.args())
+            .map(|arg| {
+                let arg_ptr = SyntaxNodePtr::new(arg.syntax());
+                let arg = self.lower_expr(Some(arg));
+                self.alloc(arg, Some(arg_ptr))
+            })
+            .collect();
+
+        HirExpr::Transform { path, name, args, range }
+    }
+
     fn lower_variable_ref(&mut self, ast: ast::Ref) -> HirExpr {
-        HirExpr::Ref {
-            var: ast.name().unwrap().text().into(),
+        let var = match ast.text() {
+            Some(var) => HirExpr::Ref {
+                var,
+                fields: Vec::new(),
+                range: ast.text_range(),
+            },
+            None => return self.expected_name(ast.text_range()),
+        };
+
+        if let Some(body) = ast.body() {
+            let body_ptr = SyntaxNodePtr::new(body.syntax());
+            let body = self.lower_struct(body);
+            HirExpr::Binary {
+                op: BinaryOp::Unify,
+                lhs: self.alloc(var, Some(SyntaxNodePtr::new(ast.syntax()))),
+                rhs: self.alloc(body, Some(body_ptr)),
+                range: ast.text_range(),
+            }
+        } else {
+            var
+        }
+    }
+
+    fn lower_struct(&mut self, ast: ast::Struct) -> HirExpr {
+        HirExpr::Struct {
+            fields: self.lower_members(ast.members()),
         }
     }
 
+    fn alloc(&mut self, expr: HirExpr, ptr: Option<SyntaxNodePtr>) -> ExprIdx {
+        let idx = self.exprs.alloc(expr);
+        if let Some(ptr) = ptr {
+            self.source_map.insert_expr(idx, ptr);
+        }
+        idx
+    }
+
+    fn missing_operator(&mut self, range: TextRange) -> HirExpr {
+        self.diagnostics.push(LoweringDiagnostic::MissingOperator { range });
+        HirExpr::Missing
+    }
+
+    fn unsupported_operator(&mut self, op: &str, range: TextRange) -> HirExpr {
+        self.diagnostics.push(LoweringDiagnostic::UnsupportedOperator { op: op.into(), range });
+        HirExpr::Missing
+    }
+
+    fn expected_name(&mut self, range: TextRange) -> HirExpr {
+        self.diagnostics.push(LoweringDiagnostic::ExpectedName { range });
+        HirExpr::Missing
+    }
+
+    fn lower_type(&mut self, ast: ast::TypePrimitive) -> HirExpr {
+        let range = ast.text_range();
+        let name = match ast.name() {
+            Some(name) => name,
+            None => return self.expected_name(range),
+        };
+        let primitive = match Primitive::named(name.text()) {
+            Some(primitive) => primitive,
+            None => {
+                self.diagnostics.push(LoweringDiagnostic::UnknownType { name: name.text().into(), range });
+                return HirExpr::Missing;
+            }
+        };
+        if primitive == Primitive::Enum {
+            return HirExpr::Enum(self.lower_enum(ast.body()));
+        }
+
+        // `n -> type` gives the length and the type of a vector's items.
+        let args = match ast.arg() {
+            Some(ast::Expr::BinaryExpr(arg)) if arg.op().map(|op| op.kind()) == Some(SyntaxKind::RArrow) => {
+                vec![arg.lhs(), arg.rhs()]
+            }
+            Some(arg) => vec![Some(arg)],
+            None => Vec::new(),
+        };
+        let args = args.into_iter()
+            .map(|arg| {
+                let arg_ptr = ptr(&arg);
+                let arg = self.lower_expr(arg);
+                self.alloc(arg, arg_ptr)
+            })
+            .collect();
+
+        HirExpr::Type { primitive, args, range }
+    }
+
+    /// Records outside a section are plain members, and `#flags` makes every
+    /// member a bit. Sets are expanded once all the members are known, so
+    /// they can be written before them.
+    fn lower_enum(&mut self, ast: Option<ast::Struct>) -> Enum {
+        let mut lowered = Enum { members: Vec::new(), sets: Vec::new(), flags: false };
+        let (mut ranges, mut groups) = (Vec::new(), Vec::new());
+
+        for member in ast.map_or_else(Vec::new, |ast| ast.members()) {
+            let section = match member {
+                ast::Member::Record(record) => {
+                    if is_written(&record) {
+                        self.lower_enum_member(record, &mut lowered);
+                    }
+                    continue;
+                }
+                ast::Member::Section(section) => section,
+            };
+
+            let records = section.members().into_iter().filter_map(|member| match member {
+                ast::Member::Record(record) if is_written(&record) => Some(record),
+                _ => None,
+            });
+            match section.name().as_deref() {
+                Some("flags") => {
+                    lowered.flags = true;
+                    for record in records {
+                        self.lower_enum_member(record, &mut lowered);
+                    }
+                }
+                Some("ranges") => ranges.extend(records),
+                Some("groups") => groups.extend(records),
+                name => self.diagnostics.push(LoweringDiagnostic::UnknownEnumSection {
+                    name: name.unwrap_or_default().into(),
+                    range: section.text_range(),
+                }),
+            }
+        }
+
+        for record in ranges {
+            if let Some(set) = self.lower_enum_range(&record, &lowered) {
+                lowered.sets.push(set);
+            }
+        }
+        for record in groups {
+            if let Some(set) = self.lower_enum_group(&record, &lowered) {
+                lowered.sets.push(set);
+            }
+        }
+        lowered
+    }
+
+    fn lower_enum_member(&mut self, ast: ast::Record, lowered: &mut Enum) {
+        let range = ast.text_range();
+        let (name, value) = match &ast {
+            ast::Record::Mono(record) => (record.name(), record.value()),
+            ast::Record::Poly(record) => (record.name(), None),
+        };
+        let value = match value {
+            Some(value) => self.enum_value(value),
+            None => {
+                self.diagnostics.push(LoweringDiagnostic::EnumValue { range });
+                None
+            }
+        };
+
+        match (name, value) {
+            (Some(name), Some(value)) => lowered.members.push((name, value)),
+            (None, _) => {
+                self.expected_name(range);
+            }
+            (Some(_), None) => {}
+        }
+    }
+
+    /// `3`, or a flag like `1 .> 2`, which is `1` moved up two bits.
+    fn enum_value(&mut self, ast: ast::Expr) -> Option<u64> {
+        let value = match &ast {
+            ast::Expr::Literal(literal) => match literal.number() {
+                Some(Number::Int(n)) => Some(n),
+                _ => None,
+            },
+            ast::Expr::ParenExpr(paren) => return self.enum_value(paren.expr()?),
+            ast::Expr::BinaryExpr(shift) if shift.op().map(|op| op.kind()) == Some(SyntaxKind::BitShiftRight) => {
+                let n = self.enum_value(shift.lhs()?)?;
+                let by = self.enum_value(shift.rhs()?)?;
+                u32::try_from(by).ok()
+                    .and_then(|by| n.checked_shl(by).filter(|shifted| shifted >> by == n))
+            }
+            _ => None,
+        };
+
+        // Members have to fit in an int, and so do any flags combined.
+        let value = value.filter(|value| i64::try_from(*value).is_ok());
+        if value.is_none() {
+            self.diagnostics.push(LoweringDiagnostic::EnumValue { range: ast.text_range() });
+        }
+        value
+    }
+
+    /// `prim: @self.A..@self.D` is every member declared from `A` up to
+    /// `D`, and `..=` takes `D` too.
+    fn lower_enum_range(&mut self, ast: &ast::Record, lowered: &Enum) -> Option<(SmolStr, Vec<SmolStr>)> {
+        let (name, range) = match enum_set(ast) {
+            Some((name, ast::Expr::BinaryExpr(range))) if range.op().map(|op| op.kind()) == Some(SyntaxKind::DotDot) => {
+                (name, range)
+            }
+            _ => {
+                self.diagnostics.push(LoweringDiagnostic::EnumSet { range: ast.text_range() });
+                return None;
+            }
+        };
+
+        let (start, end) = match (range.lhs(), range.rhs()) {
+            (Some(start), Some(end)) => (self.member_idx(&start, lowered)?, self.member_idx(&end, lowered)?),
+            _ => {
+                self.diagnostics.push(LoweringDiagnostic::EnumSet { range: range.text_range() });
+                return None;
+            }
+        };
+        let end = if range.is_inclusive() { end + 1 } else { end };
+        if start >= end {
+            self.diagnostics.push(LoweringDiagnostic::EmptyEnumRange { range: range.text_range() });
+            return None;
+        }
+
+        let members = &lowered.members[start..end];
+        Some((name, members.iter().map(|(name, _)| name.clone()).collect()))
+    }
+
+    /// `AB: A & B` is the members named, and those of any ranges or groups
+    /// named, in declaration order.
+    fn lower_enum_group(&mut self, ast: &ast::Record, lowered: &Enum) -> Option<(SmolStr, Vec<SmolStr>)> {
+        let (name, group) = match enum_set(ast) {
+            Some(set) => set,
+            None => {
+                self.diagnostics.push(LoweringDiagnostic::EnumSet { range: ast.text_range() });
+                return None;
+            }
+        };
+
+        let mut operands = Vec::new();
+        unified(group, &mut operands);
+
+        let mut covered = vec![false; lowered.members.len()];
+        for operand in operands {
+            let name = match member_name(&operand) {
+                Some(name) => name,
+                None => {
+                    self.expected_name(operand.text_range());
+                    return None;
+                }
+            };
+
+            if let Some(idx) = lowered.members.iter().position(|(member, _)| *member == name) {
+                covered[idx] = true;
+            } else if let Some((_, set)) = lowered.sets.iter().find(|(set, _)| *set == name) {
+                for (idx, (member, _)) in lowered.members.iter().enumerate() {
+                    covered[idx] |= set.contains(member);
+                }
+            } else {
+                let range = operand.text_range();
+                self.diagnostics.push(LoweringDiagnostic::UnknownMember { name, range });
+                return None;
+            }
+        }
+
+        let members = lowered.members.iter()
+            .zip(covered)
+            .filter(|(_, covered)| *covered)
+            .map(|((member, _), _)| member.clone())
+            .collect();
+        Some((name, members))
+    }
+
+    fn member_idx(&mut self, ast: &ast::Expr, lowered: &Enum) -> Option<usize> {
+        let name = match member_name(ast) {
+            Some(name) => name,
+            None => {
+                self.expected_name(ast.text_range());
+                return None;
+            }
+        };
+
+        let idx = lowered.members.iter().position(|(member, _)| *member == name);
+        if idx.is_none() {
+            self.diagnostics.push(LoweringDiagnostic::UnknownMember { name, range: ast.text_range() });
+        }
+        idx
+    }
+
     fn lower_list(&mut self, ast: ast::List) -> HirExpr {
         HirExpr::List { 
             items: ast.items().into_iter()
@@ -139,9 +661,106 @@ impl Database {
     }
 }
 
+/// Whether a member is a record someone wrote, rather than a section's
+/// header or a stray token the parser gave up on.
+fn is_written(ast: &ast::Record) -> bool {
+    ast.syntax().kind() == SyntaxKind::Record
+}
+
+/// The name and value of a record under `#ranges` or `#groups`.
+fn enum_set(ast: &ast::Record) -> Option<(SmolStr, ast::Expr)> {
+    match ast {
+        ast::Record::Mono(record) => Some((record.name()?, record.value()?)),
+        ast::Record::Poly(_) => None,
+    }
+}
+
+/// Every operand of `a & b & c`.
+fn unified(ast: ast::Expr, out: &mut Vec<ast::Expr>) {
+    match ast {
+        ast::Expr::BinaryExpr(unify) if unify.op().map(|op| op.kind()) == Some(SyntaxKind::Ampersand) => {
+            for operand in [unify.lhs(), unify.rhs()].into_iter().flatten() {
+                unified(operand, out);
+            }
+        }
+        ast::Expr::ParenExpr(paren) => {
+            if let Some(expr) = paren.expr() {
+                unified(expr, out);
+            }
+        }
+        ast => out.push(ast),
+    }
+}
+
+/// A member of the enum being lowered, written `A` or `@self.A`.
+fn member_name(ast: &ast::Expr) -> Option<SmolStr> {
+    let tokens = ast.syntax()
+        .descendants_with_tokens()
+        .filter_map(SyntaxElement::into_token)
+        .filter(|token| !matches!(token.kind(), SyntaxKind::Whitespace | SyntaxKind::Comment))
+        .collect::<Vec<SyntaxToken>>();
+
+    match tokens.as_slice() {
+        [name] if name.kind() == SyntaxKind::Ident => Some(name.text().into()),
+        [at, this, dot, name]
+            if at.kind() == SyntaxKind::At
+                && this.text() == "self"
+                && dot.kind() == SyntaxKind::Dot
+                && name.kind() == SyntaxKind::Ident =>
+        {
+            Some(name.text().into())
+        }
+        _ => None,
+    }
+}
+
+fn ptr(ast: &Option<ast::Expr>) -> Option<SyntaxNodePtr> {
+    ast.as_ref().map(|ast| SyntaxNodePtr::new(ast.syntax()))
+}
+
+fn path_segments(ast: Option<ast::Expr>, names: &mut Vec<SmolStr>) -> bool {
+    match ast {
+        Some(ast::Expr::BinaryExpr(ast))
+            if matches!(ast.op().map(|op| op.kind()), Some(SyntaxKind::Dot | SyntaxKind::ColonColon)) =>
+        {
+            path_segments(ast.lhs(), names) && path_segments(ast.rhs(), names)
+        }
+        Some(ast::Expr::Ref(ast)) if ast.body().is_none() => match ast.text() {
+            Some(name) => {
+                names.push(name);
+                true
+            }
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+/// `a::b::#f`, split into the path to the transform and the transform.
+fn transform_path(ast: &ast::BinaryExpr) -> Option<(Vec<SmolStr>, ast::Transform)> {
+    if !matches!(ast.op().map(|op| op.kind()), Some(SyntaxKind::Dot | SyntaxKind::ColonColon)) {
+        return None;
+    }
+    let transform = match ast.rhs()? {
+        ast::Expr::Transform(transform) => transform,
+        _ => return None,
+    };
+    let mut path = Vec::new();
+    path_segments(ast.lhs(), &mut path).then_some((path, transform))
+}
+
+impl Index<ExprIdx> for Database {
+    type Output = HirExpr;
+
+    fn index(&self, idx: ExprIdx) -> &Self::Output {
+        &self.exprs[idx]
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{Enum, RecordId};
 
     fn parse(input: &str) -> ast::Root {
         ast::Root::cast(parser::parse(input).syntax()).unwrap()
@@ -181,18 +800,22 @@ mod tests {
     fn lower_record_dec() {
         let rec = HirStmt::Record(
             HirRecord::Poly {
+                id: RecordId(0),
                 name: Some("foo".into()),
                 value: vec![
-                    HirRecord::Mono { 
+                    HirRecord::Mono {
+                        id: RecordId(1),
                         name: Some("name".into()),
-                        value: HirExpr::Literal { n: Some(4) },
+                        value: HirExpr::Int { n: 4 },
                         constraint: Some(HirExpr::Missing),
                         schema: false,
+                        optional: false,
                         scope: Some(HirExpr::Missing),
                      }
                 ],
                 constraint: Some(HirExpr::Missing),
                 schema: false,
+                optional: false,
                 scope: Some(HirExpr::Missing),
             }
         );
@@ -202,29 +825,71 @@ mod tests {
         );
     }
 
+    #[test]
+    fn lower_optional_record() {
+        let rec = HirStmt::Record(
+            HirRecord::Poly {
+                id: RecordId(0),
+                name: Some("foo".into()),
+                value: vec![
+                    HirRecord::Mono {
+                        id: RecordId(1),
+                        name: Some("name".into()),
+                        value: HirExpr::Int { n: 4 },
+                        constraint: Some(HirExpr::Missing),
+                        schema: false,
+                        optional: true,
+                        scope: Some(HirExpr::Missing),
+                     }
+                ],
+                constraint: Some(HirExpr::Missing),
+                schema: false,
+                optional: false,
+                scope: Some(HirExpr::Missing),
+            }
+        );
+        check_stmt(
+            "foo: {name?: 4}",
+            rec,
+        );
+    }
+
     #[test]
     fn lower_constrained_record() {
         let mut exprs = Arena::new();
-        let lhs = exprs.alloc(HirExpr::Ref { var: "aSchema".into() });
-        let rhs = exprs.alloc(HirExpr::Ref { var: "anotherSchema".into() } );
+        let lhs = exprs.alloc(HirExpr::Ref {
+            var: "aSchema".into(),
+            fields: Vec::new(),
+            range: TextRange::new(5.into(), 12.into()),
+        });
+        let rhs = exprs.alloc(HirExpr::Ref {
+            var: "anotherSchema".into(),
+            fields: Vec::new(),
+            range: TextRange::new(15.into(), 28.into()),
+        });
         let rcrd = HirStmt::Record(
-            HirRecord::Poly { 
+            HirRecord::Poly {
+                id: RecordId(0),
                 name: Some("foo".into()),
                 value: vec![
                     HirRecord::Mono {
+                        id: RecordId(1),
                         name: Some("name".into()),
-                        value: HirExpr::Literal { n: Some(4) },
+                        value: HirExpr::Int { n: 4 },
                         constraint: Some(HirExpr::Missing),
                         schema: false,
+                        optional: false,
                         scope: Some(HirExpr::Missing),
                     }
                 ], 
                 constraint: Some(HirExpr::Binary { 
                     op: BinaryOp::Unify,
                     lhs: lhs,
-                    rhs: rhs 
+                    rhs: rhs,
+                    range: TextRange::new(5.into(), 28.into()),
                 }),
                 schema: false,
+                optional: false,
                 scope: Some(HirExpr::Missing),
             }
         );
@@ -239,11 +904,13 @@ mod tests {
         check_stmt(
             "foo: {}",
             HirStmt::Record(
-                HirRecord::Poly { 
+                HirRecord::Poly {
+                    id: RecordId(0),
                     name: Some("foo".into()),
                     value: vec![],
                     constraint: Some(HirExpr::Missing),
                     schema: false,
+                    optional: false,
                     scope: Some(HirExpr::Missing),
                 }
             )
@@ -279,11 +946,13 @@ mod tests {
         check_stmt(
             "a:",
             HirStmt::Record(
-                HirRecord::Mono { 
+                HirRecord::Mono {
+                    id: RecordId(0),
                     name: Some("a".into()),
                     value: HirExpr::Missing,
                     constraint: Some(HirExpr::Missing),
                     schema: false,
+                    optional: false,
                     scope: Some(HirExpr::Missing), 
                 }
             )
@@ -297,11 +966,13 @@ mod tests {
             ">123",
             HirStmt::Record(
                 HirRecord::Mono {
+                    id: RecordId(0),
                     name: None, 
-                    value: HirExpr::Literal { n: Some(123) },
+                    value: HirExpr::Int { n: 123 },
                     constraint: Some(HirExpr::Missing),
                     scope: Some(HirExpr::Missing),
                     schema: false,
+                    optional: false,
                 }
             )
         );
@@ -310,22 +981,25 @@ mod tests {
     #[test]
     fn lower_binary_expr() {
         let mut exprs = Arena::new();
-        let lhs = exprs.alloc(HirExpr::Literal { n: Some(1) });
-        let rhs = exprs.alloc(HirExpr::Literal { n: Some(2) });
+        let lhs = exprs.alloc(HirExpr::Int { n: 1 });
+        let rhs = exprs.alloc(HirExpr::Int { n: 2 });
 
         check_stmt(
             "> 1+ 2",
             HirStmt::Record(
-                HirRecord::Mono { 
+                HirRecord::Mono {
+                    id: RecordId(0),
                     name: None,
                     value: HirExpr::Binary {
                         lhs,
                         rhs,
                         op: BinaryOp::Add,
+                        range: TextRange::new(2.into(), 6.into()),
                     },
                 constraint: Some(HirExpr::Missing),
                 scope: Some(HirExpr::Missing),
                 schema: false,
+                optional: false,
                 }
             )
         );
@@ -334,22 +1008,25 @@ mod tests {
     #[test]
     fn lower_constraint_expr() {
         let mut exprs = Arena::new();
-        let lhs = exprs.alloc(HirExpr::Literal { n: Some(1) });
-        let rhs = exprs.alloc(HirExpr::Literal { n: Some(2) });
+        let lhs = exprs.alloc(HirExpr::Int { n: 1 });
+        let rhs = exprs.alloc(HirExpr::Int { n: 2 });
 
         check_stmt(
             "> 1 & 2",
             HirStmt::Record(
                 HirRecord::Mono {
+                    id: RecordId(0),
                     name: None,
                     value: HirExpr::Binary {
                         lhs,
                         rhs,
                         op: BinaryOp::Unify,
+                        range: TextRange::new(2.into(), 7.into()),
                     },
                     constraint: Some(HirExpr::Missing),
                     scope: Some(HirExpr::Missing),
-                    schema: false
+                    schema: false,
+                    optional: false
                 }
             )
         );
@@ -358,22 +1035,25 @@ mod tests {
     #[test]
     fn lower_binary_expr_without_rhs() {
         let mut exprs = Arena::new();
-        let lhs = exprs.alloc(HirExpr::Literal { n: Some(10) });
+        let lhs = exprs.alloc(HirExpr::Int { n: 10 });
         let rhs = exprs.alloc(HirExpr::Missing);
 
         check_stmt(
             "> 10 -",
             HirStmt::Record(
                 HirRecord::Mono {
+                    id: RecordId(0),
                     name: None, 
                     value: HirExpr::Binary {
                         lhs,
                         rhs,
                         op: BinaryOp::Sub,
+                        range: TextRange::new(2.into(), 6.into()),
                     },
                     constraint: Some(HirExpr::Missing),
                     scope: Some(HirExpr::Missing),
                     schema: false,
+                    optional: false,
                 }
             )
         );
@@ -384,12 +1064,134 @@ mod tests {
         check_stmt(
             "> 999",
             HirStmt::Record(
-                HirRecord::Mono { 
+                HirRecord::Mono {
+                    id: RecordId(0),
+                    name: None,
+                    value: HirExpr::Int { n: 999 },
+                    constraint: Some(HirExpr::Missing),
+                    scope: Some(HirExpr::Missing),
+                    schema: false,
+                    optional: false
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn lower_strings() {
+        let record = |value| HirStmt::Record(HirRecord::Mono {
+            id: RecordId(0),
+            name: None,
+            value,
+            constraint: Some(HirExpr::Missing),
+            scope: Some(HirExpr::Missing),
+            schema: false,
+            optional: false,
+        });
+
+        check_stmt(r#"> "l'\u{e9}t\u{e9}, \"chaud\"""#, record(HirExpr::String { s: "l'été, \"chaud\"".into() }));
+        check_stmt(r#"> r"C:\path""#, record(HirExpr::String { s: r"C:\path".into() }));
+        check_stmt(r#"> "C:\path""#, record(HirExpr::Missing));
+    }
+
+    #[test]
+    fn lower_disjunction_with_default() {
+        let mut exprs = Arena::new();
+        let a = exprs.alloc(HirExpr::String { s: "A".into() });
+        let lhs = exprs.alloc(HirExpr::Unary { op: UnaryOp::Default, expr: a });
+        let rhs = exprs.alloc(HirExpr::String { s: "B".into() });
+
+        check_stmt(
+            r#"> *"A" | "B""#,
+            HirStmt::Record(
+                HirRecord::Mono {
+                    id: RecordId(0),
+                    name: None,
+                    value: HirExpr::Binary {
+                        lhs,
+                        rhs,
+                        op: BinaryOp::Dsj,
+                        range: TextRange::new(2.into(), 12.into()),
+                    },
+                    constraint: Some(HirExpr::Missing),
+                    scope: Some(HirExpr::Missing),
+                    schema: false,
+                    optional: false
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn lower_multiple_of_constraint() {
+        let mut exprs = Arena::new();
+        let five = exprs.alloc(HirExpr::Int { n: 5 });
+
+        check_stmt(
+            "> *5",
+            HirStmt::Record(
+                HirRecord::Mono {
+                    id: RecordId(0),
                     name: None,
-                    value: HirExpr::Literal { n: Some(999) },
+                    value: HirExpr::Constraint {
+                        op: ConstraintOp::MultipleOf,
+                        expr: five,
+                        range: TextRange::new(2.into(), 4.into()),
+                    },
                     constraint: Some(HirExpr::Missing),
                     scope: Some(HirExpr::Missing),
-                    schema: false
+                    schema: false,
+                    optional: false
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn lower_range() {
+        let mut exprs = Arena::new();
+        let start = exprs.alloc(HirExpr::Int { n: 0 });
+        let end = exprs.alloc(HirExpr::Int { n: 6 });
+
+        check_stmt(
+            "> 0..6",
+            HirStmt::Record(
+                HirRecord::Mono {
+                    id: RecordId(0),
+                    name: None,
+                    value: HirExpr::Range {
+                        start: Some(start),
+                        end: Some(end),
+                        inclusive: false,
+                        range: TextRange::new(2.into(), 6.into()),
+                    },
+                    constraint: Some(HirExpr::Missing),
+                    scope: Some(HirExpr::Missing),
+                    schema: false,
+                    optional: false
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn lower_open_range() {
+        check_stmt(
+            "> ...",
+            HirStmt::Record(
+                HirRecord::Mono {
+                    id: RecordId(0),
+                    name: None,
+                    value: HirExpr::Range {
+                        start: None,
+                        end: None,
+                        inclusive: false,
+                        range: TextRange::new(2.into(), 5.into()),
+                    },
+                    constraint: Some(HirExpr::Missing),
+                    scope: Some(HirExpr::Missing),
+                    schema: false,
+                    optional: false
                 }
             )
         );
@@ -401,11 +1203,17 @@ mod tests {
             "> ((((((abc))))))",
             HirStmt::Record(
                 HirRecord::Mono {
+                    id: RecordId(0),
                     name: None, 
-                    value: HirExpr::Ref { var: "abc".into() },
+                    value: HirExpr::Ref {
+                        var: "abc".into(),
+                        fields: Vec::new(),
+                        range: TextRange::new(8.into(), 11.into()),
+                    },
                     constraint: Some(HirExpr::Missing),
                     scope: Some(HirExpr::Missing),
-                    schema: false
+                    schema: false,
+                    optional: false
                 }
             )
         );
@@ -414,11 +1222,12 @@ mod tests {
     #[test]
     fn lower_unary_expr() {
         let mut exprs = Arena::new();
-        let ten = exprs.alloc(HirExpr::Literal { n: Some(10) });
+        let ten = exprs.alloc(HirExpr::Int { n: 10 });
         check_stmt(
-            "> -10",
+            "> -(10)",
             HirStmt::Record(
                 HirRecord::Mono {
+                    id: RecordId(0),
                     name: None, 
                     value: HirExpr::Unary {
                         expr: ten,
@@ -426,12 +1235,38 @@ mod tests {
                     },
                     constraint: Some(HirExpr::Missing),
                     scope: Some(HirExpr::Missing),
-                    schema: false
+                    schema: false,
+                    optional: false
                 }
             )
         );
     }
 
+    #[test]
+    fn lower_typed_literals() {
+        let value = |input: &str| match Database::default().lower_stmt(parse(input).stmts().next().unwrap()) {
+            Some(HirStmt::Record(HirRecord::Mono { value, .. })) => value,
+            stmt => panic!("expected a record, got {:?}", stmt),
+        };
+
+        assert_eq!(value("> 0x1F"), HirExpr::Int { n: 31 });
+        assert_eq!(value("> -0b11"), HirExpr::Int { n: -3 });
+        assert_eq!(value("> 1_2.5"), HirExpr::Float { f: 12.5 });
+        assert_eq!(value("> 1v3"), HirExpr::Address { segment: 1, offset: 3 });
+        assert_eq!(value("> 3d20"), HirExpr::RandInt { count: 3, sides: 20 });
+        assert_eq!(value("> 0.5d1.5"), HirExpr::RandFloat { low: 0.5, high: 1.5 });
+        assert_eq!(value("> -9223372036854775808"), HirExpr::Int { n: i64::MIN });
+        assert_eq!(value("> 9223372036854775808"), HirExpr::Missing);
+    }
+
+    #[test]
+    fn lower_int_out_of_range() {
+        check_diagnostics(
+            "a: 9223372036854775807\nb: -9223372036854775809\nc: 0x8000000000000000",
+            &["int literal out of range at 26..46", "int literal out of range at 50..68"],
+        );
+    }
+
     #[test]
     fn lower_unary_expr_without_expr() {
         let mut exprs = Arena::new();
@@ -441,6 +1276,7 @@ mod tests {
             "> -",
             HirStmt::Record(
                 HirRecord::Mono {
+                    id: RecordId(0),
                     name: None,
                     value: HirExpr::Unary {
                         expr,
@@ -448,7 +1284,8 @@ mod tests {
                     },
                     constraint: Some(HirExpr::Missing),
                     scope: Some(HirExpr::Missing),
-                    schema: false
+                    schema: false,
+                    optional: false
                 }
             )
         );
@@ -460,11 +1297,17 @@ mod tests {
             "> foo",
             HirStmt::Record(
                 HirRecord::Mono {
+                    id: RecordId(0),
                     name: None,
-                    value: HirExpr::Ref { var: "foo".into() },
+                    value: HirExpr::Ref {
+                        var: "foo".into(),
+                        fields: Vec::new(),
+                        range: TextRange::new(2.into(), 5.into()),
+                    },
                     constraint: Some(HirExpr::Missing),
                     scope: Some(HirExpr::Missing),
                     schema: false,
+                    optional: false,
                 }
             )
         );
@@ -475,6 +1318,142 @@ mod tests {
         // );
     }
 
+    #[test]
+    fn lower_transforms() {
+        let (db, stmts) = crate::lower(parse(
+            "a: \"elbow\" -> #replace(\"l\", \"L\")\nb: std::#clamp(2*2, 0, 3)\nc: 1 -> #abs -> #round",
+        ));
+        let values = stmts.iter()
+            .map(|stmt| match stmt {
+                HirStmt::Record(HirRecord::Mono { value, .. }) => value,
+                stmt => panic!("expected a record, got {:?}", stmt),
+            })
+            .collect::<Vec<_>>();
+        let transform = |expr: &HirExpr| match expr {
+            HirExpr::Transform { path, name, args, .. } => (
+                path.clone(),
+                name.clone(),
+                args.iter().map(|arg| &db[*arg]).collect::<Vec<_>>(),
+            ),
+            expr => panic!("expected a transform, got {:?}", expr),
+        };
+
+        let (path, name, args) = transform(values[0]);
+        assert_eq!((path, name), (Vec::new(), SmolStr::from("replace")));
+        assert_eq!(args, [
+            &HirExpr::String { s: "elbow".into() },
+            &HirExpr::String { s: "l".into() },
+            &HirExpr::String { s: "L".into() },
+        ]);
+
+        let (path, name, args) = transform(values[1]);
+        assert_eq!((path, name), (vec![SmolStr::from("std")], SmolStr::from("clamp")));
+        assert!(matches!(args[..], [HirExpr::Binary { op: BinaryOp::Mul, .. }, HirExpr::Int { n: 0 }, HirExpr::Int { n: 3 }]));
+
+        let (_, name, args) = transform(values[2]);
+        assert_eq!(name, "round");
+        let (_, name, args) = transform(args[0]);
+        assert_eq!((name, args), (SmolStr::from("abs"), vec![&HirExpr::Int { n: 1 }]));
+    }
+
+    #[test]
+    fn lower_arrow_without_transform() {
+        check_diagnostics("a: 1 -> 2", &["`->` isn't supported here, at 3..9"]);
+    }
+
+    fn check_diagnostics(input: &str, expected: &[&str]) {
+        let (db, _) = crate::lower(parse(input));
+        let diagnostics = db.diagnostics().iter().map(ToString::to_string).collect::<Vec<String>>();
+        assert_eq!(diagnostics, expected);
+    }
+
+    #[test]
+    fn lower_unsupported_operator() {
+        check_diagnostics("a: 5 % 2\nb: 1", &["`%` isn't supported here, at 3..8"]);
+    }
+
+    #[test]
+    fn lower_transform_in_path() {
+        check_diagnostics("a: #zalgo.x", &["expected a name at 3..11"]);
+    }
+
+    #[test]
+    fn lower_path_with_non_name_segment() {
+        check_diagnostics("a: @global::x", &["expected a name at 3..13"]);
+    }
+
+    #[test]
+    fn lower_unknown_type() {
+        check_diagnostics("a: !int\nb: !uint & >=0", &["unknown type `!uint` at 11..16"]);
+    }
+
+    #[test]
+    fn lower_vector_type() {
+        let (db, stmts) = crate::lower(parse("pos: !vec[3 -> !float]"));
+        let args = match &stmts[..] {
+            [HirStmt::Record(HirRecord::Mono { value: HirExpr::Type { primitive: Primitive::Vec, args, .. }, .. })] => args,
+            _ => panic!("expected a vector type, found {:?}", stmts),
+        };
+
+        assert_eq!(db[args[0]], HirExpr::Int { n: 3 });
+        assert!(matches!(db[args[1]], HirExpr::Type { primitive: Primitive::Float, ref args, .. } if args.is_empty()));
+    }
+
+    #[test]
+    fn lower_enum() {
+        let input = "$tags: !enum{\n  --- #flags\n  A: 1 .> 0\n  B: 1 .> 1\n  C: 1 .> 2\n  --- #ranges\n  low: @self.A..@self.C\n  high: @self.B..=@self.C\n  --- #groups\n  AC: A & C\n  all: low & C\n}";
+        let (db, stmts) = crate::lower(parse(input));
+        assert_eq!(db.diagnostics(), &[]);
+
+        let expected = Enum {
+            members: vec![("A".into(), 1), ("B".into(), 2), ("C".into(), 4)],
+            sets: vec![
+                ("low".into(), vec!["A".into(), "B".into()]),
+                ("high".into(), vec!["B".into(), "C".into()]),
+                ("AC".into(), vec!["A".into(), "C".into()]),
+                ("all".into(), vec!["A".into(), "B".into(), "C".into()]),
+            ],
+            flags: true,
+        };
+        match &stmts[..] {
+            [HirStmt::Record(HirRecord::Mono { value: HirExpr::Enum(lowered), .. })] => assert_eq!(lowered, &expected),
+            _ => panic!("expected an enum, found {:?}", stmts),
+        }
+    }
+
+    #[test]
+    fn lower_bad_enum() {
+        check_diagnostics(
+            "e: !enum{\n  A: 0\n  B: \"b\"\n  --- #ranges\n  r: A..D\n  --- #sets\n  s: A\n}",
+            &[
+                "expected a member value at 22..25",
+                "unknown enum section `#sets` at 52..68",
+                "no member named `D` at 48..49",
+            ],
+        );
+    }
+
+    #[test]
+    fn lower_empty_enum_range() {
+        check_diagnostics(
+            "e: !enum{\n  A: 0\n  B: 1\n  --- #ranges\n  r: @self.B..@self.A\n  s: @self.A..@self.A\n}",
+            &["enum range without members at 43..59", "enum range without members at 65..81"],
+        );
+    }
+
+    #[test]
+    fn lower_keeps_going_after_bad_record() {
+        let (db, stmts) = crate::lower(parse("a: ...b\nc: 4"));
+        assert_eq!(db.diagnostics().len(), 1);
+        assert!(matches!(
+            &stmts[..],
+            [
+                HirStmt::Record(HirRecord::Mono { value: HirExpr::Missing, .. }),
+                HirStmt::Record(HirRecord::Mono { value: HirExpr::Int { n: 4 }, .. }),
+            ]
+        ));
+    }
+
     #[test]
     fn lower_accessor() {
         check_stmt(
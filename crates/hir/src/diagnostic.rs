@@ -0,0 +1,132 @@
+use std::fmt;
+
+use crate::Primitive;
+use diagnostics::Diagnostic;
+use smol_str::SmolStr;
+use text_size::TextRange;
+
+/// Something lowering couldn't make sense of. The offending node is lowered
+/// as `Missing` instead, so the rest of the file still comes through.
+#[derive(Debug, PartialEq)]
+pub enum LoweringDiagnostic {
+    /// A statement kind that doesn't lower to anything yet, like an import.
+    UnsupportedStmt { range: TextRange },
+    UnnamedAccessor { range: TextRange },
+    MissingOperator { range: TextRange },
+    UnsupportedOperator { op: SmolStr, range: TextRange },
+    /// A reference, or a segment of `a.b::c`, that isn't a plain name.
+    ExpectedName { range: TextRange },
+    /// A `!name` that isn't one of the built-in types.
+    UnknownType { name: SmolStr, range: TextRange },
+    /// A section in an enum other than `#flags`, `#ranges` or `#groups`.
+    UnknownEnumSection { name: SmolStr, range: TextRange },
+    /// An enum member whose value isn't a whole number, or a flag like `1 .> 2`.
+    EnumValue { range: TextRange },
+    /// A range or group naming something the enum doesn't have.
+    UnknownMember { name: SmolStr, range: TextRange },
+    /// A range that isn't `A..B`, or a group that isn't `A & B`.
+    EnumSet { range: TextRange },
+    /// A range that goes backwards, like `C..A`, or covers nothing, like `A..A`.
+    EmptyEnumRange { range: TextRange },
+    /// An int literal outside the range of an `i64`, counting its sign.
+    IntOutOfRange { range: TextRange },
+}
+
+impl LoweringDiagnostic {
+    pub fn range(&self) -> TextRange {
+        match self {
+            Self::UnsupportedStmt { range }
+            | Self::UnnamedAccessor { range }
+            | Self::MissingOperator { range }
+            | Self::UnsupportedOperator { range, .. }
+            | Self::ExpectedName { range }
+            | Self::UnknownType { range, .. }
+            | Self::UnknownEnumSection { range, .. }
+            | Self::EnumValue { range }
+            | Self::UnknownMember { range, .. }
+            | Self::EnumSet { range }
+            | Self::EmptyEnumRange { range }
+            | Self::IntOutOfRange { range } => *range,
+        }
+    }
+}
+
+impl fmt::Display for LoweringDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedStmt { range } => {
+                write!(f, "unsupported statement at {:?}", range)
+            }
+            Self::UnnamedAccessor { range } => {
+                write!(f, "accessor without a name at {:?}", range)
+            }
+            Self::MissingOperator { range } => {
+                write!(f, "expected an operator at {:?}", range)
+            }
+            Self::UnsupportedOperator { op, range } => {
+                write!(f, "`{}` isn't supported here, at {:?}", op, range)
+            }
+            Self::ExpectedName { range } => write!(f, "expected a name at {:?}", range),
+            Self::UnknownType { name, range } => write!(f, "unknown type `!{}` at {:?}", name, range),
+            Self::UnknownEnumSection { name, range } => {
+                write!(f, "unknown enum section `#{}` at {:?}", name, range)
+            }
+            Self::EnumValue { range } => write!(f, "expected a member value at {:?}", range),
+            Self::UnknownMember { name, range } => write!(f, "no member named `{}` at {:?}", name, range),
+            Self::EnumSet { range } => write!(f, "expected a range or group of members at {:?}", range),
+            Self::EmptyEnumRange { range } => write!(f, "enum range without members at {:?}", range),
+            Self::IntOutOfRange { range } => write!(f, "int literal out of range at {:?}", range),
+        }
+    }
+}
+
+impl From<&LoweringDiagnostic> for Diagnostic {
+    fn from(diagnostic: &LoweringDiagnostic) -> Self {
+        let range = diagnostic.range();
+        match diagnostic {
+            LoweringDiagnostic::UnsupportedStmt { .. } => Diagnostic::error("unsupported statement")
+                .with_primary(range, "this is ignored")
+                .with_note("imports, anonymous records and bare structs aren't lowered yet"),
+            LoweringDiagnostic::UnnamedAccessor { .. } => {
+                Diagnostic::error("accessor without a name").with_primary(range, "expected `#name:`")
+            }
+            LoweringDiagnostic::MissingOperator { .. } => {
+                Diagnostic::error("expected an operator").with_primary(range, "between these values")
+            }
+            LoweringDiagnostic::UnsupportedOperator { op, .. } => {
+                Diagnostic::error(format!("`{}` isn't supported here", op))
+                    .with_primary(range, "unsupported operator")
+            }
+            LoweringDiagnostic::ExpectedName { .. } => {
+                Diagnostic::error("expected a name").with_primary(range, "not a plain name")
+            }
+            LoweringDiagnostic::UnknownType { name, .. } => {
+                let names = Primitive::ALL.map(|primitive| format!("`!{}`", primitive.name()));
+                Diagnostic::error(format!("unknown type `!{}`", name))
+                    .with_primary(range, "not a built-in type")
+                    .with_note(format!("the built-in types are {}", names.join(", ")))
+            }
+            LoweringDiagnostic::UnknownEnumSection { name, .. } => {
+                Diagnostic::error(format!("unknown enum section `#{}`", name))
+                    .with_primary(range, "this section is ignored")
+                    .with_note("an enum's sections are `#flags`, `#ranges` and `#groups`")
+            }
+            LoweringDiagnostic::EnumValue { .. } => Diagnostic::error("expected a member value")
+                .with_primary(range, "not a whole number")
+                .with_note("members are numbered like `A: 3`, and flags like `A: 1 .> 2`"),
+            LoweringDiagnostic::UnknownMember { name, .. } => {
+                Diagnostic::error(format!("no member named `{}`", name))
+                    .with_primary(range, "not a member, range or group of this enum")
+            }
+            LoweringDiagnostic::EnumSet { .. } => Diagnostic::error("expected a range or group of members")
+                .with_primary(range, "not a set of members")
+                .with_note("ranges are written `@self.A..@self.D`, and groups `A & B`"),
+            LoweringDiagnostic::EmptyEnumRange { .. } => Diagnostic::error("enum range without members")
+                .with_primary(range, "covers no members")
+                .with_note("a range goes from a member up to one declared after it, like `@self.A..@self.D`"),
+            LoweringDiagnostic::IntOutOfRange { .. } => Diagnostic::error("int literal out of range")
+                .with_primary(range, "doesn't fit in an int")
+                .with_note(format!("ints go from {} to {}", i64::MIN, i64::MAX)),
+        }
+    }
+}
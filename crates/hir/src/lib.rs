@@ -1,10 +1,17 @@
 mod database;
+mod diagnostic;
+mod resolve;
+mod source_map;
 pub use database::Database;
+pub use diagnostic::LoweringDiagnostic;
+pub use source_map::{RecordId, SourceMap};
+pub use resolve::{resolve, DefId, Definition, Resolution, ResolveDiagnostic, Resolved};
 
 use la_arena::Idx;
 use smol_str::SmolStr;
+use text_size::TextRange;
 
-type ExprIdx = Idx<HirExpr>;
+pub type ExprIdx = Idx<HirExpr>;
 
 #[derive(Debug, PartialEq)]
 pub enum HirStmt {
@@ -19,18 +26,31 @@ pub enum HirStmt {
 pub enum HirRecord {
     Missing,
     Mono {
+        id: RecordId,
         name: Option<SmolStr>,
         value: HirExpr,
         constraint: Option<HirExpr>,
         scope: Option<HirExpr>,
-        schema: bool
+        schema: bool,
+        /// `name?:`, which data can leave out.
+        optional: bool,
     },
     Poly {
+        id: RecordId,
         name: Option<SmolStr>,
         value: Vec<HirRecord>,
         constraint: Option<HirExpr>,
         scope: Option<HirExpr>,
-        schema: bool
+        schema: bool,
+        /// `name?:`, which data can leave out.
+        optional: bool,
+    },
+    /// `--- #name:`, whose records belong to the surrounding struct.
+    Section {
+        id: RecordId,
+        name: Option<SmolStr>,
+        value: Vec<HirRecord>,
+        scope: Option<HirExpr>,
     },
 }
 
@@ -54,24 +74,86 @@ pub enum HirExpr {
         op: BinaryOp,
         lhs: ExprIdx,
         rhs: ExprIdx,
+        range: TextRange,
+    },
+    /// An int, with the sign of a `-` written straight before it.
+    Int {
+        n: i64,
+    },
+    Float {
+        f: f64,
+    },
+    /// A memory address, like `1v3`.
+    Address {
+        segment: u64,
+        offset: u64,
+    },
+    /// `2d6`, rolled when it's evaluated.
+    RandInt {
+        count: u64,
+        sides: u64,
+    },
+    /// `0.8d1.2`, rolled when it's evaluated.
+    RandFloat {
+        low: f64,
+        high: f64,
     },
-    Literal {
-        /// is `None` if the number is too big to fit in a u64
-        n: Option<u64>,
+    String {
+        /// The literal's contents, without the surrounding quotes.
+        s: SmolStr,
     },
     Unary {
         op: UnaryOp,
         expr: ExprIdx,
     },
+    /// A prefix bound such as `>=0` or `*5`.
+    Constraint {
+        op: ConstraintOp,
+        expr: ExprIdx,
+        range: TextRange,
+    },
+    /// `0..6`, `..6`, `3..` or `...`, with the missing ends left open.
+    Range {
+        start: Option<ExprIdx>,
+        end: Option<ExprIdx>,
+        /// Written as `..=`.
+        inclusive: bool,
+        range: TextRange,
+    },
+    /// `a.b::c` is the name `a`, followed by the fields `b` and `c`.
     Ref {
         var: SmolStr,
+        fields: Vec<SmolStr>,
+        range: TextRange,
+    },
+    /// `<; @global::mixins ;>`, as the tokens of each path in the block.
+    Scope {
+        sources: Vec<Vec<SmolStr>>,
     },
     Struct {
-        fields: Vec<Field>
+        fields: Vec<HirRecord>
     },
     List {
         items: Vec<HirExpr>
-    }
+    },
+    /// `!int`, or a type built from what's between its brackets, which for
+    /// `!vec[3 -> !float]` is the length and the type of each item.
+    Type {
+        primitive: Primitive,
+        args: Vec<ExprIdx>,
+        range: TextRange,
+    },
+    /// `#name(args)`, from the module at `path` if it was written as
+    /// `some::module::#name`. What's piped in with `->` is the first argument.
+    Transform {
+        path: Vec<SmolStr>,
+        name: SmolStr,
+        args: Vec<ExprIdx>,
+        range: TextRange,
+    },
+    /// `!enum{ ... }`, with its members' values worked out and its
+    /// `#ranges` and `#groups` expanded.
+    Enum(Enum),
 }
 
 #[derive(Debug, PartialEq)]
@@ -81,11 +163,73 @@ pub enum BinaryOp {
     Mul,
     Div,
     Unify,
+    Dsj,
 }
 
 #[derive(Debug, PartialEq)]
 pub enum UnaryOp {
     Neg,
+    /// `*`, marking the default alternative of a disjunction.
+    Default,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ConstraintOp {
+    Gr,
+    GrEq,
+    Ls,
+    LsEq,
+    /// `*`
+    MultipleOf,
+    /// `/`
+    DivisorOf,
+    /// `^`
+    PowerOf,
+}
+
+/// The built-in types, written with a `!`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Primitive {
+    Int,
+    Float,
+    String,
+    /// An int, float or string, optionally refined: `!scalar[!int & >=0]`.
+    Scalar,
+    /// A fixed number of values of one type: `!vec[3 -> !float]`.
+    Vec,
+    /// One of a set of named members: `!enum{ Red: 0  Green: 1 }`.
+    Enum,
+}
+
+impl Primitive {
+    pub const ALL: [Primitive; 6] = [Self::Int, Self::Float, Self::String, Self::Scalar, Self::Vec, Self::Enum];
+
+    pub fn named(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|primitive| primitive.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Int => "int",
+            Self::Float => "float",
+            Self::String => "string",
+            Self::Scalar => "scalar",
+            Self::Vec => "vec",
+            Self::Enum => "enum",
+        }
+    }
+}
+
+/// An enum's members in declaration order, and the named sets of them.
+///
+/// Under `#flags` every member is a bit, and a value can be any combination
+/// of them. `#ranges` like `prim: @self.A..@self.D` and `#groups` like
+/// `AB: A & B` are expanded into the members they cover.
+#[derive(Debug, PartialEq)]
+pub struct Enum {
+    pub members: Vec<(SmolStr, u64)>,
+    pub sets: Vec<(SmolStr, Vec<SmolStr>)>,
+    pub flags: bool,
 }
 
 #[derive(Debug, PartialEq)]
@@ -104,6 +248,19 @@ pub struct Path {
     path: Vec<SmolStr>
 }
 
+/// The records of a struct body as its fields, with the records of any
+/// sections spliced in where the section was written.
+pub fn visible(records: &[HirRecord]) -> Vec<&HirRecord> {
+    let mut out = Vec::new();
+    for record in records {
+        match record {
+            HirRecord::Section { value, .. } => out.extend(visible(value)),
+            _ => out.push(record),
+        }
+    }
+    out
+}
+
 pub fn lower(ast: ast::Root) -> (Database, Vec<HirStmt>) {
     let mut db = Database::default();
     let stmts = ast.stmts().filter_map(|stmt| db.lower_stmt(stmt)).collect();
@@ -0,0 +1,551 @@
+/*
+TL;DR: Every named record becomes a `Definition`, and every reference is
+bound to one by looking outward through the scopes around it.
+
+    a: 1
+    b: {            scope of b: [c, d]
+      c: a          -> root scope: a
+      d: c          -> scope of b: c
+    }
+    e: b.c          -> root scope: b, then its field c
+
+Sections are flattened into the struct they're written in. Scope blocks and
+accessors bring in names from other files, which can't be seen from here, so
+anything that isn't found locally but might come from them is `External`.
+*/
+
+use std::fmt;
+
+use crate::{visible, Database, HirExpr, HirRecord, HirStmt, RecordId};
+use diagnostics::Diagnostic;
+use la_arena::{Arena, Idx};
+use smol_str::SmolStr;
+use text_size::TextRange;
+
+pub type DefId = Idx<Definition>;
+
+#[derive(Debug, PartialEq)]
+pub struct Definition {
+    /// Schemas are named `$name`, like the references to them.
+    pub name: SmolStr,
+    pub parent: Option<DefId>,
+    /// The record that defines it, for finding it in the source map.
+    pub record: RecordId,
+    /// The records in the definition's struct, or `None` if its value isn't a
+    /// struct literal, in which case there's nothing to check fields against.
+    pub fields: Option<Vec<DefId>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Resolved {
+    Definition(DefId),
+    /// Reached through an accessor or a scope block, or a field of something
+    /// that isn't a struct literal.
+    External,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ResolveDiagnostic {
+    Unresolved { name: SmolStr, range: TextRange },
+    /// More than one record with the name in the innermost scope that has it.
+    Ambiguous {
+        name: SmolStr,
+        range: TextRange,
+        /// Where each of the candidates is defined.
+        defined: Vec<TextRange>,
+    },
+    NoField { name: SmolStr, field: SmolStr, range: TextRange },
+}
+
+impl ResolveDiagnostic {
+    pub fn range(&self) -> TextRange {
+        match self {
+            Self::Unresolved { range, .. }
+            | Self::Ambiguous { range, .. }
+            | Self::NoField { range, .. } => *range,
+        }
+    }
+}
+
+impl fmt::Display for ResolveDiagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unresolved { name, range } => {
+                write!(f, "unresolved name `{}` at {:?}", name, range)
+            }
+            Self::Ambiguous { name, range, .. } => {
+                write!(f, "`{}` is defined more than once, at {:?}", name, range)
+            }
+            Self::NoField { name, field, range } => {
+                write!(f, "`{}` has no field `{}` at {:?}", name, field, range)
+            }
+        }
+    }
+}
+
+impl From<&ResolveDiagnostic> for Diagnostic {
+    fn from(diagnostic: &ResolveDiagnostic) -> Self {
+        match diagnostic {
+            ResolveDiagnostic::Unresolved { name, range } => {
+                Diagnostic::error(format!("unresolved name `{}`", name))
+                    .with_primary(*range, "not found in this scope")
+                    .with_note("names are looked up in the enclosing structs, then the file's records and accessors")
+            }
+            ResolveDiagnostic::Ambiguous { name, range, defined } => {
+                let diagnostic = Diagnostic::error(format!("`{}` is defined more than once", name))
+                    .with_primary(*range, "ambiguous reference");
+                defined
+                    .iter()
+                    .fold(diagnostic, |diagnostic, range| diagnostic.with_label(*range, "defined here"))
+            }
+            ResolveDiagnostic::NoField { name, field, range } => {
+                Diagnostic::error(format!("`{}` has no field `{}`", name, field))
+                    .with_primary(*range, format!("`{}` isn't in `{}`", field, name))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Resolution {
+    pub defs: Arena<Definition>,
+    /// Every reference that resolved, by where it was written.
+    pub refs: Vec<(TextRange, Resolved)>,
+    pub diagnostics: Vec<ResolveDiagnostic>,
+}
+
+impl Resolution {
+    pub fn at(&self, range: TextRange) -> Option<Resolved> {
+        self.refs.iter().find(|(r, _)| *r == range).map(|(_, resolved)| *resolved)
+    }
+}
+
+#[derive(Default)]
+struct Scope {
+    defs: Vec<DefId>,
+    /// Accessor names, which resolve to another file.
+    imports: Vec<SmolStr>,
+    /// Whether a scope block brought in names that can't be seen.
+    open: bool,
+}
+
+struct Resolver<'a> {
+    db: &'a Database,
+    res: Resolution,
+    scopes: Vec<Scope>,
+}
+
+pub fn resolve(db: &Database, stmts: &[HirStmt]) -> Resolution {
+    let mut records = Vec::new();
+    let mut imports = Vec::new();
+    for stmt in stmts {
+        match stmt {
+            HirStmt::Record(record) => records.push(record),
+            HirStmt::Accessor { name, .. } => imports.push(name.clone()),
+            _ => {}
+        }
+    }
+
+    let mut resolver = Resolver {
+        db,
+        res: Resolution::default(),
+        scopes: Vec::new(),
+    };
+
+    let defs = resolver.declare(records.iter().copied(), None);
+    resolver.scopes.push(Scope {
+        defs: defs.clone(),
+        imports,
+        open: false,
+    });
+    resolver.check(records.iter().copied(), &mut defs.into_iter());
+
+    resolver.res
+}
+
+impl<'a> Resolver<'a> {
+    /// Allocates a definition for every named record, and for the records
+    /// nested in their struct literals.
+    fn declare(
+        &mut self,
+        records: impl IntoIterator<Item = &'a HirRecord>,
+        parent: Option<DefId>,
+    ) -> Vec<DefId> {
+        let mut ids = Vec::new();
+        for record in records {
+            let (record, name, schema, body) = match record {
+                HirRecord::Section { value, .. } => {
+                    ids.extend(self.declare(value, parent));
+                    continue;
+                }
+                HirRecord::Poly { id, name: Some(name), value, schema, .. } => {
+                    (*id, name, *schema, Some(visible(value)))
+                }
+                HirRecord::Mono { id, name: Some(name), value, schema, .. } => {
+                    let body = match value {
+                        HirExpr::Struct { fields } => Some(visible(fields)),
+                        _ => None,
+                    };
+                    (*id, name, *schema, body)
+                }
+                _ => continue,
+            };
+
+            let id = self.res.defs.alloc(Definition {
+                name: key(name, schema),
+                parent,
+                record,
+                fields: None,
+            });
+            if let Some(body) = body {
+                let fields = self.declare(body, Some(id));
+                self.res.defs[id].fields = Some(fields);
+            }
+            ids.push(id);
+        }
+        ids
+    }
+
+    /// Walks records in the same order `declare` did, taking their
+    /// definitions from `ids`.
+    fn check(
+        &mut self,
+        records: impl IntoIterator<Item = &'a HirRecord>,
+        ids: &mut impl Iterator<Item = DefId>,
+    ) {
+        for record in records {
+            let (name, value, constraint, scope) = match record {
+                HirRecord::Missing => continue,
+                HirRecord::Section { value, scope, .. } => {
+                    let open = self.open(scope);
+                    self.check(value, ids);
+                    self.close(open);
+                    continue;
+                }
+                HirRecord::Mono { name, constraint, scope, .. }
+                | HirRecord::Poly { name, constraint, scope, .. } => (name, record, constraint, scope),
+            };
+
+            let id = if name.is_some() { ids.next() } else { None };
+            if let Some(constraint) = constraint {
+                self.expr(constraint, id);
+            }
+
+            let open = self.open(scope);
+            match value {
+                HirRecord::Poly { value, .. } => self.body(value, id),
+                HirRecord::Mono { value: HirExpr::Struct { fields }, .. } if id.is_some() => {
+                    self.body(fields, id)
+                }
+                HirRecord::Mono { value, .. } => self.expr(value, id),
+                _ => {}
+            }
+            self.close(open);
+        }
+    }
+
+    /// Checks the records of a struct that `declare` has already been through.
+    fn body(&mut self, records: &'a [HirRecord], id: Option<DefId>) {
+        let fields = id
+            .and_then(|id| self.res.defs[id].fields.clone())
+            .unwrap_or_default();
+
+        self.scopes.push(Scope {
+            defs: fields.clone(),
+            ..Scope::default()
+        });
+        self.check(records, &mut fields.into_iter());
+        self.scopes.pop();
+    }
+
+    fn expr(&mut self, expr: &'a HirExpr, parent: Option<DefId>) {
+        match expr {
+            HirExpr::Binary { lhs, rhs, .. } => {
+                self.expr(&self.db[*lhs], parent);
+                self.expr(&self.db[*rhs], parent);
+            }
+            HirExpr::Unary { expr, .. } | HirExpr::Constraint { expr, .. } => {
+                self.expr(&self.db[*expr], parent)
+            }
+            HirExpr::Range { start, end, .. } => {
+                for bound in start.iter().chain(end) {
+                    self.expr(&self.db[*bound], parent);
+                }
+            }
+            HirExpr::Ref { var, fields, range } => self.reference(var, fields, *range),
+            HirExpr::Struct { fields } => {
+                // Not a record's own struct, so it hasn't been declared yet.
+                let ids = self.declare(visible(fields), parent);
+                self.scopes.push(Scope {
+                    defs: ids.clone(),
+                    ..Scope::default()
+                });
+                self.check(fields, &mut ids.into_iter());
+                self.scopes.pop();
+            }
+            HirExpr::List { items } => {
+                for item in items {
+                    self.expr(item, parent);
+                }
+            }
+            HirExpr::Type { args, .. } | HirExpr::Transform { args, .. } => {
+                for arg in args {
+                    self.expr(&self.db[*arg], parent);
+                }
+            }
+            HirExpr::Missing
+            | HirExpr::Int { .. }
+            | HirExpr::Float { .. }
+            | HirExpr::Address { .. }
+            | HirExpr::RandInt { .. }
+            | HirExpr::RandFloat { .. }
+            | HirExpr::String { .. }
+            | HirExpr::Scope { .. }
+            | HirExpr::Enum(_) => {}
+        }
+    }
+
+    fn reference(&mut self, var: &SmolStr, fields: &[SmolStr], range: TextRange) {
+        let mut open = false;
+        let mut found = None;
+        for scope in self.scopes.iter().rev() {
+            let matches = scope
+                .defs
+                .iter()
+                .filter(|id| self.res.defs[**id].name == *var)
+                .collect::<Vec<&DefId>>();
+
+            match matches.as_slice() {
+                [id] => {
+                    found = Some(**id);
+                    break;
+                }
+                [_, ..] => {
+                    let defined = self.ranges(&matches);
+                    self.res.diagnostics.push(ResolveDiagnostic::Ambiguous {
+                        name: var.clone(),
+                        range,
+                        defined,
+                    });
+                    return;
+                }
+                [] if scope.imports.contains(var) => {
+                    self.res.refs.push((range, Resolved::External));
+                    return;
+                }
+                [] => open |= scope.open,
+            }
+        }
+
+        let mut id = match found {
+            Some(id) => id,
+            None if open => {
+                self.res.refs.push((range, Resolved::External));
+                return;
+            }
+            None => {
+                self.res.diagnostics.push(ResolveDiagnostic::Unresolved {
+                    name: var.clone(),
+                    range,
+                });
+                return;
+            }
+        };
+
+        for field in fields {
+            let members = match &self.res.defs[id].fields {
+                Some(members) => members,
+                None => {
+                    self.res.refs.push((range, Resolved::External));
+                    return;
+                }
+            };
+
+            let matches = members
+                .iter()
+                .filter(|member| self.res.defs[**member].name == *field)
+                .collect::<Vec<&DefId>>();
+
+            id = match matches.as_slice() {
+                [member] => **member,
+                [] => {
+                    self.res.diagnostics.push(ResolveDiagnostic::NoField {
+                        name: self.res.defs[id].name.clone(),
+                        field: field.clone(),
+                        range,
+                    });
+                    return;
+                }
+                _ => {
+                    let defined = self.ranges(&matches);
+                    self.res.diagnostics.push(ResolveDiagnostic::Ambiguous {
+                        name: field.clone(),
+                        range,
+                        defined,
+                    });
+                    return;
+                }
+            };
+        }
+
+        self.res.refs.push((range, Resolved::Definition(id)));
+    }
+
+    fn ranges(&self, defs: &[&DefId]) -> Vec<TextRange> {
+        defs.iter()
+            .filter_map(|id| self.db.source_map().record(self.res.defs[**id].record))
+            .map(|ptr| ptr.range())
+            .collect()
+    }
+
+    /// Marks the innermost scope as open if `scope` is a scope block, and
+    /// returns whether it did, so `close` can put it back.
+    fn open(&mut self, scope: &Option<HirExpr>) -> bool {
+        let opens = matches!(scope, Some(HirExpr::Scope { sources }) if !sources.is_empty());
+        if opens {
+            self.scopes.push(Scope {
+                open: true,
+                ..Scope::default()
+            });
+        }
+        opens
+    }
+
+    fn close(&mut self, opened: bool) {
+        if opened {
+            self.scopes.pop();
+        }
+    }
+}
+
+fn key(name: &SmolStr, schema: bool) -> SmolStr {
+    if schema {
+        format!("${}", name).into()
+    } else {
+        name.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve_str(input: &str) -> (Resolution, String) {
+        let root = ast::Root::cast(parser::parse(input).syntax()).unwrap();
+        let (db, stmts) = crate::lower(root);
+        (resolve(&db, &stmts), input.to_string())
+    }
+
+    /// What the last reference written as `text` resolved to.
+    fn target(res: &Resolution, input: &str, text: &str) -> Option<Resolved> {
+        let start = input.rfind(text).unwrap() as u32;
+        res.at(TextRange::new(start.into(), (start + text.len() as u32).into()))
+    }
+
+    fn name(res: &Resolution, resolved: Option<Resolved>) -> SmolStr {
+        match resolved {
+            Some(Resolved::Definition(id)) => res.defs[id].name.clone(),
+            other => panic!("expected a definition, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn resolve_root_reference() {
+        let (res, input) = resolve_str("a: 1\nb: a");
+        assert!(res.diagnostics.is_empty());
+        assert_eq!(name(&res, target(&res, &input, "a")), "a");
+    }
+
+    #[test]
+    fn resolve_innermost_first() {
+        let (res, input) = resolve_str("a: 1\nb: {\n  a: 2\n  c: a\n}");
+        let id = match target(&res, &input, "a") {
+            Some(Resolved::Definition(id)) => id,
+            other => panic!("{:?}", other),
+        };
+
+        assert_eq!(res.defs[id].name, "a");
+        assert_eq!(res.defs[res.defs[id].parent.unwrap()].name, "b");
+    }
+
+    #[test]
+    fn resolve_fields() {
+        let (res, input) = resolve_str("b: {\n  c: 1\n}\ne: b.c");
+        assert!(res.diagnostics.is_empty());
+
+        let id = match target(&res, &input, "b.c") {
+            Some(Resolved::Definition(id)) => id,
+            other => panic!("{:?}", other),
+        };
+        assert_eq!(res.defs[id].name, "c");
+    }
+
+    #[test]
+    fn resolve_schema_reference() {
+        let (res, input) = resolve_str("$s: {\n  x: 1\n}\na: $s");
+        assert!(res.diagnostics.is_empty());
+        assert_eq!(name(&res, target(&res, &input, "$s")), "$s");
+    }
+
+    #[test]
+    fn resolve_section_records() {
+        let (res, input) = resolve_str("--- #sec:\nx: 1\n--- #other:\ny: x");
+        assert!(res.diagnostics.is_empty(), "{:?}", res.diagnostics);
+        assert_eq!(name(&res, target(&res, &input, "x")), "x");
+    }
+
+    #[test]
+    fn report_unresolved() {
+        let (res, _) = resolve_str("a: 1\nb: nope");
+        let diagnostics = res.diagnostics.iter().map(ToString::to_string).collect::<Vec<String>>();
+        assert_eq!(diagnostics, vec!["unresolved name `nope` at 8..12"]);
+    }
+
+    #[test]
+    fn report_missing_field() {
+        let (res, _) = resolve_str("b: {\n  c: 1\n}\ne: b.d");
+        let diagnostics = res.diagnostics.iter().map(ToString::to_string).collect::<Vec<String>>();
+        assert_eq!(diagnostics, vec!["`b` has no field `d` at 17..20"]);
+    }
+
+    #[test]
+    fn report_ambiguous() {
+        let (res, _) = resolve_str("a: 1\na: 2\nb: a");
+        let diagnostics = res.diagnostics.iter().map(ToString::to_string).collect::<Vec<String>>();
+        assert_eq!(diagnostics, vec!["`a` is defined more than once, at 13..14"]);
+    }
+
+    #[test]
+    fn ambiguous_diagnostic_points_at_definitions() {
+        let (res, input) = resolve_str("a: 1\na: 2\nb: a");
+        let diagnostic = Diagnostic::from(&res.diagnostics[0]);
+
+        assert_eq!(
+            diagnostic.render("a.vada", &input),
+            "error: `a` is defined more than once
+ --> a.vada:3:4
+  |
+1 | a: 1
+  | ---- defined here
+2 | a: 2
+  | ---- defined here
+3 | b: a
+  |    ^ ambiguous reference
+"
+        );
+    }
+
+    #[test]
+    fn scope_blocks_open_the_scope() {
+        let (res, input) = resolve_str("--- #other: <; @global::mixins ;>\ny: fromMixins");
+        assert!(res.diagnostics.is_empty(), "{:?}", res.diagnostics);
+        assert_eq!(target(&res, &input, "fromMixins"), Some(Resolved::External));
+    }
+
+    #[test]
+    fn accessors_are_external() {
+        let (res, input) = resolve_str("#zalgo: @global::zalgo\na: zalgo.tools");
+        assert!(res.diagnostics.is_empty(), "{:?}", res.diagnostics);
+        assert_eq!(target(&res, &input, "zalgo.tools"), Some(Resolved::External));
+    }
+}
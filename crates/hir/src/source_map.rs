@@ -0,0 +1,119 @@
+/*
+TL;DR: Where each piece of HIR came from, kept beside the HIR rather than in
+it, so the HIR stays easy to build and compare.
+
+    ExprIdx  <->  SyntaxNodePtr
+    RecordId <->  SyntaxNodePtr
+
+Expressions nested in other expressions live in the `Database` arena and get
+an `ExprIdx`. Records aren't kept in an arena, so each carries a `RecordId`.
+*/
+
+use std::collections::HashMap;
+
+use crate::ExprIdx;
+use la_arena::ArenaMap;
+use syntax::SyntaxNodePtr;
+use text_size::TextSize;
+
+/// Identifies a record in its `Database`'s source map.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct RecordId(pub(crate) u32);
+
+#[derive(Debug, PartialEq, Default)]
+pub struct SourceMap {
+    exprs: ArenaMap<ExprIdx, SyntaxNodePtr>,
+    expr_nodes: HashMap<SyntaxNodePtr, ExprIdx>,
+    records: Vec<SyntaxNodePtr>,
+}
+
+impl SourceMap {
+    pub(crate) fn insert_expr(&mut self, idx: ExprIdx, ptr: SyntaxNodePtr) {
+        self.exprs.insert(idx, ptr);
+        self.expr_nodes.insert(ptr, idx);
+    }
+
+    pub(crate) fn alloc_record(&mut self, ptr: SyntaxNodePtr) -> RecordId {
+        self.records.push(ptr);
+        RecordId(self.records.len() as u32 - 1)
+    }
+
+    pub fn expr(&self, idx: ExprIdx) -> Option<SyntaxNodePtr> {
+        self.exprs.get(idx).copied()
+    }
+
+    pub fn record(&self, id: RecordId) -> Option<SyntaxNodePtr> {
+        self.records.get(id.0 as usize).copied()
+    }
+
+    pub fn expr_for_node(&self, ptr: SyntaxNodePtr) -> Option<ExprIdx> {
+        self.expr_nodes.get(&ptr).copied()
+    }
+
+    pub fn record_for_node(&self, ptr: SyntaxNodePtr) -> Option<RecordId> {
+        self.records
+            .iter()
+            .position(|record| *record == ptr)
+            .map(|idx| RecordId(idx as u32))
+    }
+
+    /// The innermost expression with an `ExprIdx` that covers `offset`.
+    pub fn expr_at(&self, offset: TextSize) -> Option<ExprIdx> {
+        self.exprs
+            .iter()
+            .filter(|(_, ptr)| ptr.range().contains_inclusive(offset))
+            .min_by_key(|(_, ptr)| ptr.range().len())
+            .map(|(idx, _)| idx)
+    }
+
+    /// The innermost record that covers `offset`.
+    pub fn record_at(&self, offset: TextSize) -> Option<RecordId> {
+        self.records
+            .iter()
+            .enumerate()
+            .filter(|(_, ptr)| ptr.range().contains_inclusive(offset))
+            .min_by_key(|(_, ptr)| ptr.range().len())
+            .map(|(idx, _)| RecordId(idx as u32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{HirExpr, HirRecord, HirStmt};
+    use syntax::SyntaxKind;
+
+    fn lower(input: &str) -> (crate::Database, Vec<HirStmt>) {
+        crate::lower(ast::Root::cast(parser::parse(input).syntax()).unwrap())
+    }
+
+    #[test]
+    fn expr_at_offset() {
+        let (db, _) = lower("a: 1 + foo");
+        let idx = db.source_map().expr_at(8.into()).unwrap();
+
+        assert!(matches!(&db[idx], HirExpr::Ref { var, .. } if var == "foo"));
+
+        let ptr = db.source_map().expr(idx).unwrap();
+        assert_eq!(ptr.kind(), SyntaxKind::Ref);
+        assert_eq!(ptr.range(), text_size::TextRange::new(7.into(), 10.into()));
+        assert_eq!(db.source_map().expr_for_node(ptr), Some(idx));
+    }
+
+    #[test]
+    fn innermost_record_at_offset() {
+        let (db, stmts) = lower("a: {\n  b: 2\n}");
+        let inner = match &stmts[0] {
+            HirStmt::Record(HirRecord::Poly { value, .. }) => match &value[0] {
+                HirRecord::Mono { id, .. } => *id,
+                other => panic!("{:?}", other),
+            },
+            other => panic!("{:?}", other),
+        };
+
+        assert_eq!(db.source_map().record_at(8.into()), Some(inner));
+
+        let ptr = db.source_map().record(inner).unwrap();
+        assert_eq!(ptr.kind(), SyntaxKind::Record);
+        assert_eq!(db.source_map().record_for_node(ptr), Some(inner));
+    }
+}
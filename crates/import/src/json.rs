@@ -0,0 +1,283 @@
+use crate::{field, index, number, ImportError, Imported, Locations};
+use eval::{Step, Value};
+use smol_str::SmolStr;
+use text_size::{TextRange, TextSize};
+
+/// Reads a JSON document.
+pub fn json(text: &str) -> Result<Imported, ImportError> {
+    let mut reader = Reader {
+        text,
+        pos: 0,
+        locations: Locations::default(),
+    };
+
+    let value = reader.value(&[])?;
+    reader.skip_whitespace();
+    if reader.pos < text.len() {
+        return Err(reader.error("expected the end of the file"));
+    }
+
+    Ok(reader.locations.finish(value))
+}
+
+struct Reader<'a> {
+    text: &'a str,
+    pos: usize,
+    locations: Locations,
+}
+
+impl Reader<'_> {
+    fn value(&mut self, path: &[Step]) -> Result<Value, ImportError> {
+        self.skip_whitespace();
+        let start = self.pos;
+
+        let value = match self.peek() {
+            Some('{') => self.object(path)?,
+            Some('[') => self.array(path)?,
+            Some('"') => Value::String(self.string()?),
+            Some(c) if c == '-' || c.is_ascii_digit() => self.number()?,
+            Some(c) if c.is_ascii_alphabetic() => {
+                let word = self.word().to_string();
+                let range = self.range(start);
+                return Err(match word.as_str() {
+                    "true" | "false" => ImportError::new("Vada has no booleans", range),
+                    "null" => ImportError::new("Vada has no null", range),
+                    _ => ImportError::new(format!("expected a value, but found `{}`", word), range),
+                });
+            }
+            _ => return Err(self.error("expected a value")),
+        };
+
+        self.locations.add(path, self.range(start));
+        Ok(value)
+    }
+
+    fn object(&mut self, path: &[Step]) -> Result<Value, ImportError> {
+        self.pos += 1;
+        let mut fields: Vec<(SmolStr, Value)> = Vec::new();
+
+        self.skip_whitespace();
+        if self.eat('}') {
+            return Ok(Value::Struct(fields));
+        }
+
+        loop {
+            self.skip_whitespace();
+            let start = self.pos;
+            if self.peek() != Some('"') {
+                return Err(self.error("expected a key"));
+            }
+            let name = self.string()?;
+            if fields.iter().any(|(n, _)| *n == name) {
+                return Err(ImportError::new(format!("`{}` is already a key here", name), self.range(start)));
+            }
+
+            self.skip_whitespace();
+            if !self.eat(':') {
+                return Err(self.error("expected `:`"));
+            }
+            let value = self.value(&field(path, &name))?;
+            fields.push((name, value));
+
+            self.skip_whitespace();
+            if self.eat('}') {
+                return Ok(Value::Struct(fields));
+            }
+            if !self.eat(',') {
+                return Err(self.error("expected `,` or `}`"));
+            }
+        }
+    }
+
+    fn array(&mut self, path: &[Step]) -> Result<Value, ImportError> {
+        self.pos += 1;
+        let mut items = Vec::new();
+
+        self.skip_whitespace();
+        if self.eat(']') {
+            return Ok(Value::List(items));
+        }
+
+        loop {
+            items.push(self.value(&index(path, items.len()))?);
+
+            self.skip_whitespace();
+            if self.eat(']') {
+                return Ok(Value::List(items));
+            }
+            if !self.eat(',') {
+                return Err(self.error("expected `,` or `]`"));
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<SmolStr, ImportError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut out = String::new();
+
+        loop {
+            let c = match self.peek() {
+                Some(c) => c,
+                None => return Err(ImportError::new("this string is never closed", self.range(start))),
+            };
+            self.pos += c.len_utf8();
+
+            match c {
+                '"' => return Ok(out.into()),
+                '\\' => out.push(self.escape()?),
+                c if c.is_control() => return Err(self.error("strings can't hold control characters")),
+                c => out.push(c),
+            }
+        }
+    }
+
+    fn escape(&mut self) -> Result<char, ImportError> {
+        let start = self.pos - 1;
+        let c = self.peek();
+        self.pos += c.map_or(0, char::len_utf8);
+
+        Ok(match c {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let high = self.hex(start)?;
+                // Characters outside the basic plane come as two halves.
+                if (0xd800..0xdc00).contains(&high) && self.text[self.pos..].starts_with("\\u") {
+                    self.pos += 2;
+                    let low = self.hex(start)?;
+                    let c = 0x10000 + ((high - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                    char::from_u32(c).ok_or_else(|| ImportError::new("invalid escape", self.range(start)))?
+                } else {
+                    char::from_u32(high).ok_or_else(|| ImportError::new("invalid escape", self.range(start)))?
+                }
+            }
+            _ => return Err(ImportError::new("invalid escape", self.range(start))),
+        })
+    }
+
+    fn hex(&mut self, start: usize) -> Result<u32, ImportError> {
+        let digits = self.text.get(self.pos..self.pos + 4).unwrap_or("");
+        let n = u32::from_str_radix(digits, 16)
+            .ok()
+            .filter(|_| digits.chars().all(|c| c.is_ascii_hexdigit()));
+        self.pos += digits.len();
+        n.ok_or_else(|| ImportError::new("invalid escape", self.range(start)))
+    }
+
+    fn number(&mut self) -> Result<Value, ImportError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E'))
+        {
+            self.pos += 1;
+        }
+
+        let text = &self.text[start..self.pos];
+        number(text).ok_or_else(|| ImportError::new(format!("`{}` isn't a number", text), self.range(start)))
+    }
+
+    fn word(&mut self) -> &str {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_ascii_alphanumeric()) {
+            self.pos += 1;
+        }
+        &self.text[start..self.pos]
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek().is_some_and(|c| matches!(c, ' ' | '\t' | '\n' | '\r')) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.pos..].chars().next()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        let found = self.peek() == Some(c);
+        if found {
+            self.pos += 1;
+        }
+        found
+    }
+
+    fn range(&self, start: usize) -> TextRange {
+        TextRange::new(TextSize::from(start as u32), TextSize::from(self.pos as u32))
+    }
+
+    /// An error at the next character.
+    fn error(&self, message: &str) -> ImportError {
+        let len = self.peek().map_or(0, char::len_utf8);
+        let start = TextSize::from(self.pos as u32);
+        let found = match self.peek() {
+            Some(c) => format!("{}, but found `{}`", message, c),
+            None => format!("{}, but the file ended", message),
+        };
+        ImportError::new(found, TextRange::at(start, TextSize::from(len as u32)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(fields: Vec<(&str, Value)>) -> Value {
+        Value::Struct(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.into(), value))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn reads_values_in_order() {
+        let imported = json(r#" {"zed": 1, "alpha": [2.5, "x\n\u00e9\ud83d\ude00"], "n": -3e2, "empty": {}} "#).unwrap();
+        assert_eq!(
+            imported.value,
+            fields(vec![
+                ("zed", Value::Int(1)),
+                ("alpha", Value::List(vec![Value::Float(2.5), Value::String("x\né😀".into())])),
+                ("n", Value::Float(-300.0)),
+                ("empty", fields(vec![])),
+            ])
+        );
+    }
+
+    #[test]
+    fn keeps_ranges() {
+        let text = "{\n  \"a\": {\"b\": [1, \"two\"]}\n}";
+        let imported = json(text).unwrap();
+
+        let at = |path: &[Step]| &text[imported.locate(path).unwrap()];
+        let key = |name: &str| Step::Field(name.into());
+        assert_eq!(at(&[key("a"), key("b"), Step::Index(1)]), "\"two\"");
+        assert_eq!(at(&[key("a"), key("b")]), "[1, \"two\"]");
+        assert_eq!(at(&[]), text);
+    }
+
+    #[test]
+    fn refuses_booleans_and_null() {
+        let error = json(r#"{"alive": true}"#).unwrap_err();
+        assert_eq!(error.message, "Vada has no booleans");
+        assert_eq!(error.range, TextRange::new(10.into(), 14.into()));
+        assert_eq!(json("[null]").unwrap_err().message, "Vada has no null");
+    }
+
+    #[test]
+    fn reports_syntax_errors() {
+        assert_eq!(json(r#"{"a": 1,}"#).unwrap_err().message, "expected a key, but found `}`");
+        assert_eq!(json(r#"{"a": 1"#).unwrap_err().message, "expected `,` or `}`, but the file ended");
+        assert_eq!(json(r#"{"a": 1, "a": 2}"#).unwrap_err().message, "`a` is already a key here");
+        assert_eq!(json("[1] 2").unwrap_err().message, "expected the end of the file, but found `2`");
+    }
+}
@@ -0,0 +1,175 @@
+/*
+TL;DR: JSON and YAML data files in, evaluated values out, so data that was
+never written in Vada can still be checked against Vada schemas.
+
+    {"goblin": {"hp": -5}}   becomes   { goblin: { hp: -5 } }
+
+and validating it against `$unit: { hp: >=0 }` points back at the file it
+came from:
+
+    error: `goblin.hp`: -5 does not satisfy `>=0`
+     --> units.json:1:19
+      |
+    1 | {"goblin": {"hp": -5}}
+      |                   ^^ not allowed by the schema
+
+Every value's range in the file is kept by its path, as the keys and indices
+that lead to it, so keys with `.` or `[` in them are found too. Objects and mappings become structs in
+the order their keys were written, arrays and sequences become lists, and
+numbers become ints when they're whole and fit, floats otherwise. Vada has no
+booleans or nulls, so those are errors rather than being guessed at.
+*/
+
+mod json;
+mod yaml;
+
+pub use json::json;
+pub use yaml::yaml;
+
+use diagnostics::Diagnostic;
+use eval::{Step, Value};
+use std::fmt;
+use std::path::Path;
+use text_size::TextRange;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Yaml,
+}
+
+impl Format {
+    /// The format a file is in, going by its extension.
+    pub fn of(file: &Path) -> Option<Self> {
+        match file.extension()?.to_str()? {
+            "json" => Some(Self::Json),
+            "yaml" | "yml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Reads a data file in the given format.
+pub fn import(text: &str, format: Format) -> Result<Imported, ImportError> {
+    match format {
+        Format::Json => json(text),
+        Format::Yaml => yaml(text),
+    }
+}
+
+/// A data file as a value, and where in the file each part of it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Imported {
+    pub value: Value,
+    locations: Vec<(Vec<Step>, TextRange)>,
+}
+
+impl Imported {
+    /// Where the value at `path` was written. Paths the file doesn't have,
+    /// like a field a schema wants but the data left out, point at the
+    /// closest thing that is there.
+    pub fn locate(&self, path: &[Step]) -> Option<TextRange> {
+        let mut path = path;
+        loop {
+            if let Some((_, range)) = self.locations.iter().find(|(p, _)| p == path) {
+                return Some(*range);
+            }
+            path = path.split_last()?.1;
+        }
+    }
+}
+
+/// A file that isn't valid JSON or YAML, or that has something in it Vada
+/// has no value for.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImportError {
+    pub message: String,
+    pub range: TextRange,
+}
+
+impl ImportError {
+    fn new(message: impl Into<String>, range: TextRange) -> Self {
+        Self {
+            message: message.into(),
+            range,
+        }
+    }
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<&ImportError> for Diagnostic {
+    fn from(error: &ImportError) -> Self {
+        Diagnostic::error(error.message.clone()).with_primary(error.range, "here")
+    }
+}
+
+/// Collects values and their ranges as a file is read.
+#[derive(Default)]
+struct Locations(Vec<(Vec<Step>, TextRange)>);
+
+impl Locations {
+    fn add(&mut self, path: &[Step], range: TextRange) {
+        self.0.push((path.to_vec(), range));
+    }
+
+    fn finish(self, value: Value) -> Imported {
+        Imported {
+            value,
+            locations: self.0,
+        }
+    }
+}
+
+fn field(path: &[Step], name: &str) -> Vec<Step> {
+    let mut path = path.to_vec();
+    path.push(Step::Field(name.into()));
+    path
+}
+
+fn index(path: &[Step], idx: usize) -> Vec<Step> {
+    let mut path = path.to_vec();
+    path.push(Step::Index(idx));
+    path
+}
+
+/// A number as an int if it's whole and fits, a float otherwise.
+fn number(text: &str) -> Option<Value> {
+    if let Ok(n) = text.parse::<i64>() {
+        return Some(Value::Int(n));
+    }
+    text.parse::<f64>().ok().map(Value::Float)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(name: &str) -> Step {
+        Step::Field(name.into())
+    }
+
+    #[test]
+    fn locates_missing_paths_at_their_parent() {
+        let imported = json(r#"{"units": [{"hp": 1}]}"#).unwrap();
+        let unit = [key("units"), Step::Index(0)];
+
+        assert_eq!(imported.locate(&[key("units"), Step::Index(0), key("hp")]), Some(TextRange::new(18.into(), 19.into())));
+        assert_eq!(imported.locate(&[key("units"), Step::Index(0), key("speed")]), imported.locate(&unit));
+        assert_eq!(imported.locate(&[key("nope")]), imported.locate(&[]));
+    }
+
+    #[test]
+    fn locates_keys_with_dots() {
+        let imported = json(r#"{"cfg": {"v1": 1, "v1.2": {"hp": 2}}}"#).unwrap();
+
+        assert_eq!(imported.locate(&[key("cfg"), key("v1.2"), key("hp")]), Some(TextRange::new(33.into(), 34.into())));
+        assert_eq!(imported.locate(&[key("cfg"), key("v2.0")]), imported.locate(&[key("cfg")]));
+    }
+}
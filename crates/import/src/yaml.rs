@@ -0,0 +1,321 @@
+use crate::{field, index, number, ImportError, Imported, Locations};
+use eval::{Step, Value};
+use smol_str::SmolStr;
+use std::collections::HashMap;
+use text_size::{TextRange, TextSize};
+use yaml_rust::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust::scanner::{Marker, TScalarStyle, TokenType};
+
+/// Reads the one document in a YAML file.
+pub fn yaml(text: &str) -> Result<Imported, ImportError> {
+    let offsets = text.char_indices().map(|(offset, _)| offset).collect::<Vec<usize>>();
+    let mut events = Events {
+        events: Vec::new(),
+        offsets: &offsets,
+        len: text.len(),
+    };
+
+    Parser::new(text.chars()).load(&mut events, true).map_err(|error| {
+        // The scanner says where in its message, which the range covers.
+        let message = error.to_string();
+        let message = message.rsplit_once(" at line ").map_or(message.as_str(), |(message, _)| message);
+        let start = events.offset(error.marker());
+        ImportError::new(message, TextRange::empty(start))
+    })?;
+
+    let mut builder = Builder {
+        text,
+        events: events.events.into_iter().peekable(),
+        anchors: HashMap::new(),
+        locations: Locations::default(),
+    };
+    builder.document()
+}
+
+/// Every event, with the byte offset it starts at.
+struct Events<'a> {
+    events: Vec<(Event, TextSize)>,
+    /// The byte offset of each character, since markers count characters.
+    offsets: &'a [usize],
+    len: usize,
+}
+
+impl Events<'_> {
+    fn offset(&self, marker: &Marker) -> TextSize {
+        let offset = self.offsets.get(marker.index()).copied().unwrap_or(self.len);
+        TextSize::from(offset as u32)
+    }
+}
+
+impl MarkedEventReceiver for Events<'_> {
+    fn on_event(&mut self, event: Event, marker: Marker) {
+        let offset = self.offset(&marker);
+        self.events.push((event, offset));
+    }
+}
+
+struct Builder<'a, I: Iterator<Item = (Event, TextSize)>> {
+    text: &'a str,
+    events: std::iter::Peekable<I>,
+    /// Values with an `&anchor`, for the `*aliases` to them.
+    anchors: HashMap<usize, Value>,
+    locations: Locations,
+}
+
+impl<I: Iterator<Item = (Event, TextSize)>> Builder<'_, I> {
+    fn document(&mut self) -> Result<Imported, ImportError> {
+        let mut value = None;
+        while let Some((event, start)) = self.events.next() {
+            match event {
+                Event::StreamStart | Event::DocumentStart | Event::DocumentEnd | Event::StreamEnd | Event::Nothing => {}
+                _ if value.is_some() => {
+                    return Err(ImportError::new("only one document per file can be imported", TextRange::empty(start)))
+                }
+                event => value = Some(self.value(event, start, &[], None)?.0),
+            }
+        }
+
+        // An empty file is an empty struct, like an empty Vada file.
+        let value = value.unwrap_or_else(|| Value::Struct(Vec::new()));
+        Ok(std::mem::take(&mut self.locations).finish(value))
+    }
+
+    /// Reads the value `event` starts, and where it is. A sequence or mapping
+    /// runs to the end of what's in it, and starts at its key if it's the
+    /// value of one, since a block one has nothing else of its own.
+    fn value(
+        &mut self,
+        event: Event,
+        start: TextSize,
+        path: &[Step],
+        key: Option<TextRange>,
+    ) -> Result<(Value, TextRange), ImportError> {
+        let start_block = key.map_or(start, TextRange::start);
+        let (value, range, anchor) = match event {
+            Event::Scalar(text, style, anchor, tag) => {
+                let range = self.scalar_range(&text, style, start);
+                (scalar(text, style, tag, range)?, range, anchor)
+            }
+            Event::SequenceStart(anchor) => {
+                let mut items = Vec::new();
+                let mut end = start + TextSize::from(1);
+                loop {
+                    match self.next()? {
+                        (Event::SequenceEnd, close) => {
+                            end = self.closing(close, ']').unwrap_or(end);
+                            break;
+                        }
+                        (event, start) => {
+                            let (item, range) = self.value(event, start, &index(path, items.len()), None)?;
+                            end = end.max(range.end());
+                            items.push(item);
+                        }
+                    }
+                }
+                (Value::List(items), TextRange::new(start_block, end), anchor)
+            }
+            Event::MappingStart(anchor) => {
+                let mut fields: Vec<(SmolStr, Value)> = Vec::new();
+                let mut end = start + TextSize::from(1);
+                loop {
+                    let (key, key_range) = match self.next()? {
+                        (Event::MappingEnd, close) => {
+                            end = self.closing(close, '}').unwrap_or(end);
+                            break;
+                        }
+                        (Event::Scalar(key, style, ..), key_start) => {
+                            let range = self.scalar_range(&key, style, key_start);
+                            (key, range)
+                        }
+                        (_, key_start) => {
+                            return Err(ImportError::new("keys have to be plain strings", TextRange::empty(key_start)))
+                        }
+                    };
+                    if fields.iter().any(|(name, _)| *name == key) {
+                        return Err(ImportError::new(format!("`{}` is already a key here", key), key_range));
+                    }
+
+                    let (event, start) = self.next()?;
+                    let (value, range) = self.value(event, start, &field(path, &key), Some(key_range))?;
+                    end = end.max(range.end());
+                    fields.push((key.into(), value));
+                }
+                (Value::Struct(fields), TextRange::new(start_block, end), anchor)
+            }
+            Event::Alias(id) => match self.anchors.get(&id) {
+                Some(value) => (value.clone(), TextRange::at(start, 1.into()), 0),
+                None => return Err(ImportError::new("unknown alias", TextRange::empty(start))),
+            },
+            _ => return Err(ImportError::new("expected a value", TextRange::empty(start))),
+        };
+
+        if anchor > 0 {
+            self.anchors.insert(anchor, value.clone());
+        }
+        self.locations.add(path, range);
+        Ok((value, range))
+    }
+
+    fn next(&mut self) -> Result<(Event, TextSize), ImportError> {
+        let end = TextSize::from(self.text.len() as u32);
+        self.events
+            .next()
+            .ok_or_else(|| ImportError::new("the file ended too soon", TextRange::empty(end)))
+    }
+
+    /// Just past the `]` or `}` that closes a flow sequence or mapping at
+    /// `offset`. Block ones have nothing closing them, and end with the last
+    /// thing in them.
+    fn closing(&self, offset: TextSize, bracket: char) -> Option<TextSize> {
+        self.text[usize::from(offset)..]
+            .starts_with(bracket)
+            .then(|| offset + TextSize::from(1))
+    }
+
+    /// How much of the file a scalar takes up. Plain scalars are as long as
+    /// their text, quoted ones run to their closing quote, and block scalars
+    /// are just their `|` or `>`.
+    fn scalar_range(&self, text: &str, style: TScalarStyle, start: TextSize) -> TextRange {
+        let rest = &self.text[usize::from(start)..];
+        let len = match style {
+            TScalarStyle::Plain => text.len(),
+            TScalarStyle::SingleQuoted => closing(rest, '\''),
+            TScalarStyle::DoubleQuoted => closing(rest, '"'),
+            _ => 1,
+        };
+        TextRange::at(start, TextSize::from(len.min(rest.len()) as u32))
+    }
+}
+
+/// The length of a quoted scalar up to and including its closing quote.
+fn closing(rest: &str, quote: char) -> usize {
+    let mut chars = rest.char_indices().skip(1);
+    while let Some((idx, c)) = chars.next() {
+        // `\"` in double quotes and `''` in single ones don't close them.
+        let escaped = (c == '\\' && quote == '"') || (c == '\'' && quote == '\'' && rest[idx + 1..].starts_with('\''));
+        if escaped {
+            chars.next();
+        } else if c == quote {
+            return idx + 1;
+        }
+    }
+    rest.len()
+}
+
+/// Reads a scalar the way YAML's core schema does, bar the booleans and
+/// nulls Vada can't hold.
+fn scalar(text: String, style: TScalarStyle, tag: Option<TokenType>, range: TextRange) -> Result<Value, ImportError> {
+    let tagged_string = matches!(&tag, Some(TokenType::Tag(handle, suffix)) if handle == "!!" && suffix == "str");
+    if style != TScalarStyle::Plain || tagged_string {
+        return Ok(Value::String(text.into()));
+    }
+
+    match text.as_str() {
+        "true" | "True" | "TRUE" | "false" | "False" | "FALSE" => Err(ImportError::new("Vada has no booleans", range)),
+        "" | "~" | "null" | "Null" | "NULL" => Err(ImportError::new("Vada has no null", range)),
+        ".inf" | ".Inf" | ".INF" | "+.inf" => Ok(Value::Float(f64::INFINITY)),
+        "-.inf" | "-.Inf" | "-.INF" => Ok(Value::Float(f64::NEG_INFINITY)),
+        ".nan" | ".NaN" | ".NAN" => Ok(Value::Float(f64::NAN)),
+        _ => Ok(yaml_number(&text).unwrap_or(Value::String(text.into()))),
+    }
+}
+
+/// Numbers as YAML writes them, which Rust reads more of than it should.
+fn yaml_number(text: &str) -> Option<Value> {
+    let digits = text.strip_prefix(['-', '+']).unwrap_or(text);
+    if let Some(hex) = digits.strip_prefix("0x") {
+        let n = i64::from_str_radix(hex, 16).ok()?;
+        return Some(Value::Int(if text.starts_with('-') { -n } else { n }));
+    }
+
+    let numeric = digits.starts_with(|c: char| c.is_ascii_digit() || c == '.')
+        && digits.chars().any(|c| c.is_ascii_digit())
+        && digits.chars().all(|c| c.is_ascii_digit() || matches!(c, '.' | 'e' | 'E' | '-' | '+'));
+    if !numeric {
+        return None;
+    }
+    number(text.strip_prefix('+').unwrap_or(text))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(fields: Vec<(&str, Value)>) -> Value {
+        Value::Struct(
+            fields
+                .into_iter()
+                .map(|(name, value)| (name.into(), value))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn reads_values_in_order() {
+        let text = "zed: 1\nalpha:\n  - 2.5\n  - 'it''s'\n  - \"0x10\"\n  - 0x10\nname: green goblin\nstats: {hp: 3}\n";
+        assert_eq!(
+            yaml(text).unwrap().value,
+            fields(vec![
+                ("zed", Value::Int(1)),
+                (
+                    "alpha",
+                    Value::List(vec![
+                        Value::Float(2.5),
+                        Value::String("it's".into()),
+                        Value::String("0x10".into()),
+                        Value::Int(16),
+                    ])
+                ),
+                ("name", Value::String("green goblin".into())),
+                ("stats", fields(vec![("hp", Value::Int(3))])),
+            ])
+        );
+    }
+
+    #[test]
+    fn keeps_ranges() {
+        let text = "units:\n  goblin:\n    hp: -5\n    name: \"gob\"\n";
+        let imported = yaml(text).unwrap();
+
+        let at = |path: &[Step]| &text[imported.locate(path).unwrap()];
+        let key = |name: &str| Step::Field(name.into());
+        assert_eq!(at(&[key("units"), key("goblin"), key("hp")]), "-5");
+        assert_eq!(at(&[key("units"), key("goblin"), key("name")]), "\"gob\"");
+        assert_eq!(at(&[key("units"), key("goblin")]), "goblin:\n    hp: -5\n    name: \"gob\"");
+        assert_eq!(at(&[key("units"), key("goblin"), key("speed")]), at(&[key("units"), key("goblin")]));
+    }
+
+    #[test]
+    fn collections_cover_what_is_in_them() {
+        let text = "- [1, 2]\n- {hp: 3}  \n- - a\n  - b\n";
+        let imported = yaml(text).unwrap();
+
+        let at = |path: &[Step]| &text[imported.locate(path).unwrap()];
+        assert_eq!(at(&[Step::Index(0)]), "[1, 2]");
+        assert_eq!(at(&[Step::Index(1)]), "{hp: 3}");
+        assert_eq!(at(&[Step::Index(2)]), "- a\n  - b");
+        assert_eq!(at(&[]), text.trim_end());
+    }
+
+    #[test]
+    fn follows_aliases() {
+        let text = "base: &base {hp: 3}\ngoblin: *base\n";
+        assert_eq!(
+            yaml(text).unwrap().value,
+            fields(vec![
+                ("base", fields(vec![("hp", Value::Int(3))])),
+                ("goblin", fields(vec![("hp", Value::Int(3))])),
+            ])
+        );
+    }
+
+    #[test]
+    fn refuses_what_vada_cant_hold() {
+        let error = yaml("alive: yes\ndead: false\n").unwrap_err();
+        assert_eq!(error.message, "Vada has no booleans");
+        assert_eq!(error.range, TextRange::new(17.into(), 22.into()));
+        assert_eq!(yaml("a:\n").unwrap_err().message, "Vada has no null");
+        assert_eq!(yaml("a: 1\n---\nb: 2\n").unwrap_err().message, "only one document per file can be imported");
+        assert!(yaml("a: [1\n").is_err());
+    }
+}
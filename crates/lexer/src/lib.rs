@@ -1,4 +1,6 @@
+mod string;
 mod token_kind;
+pub use string::{string_value, EscapeError};
 pub use token_kind::TokenKind;
 
 
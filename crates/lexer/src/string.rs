@@ -0,0 +1,222 @@
+/*
+TL;DR: What a string token stands for.
+
+    "tab\there"             → tab	here
+    "\u{e9}t\u{e9}"         → été
+    r"C:\no\escapes"        → C:\no\escapes
+    r#"say "hi""#           → say "hi"
+    """
+    one "line"              → one "line"⏎two
+    two"""
+
+Plain and triple-quoted strings take escapes: `\n`, `\r`, `\t`, `\0`, `\\`,
+`\"`, `\'` and `\u{...}` with one to six hex digits. A triple-quoted string
+can hold line breaks and quotes, and the line break straight after its
+opening `"""` isn't part of it. Raw strings are taken as written, and can
+have as many `#`s either side as it takes for their quotes not to end them.
+
+Every escape that isn't one is reported, with where it is in the file.
+*/
+
+use logos::Lexer;
+use std::fmt;
+use text_size::{TextRange, TextSize};
+
+use crate::TokenKind;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EscapeError {
+    /// A backslash before something that means nothing after one.
+    Unknown { escape: String, range: TextRange },
+    /// A `\u` that isn't followed by one to six hex digits in braces.
+    MalformedUnicode { range: TextRange },
+    /// A `\u{...}` naming a code point that isn't a character.
+    NotAChar { code: u32, range: TextRange },
+}
+
+impl EscapeError {
+    pub fn range(&self) -> TextRange {
+        match self {
+            Self::Unknown { range, .. } | Self::MalformedUnicode { range } | Self::NotAChar { range, .. } => *range,
+        }
+    }
+}
+
+impl fmt::Display for EscapeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unknown { escape, .. } => write!(f, "unknown escape `{}`", escape),
+            Self::MalformedUnicode { .. } => write!(f, "expected one to six hex digits in braces after `\\u`"),
+            Self::NotAChar { code, .. } => write!(f, "`\\u{{{:x}}}` isn't a character", code),
+        }
+    }
+}
+
+/// The value of a string token, however it was written, or every escape in
+/// it that isn't one. `start` is where the token is in the file.
+pub fn string_value(text: &str, start: TextSize) -> Result<String, Vec<EscapeError>> {
+    if let Some(raw) = text.strip_prefix('r') {
+        let hashes = raw.len() - raw.trim_start_matches('#').len();
+        return Ok(raw[hashes + 1..raw.len() - hashes - 1].to_string());
+    }
+
+    if text.len() >= 6 && text.starts_with(r#"""""#) {
+        let body = &text[3..text.len() - 3];
+        return match body.strip_prefix('\n') {
+            Some(body) => unescape(body, start + TextSize::from(4)),
+            None => unescape(body, start + TextSize::from(3)),
+        };
+    }
+
+    unescape(&text[1..text.len() - 1], start + TextSize::from(1))
+}
+
+fn unescape(body: &str, start: TextSize) -> Result<String, Vec<EscapeError>> {
+    let mut value = String::with_capacity(body.len());
+    let mut errors = Vec::new();
+    let mut idx = 0;
+
+    while let Some(c) = body[idx..].chars().next() {
+        if c != '\\' {
+            value.push(c);
+            idx += c.len_utf8();
+            continue;
+        }
+
+        let (len, escaped) = match body[idx + 1..].chars().next() {
+            Some('n') => (2, Ok('\n')),
+            Some('r') => (2, Ok('\r')),
+            Some('t') => (2, Ok('\t')),
+            Some('0') => (2, Ok('\0')),
+            Some(c @ ('\\' | '"' | '\'')) => (2, Ok(c)),
+            Some('u') => match unicode(&body[idx..]) {
+                (len, Some(code)) => (len, char::from_u32(code).ok_or(Some(code))),
+                (len, None) => (len, Err(None)),
+            },
+            Some(c) => (1 + c.len_utf8(), Err(None)),
+            None => (1, Err(None)),
+        };
+
+        let range = TextRange::at(start + TextSize::from(idx as u32), TextSize::from(len as u32));
+        match escaped {
+            Ok(c) => value.push(c),
+            Err(Some(code)) => errors.push(EscapeError::NotAChar { code, range }),
+            Err(None) if body[idx..].starts_with("\\u") => errors.push(EscapeError::MalformedUnicode { range }),
+            Err(None) => errors.push(EscapeError::Unknown {
+                escape: body[idx..idx + len].to_string(),
+                range,
+            }),
+        }
+        idx += len;
+    }
+
+    if errors.is_empty() {
+        Ok(value)
+    } else {
+        Err(errors)
+    }
+}
+
+/// How far the `\u{...}` at the start of `text` goes, and the code point it
+/// names if it's well formed.
+fn unicode(text: &str) -> (usize, Option<u32>) {
+    let digits = match text[2..].strip_prefix('{') {
+        Some(digits) => digits,
+        None => return (2, None),
+    };
+
+    let len = digits.find(|c: char| !c.is_ascii_hexdigit()).unwrap_or(digits.len());
+    if !digits[len..].starts_with('}') {
+        return (3 + len, None);
+    }
+    let code = (1..=6).contains(&len).then(|| u32::from_str_radix(&digits[..len], 16).unwrap());
+    (4 + len, code)
+}
+
+/// Lexes the rest of a triple-quoted string once its `"""` has been seen.
+pub(crate) fn triple_quoted(lex: &mut Lexer<TokenKind>) -> bool {
+    let rest = lex.remainder();
+    let mut idx = 0;
+
+    while let Some(c) = rest[idx..].chars().next() {
+        if rest[idx..].starts_with(r#"""""#) {
+            lex.bump(idx + 3);
+            return true;
+        }
+        idx += c.len_utf8();
+        // Whatever's escaped is skipped, so `\"` doesn't end the string.
+        if c == '\\' {
+            idx += rest[idx..].chars().next().map_or(0, char::len_utf8);
+        }
+    }
+    false
+}
+
+/// Lexes the rest of a raw string once its `r`, `#`s and `"` have been seen.
+pub(crate) fn raw(lex: &mut Lexer<TokenKind>) -> bool {
+    let hashes = lex.slice().len() - 2;
+    let end = format!("\"{}", "#".repeat(hashes));
+
+    match lex.remainder().find(&end) {
+        Some(idx) => {
+            lex.bump(idx + end.len());
+            true
+        }
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn value(text: &str) -> Result<String, Vec<EscapeError>> {
+        string_value(text, TextSize::from(10))
+    }
+
+    #[test]
+    fn decode_escapes() {
+        assert_eq!(value(r#""a\tb\n\"c\" \\ \'""#), Ok(String::from("a\tb\n\"c\" \\ '")));
+        assert_eq!(value(r#""\u{e9}t\u{E9} \u{1F600}""#), Ok(String::from("été 😀")));
+        assert_eq!(value(r#""déjà vu, n'est-ce pas?""#), Ok(String::from("déjà vu, n'est-ce pas?")));
+    }
+
+    #[test]
+    fn decode_raw_and_triple_quoted() {
+        assert_eq!(value(r#"r"C:\no\escapes""#), Ok(String::from(r"C:\no\escapes")));
+        assert_eq!(value(r###"r##"say "#hi"#"##"###), Ok(String::from(r##"say "#hi"#"##)));
+        assert_eq!(
+            value("\"\"\"\nfirst \"line\"\n\\tsecond\n\"\"\""),
+            Ok(String::from("first \"line\"\n\tsecond\n"))
+        );
+        assert_eq!(value(r#""""""""#), Ok(String::new()));
+    }
+
+    #[test]
+    fn report_bad_escapes() {
+        let range = |start: u32, end: u32| TextRange::new(start.into(), end.into());
+
+        assert_eq!(
+            value(r#""a\qb\u{d800}\u{}\u00e9""#),
+            Err(vec![
+                EscapeError::Unknown {
+                    escape: String::from(r"\q"),
+                    range: range(12, 14),
+                },
+                EscapeError::NotAChar {
+                    code: 0xd800,
+                    range: range(15, 23),
+                },
+                EscapeError::MalformedUnicode { range: range(23, 27) },
+                EscapeError::MalformedUnicode { range: range(27, 29) },
+            ])
+        );
+        assert_eq!(
+            value("\"\"\"\n\\é\"\"\""),
+            Err(vec![EscapeError::Unknown {
+                escape: String::from(r"\é"),
+                range: range(14, 17),
+            }])
+        );
+    }
+}
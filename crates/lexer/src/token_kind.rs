@@ -31,17 +31,22 @@ pub enum TokenKind {
     #[regex(r"[0-9][0-9_]*")]
     IntLiteral,
 
-    #[regex(r"[0-9]+[0-9_]*d[0-9]+[0-9_]")]
+    // Dice, like `2d6`.
+    #[regex(r"[0-9][0-9_]*d[0-9][0-9_]*")]
     RandIntLiteral,
 
     #[regex(r"[0-9][0-9_]*\.[0-9][0-9_]*")]
     FloatLiteral,
 
-    #[regex(r"[0-9]+\\.[0-9_]*d[0-9]+\\.[0-9_]*")]
+    // A float between two others, like `0.8d1.2`.
+    #[regex(r"[0-9][0-9_]*\.[0-9][0-9_]*d[0-9][0-9_]*\.[0-9][0-9_]*")]
     RandFloatLiteral,
 
     // Strings-as-tokens is a bad idea, as it doesn't allow interior lexing.
-    #[regex(r#""[A-Za-z0-9 _]*""#)]
+    // Plain strings stay on one line; `crate::string` says what they mean.
+    #[regex(r#""([^"\\\n]|\\[^\n])*""#)]
+    #[token(r#"""""#, crate::string::triple_quoted)]
+    #[regex(r##"r#*""##, crate::string::raw)]
     String,
 
     // END LITERALS
@@ -687,6 +692,30 @@ mod tests {
         ])
     }
 
+    #[test]
+    fn lex_strings() {
+        check(r#""déjà vu, n'est-ce pas? \"oui\"""#, TokenKind::String);
+        check(r#""""#, TokenKind::String);
+        check("\"\"\"\nfirst \"line\"\nsecond \\\"\"\"\n\"\"\"", TokenKind::String);
+        check(r#"r"C:\no\escapes""#, TokenKind::String);
+        check(r###"r##"say "#hi"#"##"###, TokenKind::String);
+    }
+
+    #[test]
+    fn lex_strings_end_where_they_should() {
+        check_seq(r#""a" "b""#, vec![TokenKind::String, TokenKind::Whitespace, TokenKind::String]);
+        check_seq("\"a\nb", vec![TokenKind::Error, TokenKind::Whitespace, TokenKind::Ident]);
+        check_seq(r#"r"a" r"#, vec![TokenKind::String, TokenKind::Whitespace, TokenKind::Ident]);
+    }
+
+    #[test]
+    fn lex_random_literals() {
+        check("2d6", TokenKind::RandIntLiteral);
+        check("1_0d2_0", TokenKind::RandIntLiteral);
+        check("0.8d1.2", TokenKind::RandFloatLiteral);
+        check_seq("2d6+1", vec![TokenKind::RandIntLiteral, TokenKind::Plus, TokenKind::IntLiteral]);
+    }
+
     #[test]
     fn lex_comment() {
         check(r#"// foo"#, TokenKind::Comment);
@@ -25,6 +25,13 @@ pub(crate) fn root(p: &mut Parser) -> CompletedMarker {
     m.complete(p, SyntaxKind::Root)
 }
 
+/// A struct in a root of its own, for reparsing one in place.
+pub(crate) fn lone_struct(p: &mut Parser) -> CompletedMarker {
+    let m = p.start();
+    strct::strct(p);
+    m.complete(p, SyntaxKind::Root)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::check;
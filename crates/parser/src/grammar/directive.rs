@@ -42,7 +42,6 @@ fn keyword_directive(p: &mut Parser) -> Option<CompletedMarker> {
         p.expect(TokenKind::RBrack);
         return Some(m.complete(p, SyntaxKind::TypePrimitive))
     } else {
-        println!("Exiting primitive dec");
         // p.expect(TokenKind::RBrack);
         return Some(m.complete(p, SyntaxKind::TypePrimitive))
     }
@@ -68,7 +67,6 @@ fn match_directive(p: &mut Parser) -> Option<CompletedMarker> {
         expr::expr(p);
     }
 
-    println!("herhehre");
     p.expect(TokenKind::RBrack);
 
     return Some(m.complete(p, SyntaxKind::Directive));
@@ -28,7 +28,12 @@ fn exp(p: &mut Parser) -> CompletedMarker {
         p.bump();
         p.expect(TokenKind::Ident);
         inner.complete(p, SyntaxKind::Name);
-        if p.at(TokenKind::LBrace) {
+        if p.at(TokenKind::LBrack) { // Built from other types
+            p.bump();
+            expr::expr(p);
+            p.expect(TokenKind::RBrack);
+        }
+        else if p.at(TokenKind::LBrace) {
             strct::strct(p);
         }
         outer.complete(p, SyntaxKind::Type)
@@ -61,7 +66,6 @@ fn exp(p: &mut Parser) -> CompletedMarker {
         if p.at(TokenKind::LBrace) {
             strct::strct(p);
         }
-        println!("octo'd");
         outer.complete(p, SyntaxKind::Transform)
     }
 
@@ -80,6 +84,9 @@ fn exp(p: &mut Parser) -> CompletedMarker {
     else if p.at(TokenKind::Ident) {
         let m = p.start(); // Generic
         p.bump();
+        if p.at_digraph([TokenKind::QMark, TokenKind::Colon]) {
+            p.bump(); // for nullable records.
+        }
         m.complete(p, SyntaxKind::Name)
     }
 
@@ -101,7 +108,6 @@ fn exp(p: &mut Parser) -> CompletedMarker {
         return outer.complete(p, SyntaxKind::Path);
     }
     else {
-        println!("exit expansion");
         return cm;
     }
 
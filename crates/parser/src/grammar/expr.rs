@@ -38,6 +38,11 @@ pub(super) fn expr(p: &mut Parser) -> Option<CompletedMarker> {
     expr_binding_power(p, 0, true)
 }
 
+/// An expression that stops at a comma, like an argument in `f(a, b)`.
+pub(super) fn arg(p: &mut Parser) -> Option<CompletedMarker> {
+    expr_binding_power(p, 0, false)
+}
+
 fn expr_binding_power(p: &mut Parser, minimum_binding_power: u8, do_list: bool) -> Option<CompletedMarker> {
     let mut lhs = lhs(p, do_list)?;
     loop {
@@ -53,8 +58,19 @@ fn expr_binding_power(p: &mut Parser, minimum_binding_power: u8, do_list: bool)
         }
 
         p.bump();
+        // `..=`, which only takes its `=` once the range is known to bind.
+        if op == BinaryOp::Range && p.at(TokenKind::Equals) {
+            p.bump();
+        }
 
         let m = lhs.precede(p);
+
+        // `3..` is a range without an upper bound.
+        if op == BinaryOp::Range && at_operand_end(p) {
+            lhs = m.complete(p, SyntaxKind::InfixExpr);
+            continue;
+        }
+
         let parsed_rhs = expr_binding_power(p, right_binding_power, do_list).is_some();
         lhs = m.complete(p, SyntaxKind::InfixExpr);
 
@@ -83,6 +99,18 @@ fn at_postfix(p: &mut Parser) -> bool {
     return false;
 }
 
+/// Whether an open-ended operator like `...` or `3..` has nothing left to
+/// take as its operand.
+fn at_operand_end(p: &mut Parser) -> bool {
+    p.at_line_break()
+        || p.at(TokenKind::RBrace)
+        || p.at(TokenKind::RBrack)
+        || p.at(TokenKind::RParen)
+        || p.at(TokenKind::Comma)
+        || p.at(TokenKind::Ampersand)
+        || p.at(TokenKind::Bar)
+}
+
 fn at_literal(p: &mut Parser) -> bool {
     for t in LITERAL_TOKENS.into_iter() {
         if p.at(t) {
@@ -93,8 +121,12 @@ fn at_literal(p: &mut Parser) -> bool {
 }
 
 fn lhs(p: &mut Parser, do_list: bool) -> Option<CompletedMarker> {
+    // A record on the next line means this operand is missing, and the
+    // record belongs to whatever is around the expression.
+    let at_record = p.at_record_start();
+
     let cm = 
-    if p.at_expandable() {
+    if !at_record && p.at_expandable() {
       let t = expand::expand(p, false).unwrap();
       match t.extract(p) {
         SyntaxKind::Name      => { t.precede(p).complete(p, SyntaxKind::Ref) },
@@ -125,13 +157,7 @@ fn lhs(p: &mut Parser, do_list: bool) -> Option<CompletedMarker> {
         scope_stmt::expand(p).unwrap() // Change to bind syntax?
     }
     else {
-        if at_postfix(p) {
-        }
-        else if p.at(TokenKind::LBrace) {
-            println!("Brace found, exiting expression");
-        } 
-        else {
-            println!("LHS Err");
+        if !at_postfix(p) && !p.at(TokenKind::LBrace) {
             p.error();
         }
         return None;
@@ -165,6 +191,7 @@ enum BinaryOp {
     Unify,   // &
     Dsj,     // |
     Range,   // ..
+    Shift,   // .> and .<
     Err,
 
     // Pipes
@@ -187,14 +214,16 @@ impl BinaryOp {
             Self::Add | Self::Sub             => (4, 5),
             Self::Mul | Self::Div 
                       | Self::Mod             => (6, 7),
+            // Shifts scale a number like multiplying does, as in `1 .> 2`.
+            Self::Shift                       => (6, 7),
             
             Self::DirArrow                    => (8, 9),
 
+            // Looser than resolvers, so `@self.A..@self.D` spans two paths.
+            Self::Range                       => (9, 10),
+
             // Resolvers are always the tightest-binding operators
             Self::ScopeRes | Self::MemberRes  => (10, 11),
-            
-            
-            Self::Range => (120, 120),
             _ => (0, 0),
         }
     }
@@ -227,8 +256,9 @@ impl UnaryOp {
         match self {
             Self::Func              => (0, 0), // Unary zero-bind for single-arity transforms
 
+            // Looser than arithmetic, so `<5+2` bounds by 7.
             Self::Gr | Self::GrEq 
-            | Self::Ls | Self::LsEq => (0, 5),
+            | Self::Ls | Self::LsEq => (0, 4),
 
             Self::Pos | Self::Neg 
             | Self::Mul | Self::Div 
@@ -257,11 +287,9 @@ fn infix_expr(p: &mut Parser) -> BinaryOp {
         BinaryOp::Div
     } else if p.at(TokenKind::Percent) {
         BinaryOp::Mod
+    } else if p.at(TokenKind::BitShiftRight) || p.at(TokenKind::BitShiftLeft) {
+        BinaryOp::Shift
     } else if p.at(TokenKind::DotDot) {
-        // This is... Horrible.
-        if p.at_next(TokenKind::Equals) {
-            p.bump();
-        }
         BinaryOp::Range
     } else if p.at(TokenKind::Ampersand) {
         BinaryOp::Unify
@@ -283,20 +311,22 @@ fn infix_expr(p: &mut Parser) -> BinaryOp {
 }
 
 fn transform_expr(p: &mut Parser) {
-  println!("Look ma!");
-
-
   // Low binding open expression
   if p.at(TokenKind::Colon) {
     p.bump();
     expr_binding_power(p, 1, false);
   }
-  // High binding closed expression
-  else {
+  // Arguments, as in `#clamp(0, 10)`
+  else if p.at(TokenKind::LParen) {
+    func::func_args(p);
+  }
+  // High binding closed expression, as long as it's on the same line and
+  // isn't the next operator, as in `#round -> #abs`.
+  else if !p.at_line_start()
+    && (at_literal(p) || p.at(TokenKind::Ident) || p.at(TokenKind::LBrack) || p.at(TokenKind::LBrace))
+  {
     expr_binding_power(p, 10, false);
   }
-
-
 }
 
 /// Handles both math prefixes and record prefixes
@@ -346,6 +376,11 @@ fn prefix_expr(p: &mut Parser) -> CompletedMarker {
       p.bump();
     }
 
+    // A bare `...` stands for any number at all.
+    if op == UnaryOp::Open && at_operand_end(p) {
+        return m.complete(p, SyntaxKind::PrefixExpr);
+    }
+
     expr_binding_power(p, right_binding_power, false);
 
     m.complete(p, SyntaxKind::PrefixExpr)
@@ -356,14 +391,15 @@ fn paren_expr(p: &mut Parser) -> CompletedMarker {
 
     let m = p.start();
     p.bump();
+    p.enter(TokenKind::RParen);
     expr_binding_power(p, 0, false);
+    p.exit();
     p.expect(TokenKind::RParen);
 
     m.complete(p, SyntaxKind::ParenExpr)
 }
 
 fn build_list(p: &mut Parser, start: Option<CompletedMarker>) -> CompletedMarker {
-  println!("top of build list");
   let mut outer: Option<Marker> = None;
   let mut close: bool = false;
   let mut row: Marker = if start.is_some() {
@@ -373,13 +409,16 @@ fn build_list(p: &mut Parser, start: Option<CompletedMarker>) -> CompletedMarker
     p.start()
   };
 
+  if close {
+    p.enter(TokenKind::RBrack);
+  }
+
   loop {
       if p.at(TokenKind::Comma) {
           p.bump();
       }
 
       if p.at(TokenKind::Semicolon) {
-          println!("starting list column");
           p.bump();
           if outer.is_none() {
             outer = Some(row.complete(p, SyntaxKind::Row).precede(p));
@@ -392,8 +431,7 @@ fn build_list(p: &mut Parser, start: Option<CompletedMarker>) -> CompletedMarker
           // outer = p.start();
       }
 
-      if p.at_end() {
-          println!("Break list at end of input");
+      if p.at_end() || p.at_record_start() || (close && p.at(TokenKind::RBrack)) {
           break;
       }
 
@@ -401,7 +439,6 @@ fn build_list(p: &mut Parser, start: Option<CompletedMarker>) -> CompletedMarker
       if exp.is_some() {
           exp.unwrap().precede(p).complete(p, SyntaxKind::Entry);
       } else {
-        println!("breaking list");
           break;
       }
 
@@ -411,6 +448,7 @@ fn build_list(p: &mut Parser, start: Option<CompletedMarker>) -> CompletedMarker
   }
 
   if close == true {
+    p.exit();
     p.expect(TokenKind::RBrack);
   }
 
@@ -860,7 +898,7 @@ Root@0..5
           Literal@3..4
             Number@3..4 "1"
           Plus@4..5 "+"
-error at 4..5: expected hex number, integer, float, octal number, binary number, memory address, random integer, random float, string, {, +, -, *, /, ^, %, :, <=, >=, <, >, .., ..., (, [, <; or {
+error at 4..5: expected an expression
 error at 4..5: expected )"#]],
         );
     }
@@ -1276,28 +1314,199 @@ Root@0..20
     fn parse_transform_path() {
         check(
             r#"var: 32->#reduce -> some::module::path::#transform"#,
+            expect![[r##"
+                Root@0..50
+                  Record@0..50
+                    Name@0..3
+                      Ident@0..3 "var"
+                    Colon@3..4 ":"
+                    Whitespace@4..5 " "
+                    Body@5..50
+                      InfixExpr@5..50
+                        InfixExpr@5..17
+                          Literal@5..7
+                            Number@5..7 "32"
+                          RArrow@7..9 "->"
+                          PrefixExpr@9..17
+                            Transform@9..17
+                              Name@9..17
+                                Octothorpe@9..10 "#"
+                                Ident@10..16 "reduce"
+                                Whitespace@16..17 " "
+                        RArrow@17..19 "->"
+                        Whitespace@19..20 " "
+                        InfixExpr@20..50
+                          InfixExpr@20..38
+                            InfixExpr@20..32
+                              Ref@20..24
+                                Name@20..24
+                                  Ident@20..24 "some"
+                              ColonColon@24..26 "::"
+                              Ref@26..32
+                                Name@26..32
+                                  Ident@26..32 "module"
+                            ColonColon@32..34 "::"
+                            Ref@34..38
+                              Name@34..38
+                                Ident@34..38 "path"
+                          ColonColon@38..40 "::"
+                          PrefixExpr@40..50
+                            Transform@40..50
+                              Name@40..50
+                                Octothorpe@40..41 "#"
+                                Ident@41..50 "transform""##]],
+        )
+    }
+
+    #[test]
+    fn parse_transform_args() {
+        check(
+            "a: \"elbow\" -> #replace(\"l\", \"L\")\nb: #clamp(-3, 0, 10)",
+            expect![[r##"
+                Root@0..53
+                  Record@0..33
+                    Name@0..1
+                      Ident@0..1 "a"
+                    Colon@1..2 ":"
+                    Whitespace@2..3 " "
+                    Body@3..33
+                      InfixExpr@3..33
+                        Literal@3..11
+                          String@3..10 "\"elbow\""
+                          Whitespace@10..11 " "
+                        RArrow@11..13 "->"
+                        Whitespace@13..14 " "
+                        PrefixExpr@14..33
+                          Transform@14..22
+                            Name@14..22
+                              Octothorpe@14..15 "#"
+                              Ident@15..22 "replace"
+                          FuncArgs@22..33
+                            LParen@22..23 "("
+                            Literal@23..26
+                              String@23..26 "\"l\""
+                            Comma@26..27 ","
+                            Whitespace@27..28 " "
+                            Literal@28..31
+                              String@28..31 "\"L\""
+                            RParen@31..32 ")"
+                            Whitespace@32..33 "\n"
+                  Record@33..53
+                    Name@33..34
+                      Ident@33..34 "b"
+                    Colon@34..35 ":"
+                    Whitespace@35..36 " "
+                    Body@36..53
+                      PrefixExpr@36..53
+                        Transform@36..42
+                          Name@36..42
+                            Octothorpe@36..37 "#"
+                            Ident@37..42 "clamp"
+                        FuncArgs@42..53
+                          LParen@42..43 "("
+                          PrefixExpr@43..45
+                            Minus@43..44 "-"
+                            Literal@44..45
+                              Number@44..45 "3"
+                          Comma@45..46 ","
+                          Whitespace@46..47 " "
+                          Literal@47..48
+                            Number@47..48 "0"
+                          Comma@48..49 ","
+                          Whitespace@49..50 " "
+                          Literal@50..52
+                            Number@50..52 "10"
+                          RParen@52..53 ")""##]],
+        );
+    }
+
+    #[test]
+    fn parse_transform_before_next_record() {
+        check(
+            "a: x -> #TitleCase\nb: 1",
+            expect![[r##"
+                Root@0..23
+                  Record@0..19
+                    Name@0..1
+                      Ident@0..1 "a"
+                    Colon@1..2 ":"
+                    Whitespace@2..3 " "
+                    Body@3..19
+                      InfixExpr@3..19
+                        Ref@3..5
+                          Name@3..5
+                            Ident@3..4 "x"
+                            Whitespace@4..5 " "
+                        RArrow@5..7 "->"
+                        Whitespace@7..8 " "
+                        PrefixExpr@8..19
+                          Transform@8..19
+                            Name@8..19
+                              Octothorpe@8..9 "#"
+                              Ident@9..18 "TitleCase"
+                              Whitespace@18..19 "\n"
+                  Record@19..23
+                    Name@19..20
+                      Ident@19..20 "b"
+                    Colon@20..21 ":"
+                    Whitespace@21..22 " "
+                    Body@22..23
+                      Literal@22..23
+                        Number@22..23 "1""##]],
+        );
+    }
+
+    #[test]
+    fn parse_open_prefix_before_next_record() {
+        check(
+            "a: ...\nb: 1",
             expect![[r#"
-Root@0..20
-  Record@0..20
-    Name@0..3
-      Ident@0..3 "var"
-    Colon@3..4 ":"
-    Whitespace@4..5 " "
-    Body@5..20
-      InfixExpr@5..20
-        InfixExpr@5..15
-          Literal@5..9
-            String@5..8 "\"A\""
-            Whitespace@8..9 " "
-          Plus@9..10 "+"
-          Whitespace@10..11 " "
-          Literal@11..15
-            String@11..14 "\"B\""
-            Whitespace@14..15 " "
-        Plus@15..16 "+"
-        Whitespace@16..17 " "
-        Literal@17..20
-          String@17..20 "\"C\"""#]],
+                Root@0..11
+                  Record@0..7
+                    Name@0..1
+                      Ident@0..1 "a"
+                    Colon@1..2 ":"
+                    Whitespace@2..3 " "
+                    Body@3..7
+                      PrefixExpr@3..7
+                        DotDotDot@3..6 "..."
+                        Whitespace@6..7 "\n"
+                  Record@7..11
+                    Name@7..8
+                      Ident@7..8 "b"
+                    Colon@8..9 ":"
+                    Whitespace@9..10 " "
+                    Body@10..11
+                      Literal@10..11
+                        Number@10..11 "1""#]],
+        )
+    }
+
+    #[test]
+    fn parse_range_without_upper_bound() {
+        check(
+            "a: 3..\nb: 1",
+            expect![[r#"
+                Root@0..11
+                  Record@0..7
+                    Name@0..1
+                      Ident@0..1 "a"
+                    Colon@1..2 ":"
+                    Whitespace@2..3 " "
+                    Body@3..7
+                      InfixExpr@3..7
+                        Literal@3..4
+                          Number@3..4 "3"
+                        DotDot@4..6 ".."
+                        Whitespace@6..7 "\n"
+                  Record@7..11
+                    Name@7..8
+                      Ident@7..8 "b"
+                    Colon@8..9 ":"
+                    Whitespace@9..10 " "
+                    Body@10..11
+                      Literal@10..11
+                        Number@10..11 "1""#]],
         )
     }
 }
@@ -3,17 +3,21 @@ use super::*;
 pub(super) fn func_args(p: &mut Parser) -> Option<CompletedMarker> {
     let m = p.start();
     p.expect(TokenKind::LParen);
+    p.enter(TokenKind::RParen);
     loop {
         p.clear_expected();
-        if p.if_bump(TokenKind::Comma) {
-            println!("func args: at comma");
-            expr::expr(p);
-        } else if p.at(TokenKind::Ident) {
-            println!("func args: at ident");
-            expr::expr(p);
-        } else if p.at_literal(){
-            println!("func_args_at_literal");
-            expr::expr(p);
+        if p.at_record_start() {
+            // An unclosed call ends where the next record begins.
+            p.expect(TokenKind::RParen);
+            break;
+        } else if p.if_bump(TokenKind::Comma)
+            || p.at(TokenKind::Ident)
+            || p.at_literal()
+            || p.at(TokenKind::LBrack)
+            || p.at(TokenKind::LParen)
+            || p.at(TokenKind::Minus)
+        {
+            expr::arg(p);
         } else if p.at(TokenKind::DollarSign) {
             // Technically valid
 
@@ -30,11 +34,11 @@ pub(super) fn func_args(p: &mut Parser) -> Option<CompletedMarker> {
         } else if p.if_bump(TokenKind::RParen) {
             break;
         } else {
-            println!("four");
             p.error();
             break;
         }
     }
+    p.exit();
     return Some(m.complete(p, SyntaxKind::FuncArgs));
 }
 
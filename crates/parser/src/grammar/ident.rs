@@ -12,7 +12,6 @@ fn path(p: &mut Parser, allow_prefix: bool) -> CompletedMarker {
         if allow_prefix {
             p.bump();
         } else {
-            println!("Unexpected Prefix!");
             p.error();
         }
     }
@@ -30,7 +29,6 @@ fn path(p: &mut Parser, allow_prefix: bool) -> CompletedMarker {
     }
 
     else {
-        println!("left the member open");
         m.complete(p, SyntaxKind::LJuke)
     };
 
@@ -41,12 +39,10 @@ fn path_sep(p: &mut Parser) {
     
     if p.at(TokenKind::Dot) {
         p.bump();
-        println!("recurse on dot");
         path(p, true);
     }
 
     else if p.at(TokenKind::ColonColon) {
-        println!("recurse on colon");
         p.bump();
         path(p, true);
     }
@@ -76,12 +72,6 @@ fn path_sep(p: &mut Parser) {
         p.expect(TokenKind::RBrack);
         m.complete(p, SyntaxKind::TypePrimitive);
 
-    }
-    
-    // Terminating
-    else {
-        println!("terminate on else");
-
     };
 
 }
@@ -4,6 +4,7 @@ use super::*;
 pub(super) fn expand(p: &mut Parser, open_on: TokenKind, close_on: TokenKind) -> Option<CompletedMarker> {
     let m = p.start();
     p.expect(open_on);
+    p.enter(close_on);
     loop {
 
         if p.at(TokenKind::Comma) {
@@ -16,11 +17,16 @@ pub(super) fn expand(p: &mut Parser, open_on: TokenKind, close_on: TokenKind) ->
         }
 
         if p.at_end() {
+            p.error();
             break;
         }
 
-        expr::expr(p);
+        let resumes = p.at_recovery();
+        if expr::expr(p).is_none() && resumes {
+            break;
+        }
     }
+    p.exit();
 
     return Some(m.complete(p, SyntaxKind::List));
 }
@@ -23,7 +23,6 @@ pub(super) fn build_standard(p: &mut Parser) -> Option<CompletedMarker> {
     } else {
         if p.at(TokenKind::LBrace) {
             let body = p.start();
-            println!("boop");
             strct::strct(p);
             body.complete(p, SyntaxKind::Body);
         }
@@ -43,7 +42,6 @@ pub(super) fn build_schema(p: &mut Parser) -> Option<CompletedMarker> {
     }
 
     if let Some(e) = expr::expr(p) {
-        println!("2A");
         if p.at(TokenKind::LBrace) {
             e.precede(p).complete(p, SyntaxKind::ConstraintExpr);
             let body = p.start();
@@ -53,10 +51,8 @@ pub(super) fn build_schema(p: &mut Parser) -> Option<CompletedMarker> {
             e.precede(p).complete(p, SyntaxKind::Body);
         }
     } else {
-        println!("2B");
         if p.at(TokenKind::LBrace) {
             let body = p.start();
-            println!("boop");
             strct::strct(p);
             body.complete(p, SyntaxKind::Body);
         }
@@ -18,20 +18,7 @@ fn build_stmt(p: &mut Parser) -> Option<CompletedMarker> {
     else if p.at(TokenKind::LSquiggleArrow) {
         record::build_inlined(p)
 
-    } else if p.at_literal() {
-        println!("MISSING <");
-        p.error();
-        expr::expr(p)
-    } else if p.at(TokenKind::Minus) {
-        println!("MISSING <");
-        p.error();
-        expr::expr(p)
-    } else if p.at(TokenKind::LParen) {
-        println!("MISSING <");
-        p.error();
-        expr::expr(p)
     } else {
-        println!("UNHANDLED CASE");
         p.error();
         expr::expr(p)
     };
@@ -88,30 +75,420 @@ Root@0..17
         );
     }
 
+    #[test]
+    fn parse_optional_record() {
+        check(
+            "foo?: 1",
+            expect![[r#"
+                Root@0..7
+                  Record@0..7
+                    Name@0..4
+                      Ident@0..3 "foo"
+                      QMark@3..4 "?"
+                    Colon@4..5 ":"
+                    Whitespace@5..6 " "
+                    Body@6..7
+                      Literal@6..7
+                        Number@6..7 "1""#]],
+        );
+    }
+
+    #[test]
+    fn parse_refined_scalar_type() {
+        check(
+            "hp: !scalar[!int & >=0]",
+            expect![[r#"
+                Root@0..23
+                  Record@0..23
+                    Name@0..2
+                      Ident@0..2 "hp"
+                    Colon@2..3 ":"
+                    Whitespace@3..4 " "
+                    Body@4..23
+                      Type@4..23
+                        Name@4..11
+                          Bang@4..5 "!"
+                          Ident@5..11 "scalar"
+                        LBrack@11..12 "["
+                        InfixExpr@12..22
+                          Type@12..17
+                            Name@12..17
+                              Bang@12..13 "!"
+                              Ident@13..16 "int"
+                              Whitespace@16..17 " "
+                          Ampersand@17..18 "&"
+                          Whitespace@18..19 " "
+                          PrefixExpr@19..22
+                            GreaterEqual@19..21 ">="
+                            Literal@21..22
+                              Number@21..22 "0"
+                        RBrack@22..23 "]""#]],
+        );
+    }
+
+    #[test]
+    fn parse_vector_type() {
+        check(
+            "pos: !vec[3 -> !float]",
+            expect![[r#"
+                Root@0..22
+                  Record@0..22
+                    Name@0..3
+                      Ident@0..3 "pos"
+                    Colon@3..4 ":"
+                    Whitespace@4..5 " "
+                    Body@5..22
+                      Type@5..22
+                        Name@5..9
+                          Bang@5..6 "!"
+                          Ident@6..9 "vec"
+                        LBrack@9..10 "["
+                        InfixExpr@10..21
+                          Literal@10..12
+                            Number@10..11 "3"
+                            Whitespace@11..12 " "
+                          RArrow@12..14 "->"
+                          Whitespace@14..15 " "
+                          Type@15..21
+                            Name@15..21
+                              Bang@15..16 "!"
+                              Ident@16..21 "float"
+                        RBrack@21..22 "]""#]],
+        );
+    }
+
+    #[test]
+    fn parse_enum_type() {
+        check(
+            "t: !enum{\n  --- #flags\n  A: 1 .> 0\n  --- #ranges\n  r: @self.A..@self.B\n}",
+            expect![[r##"
+                Root@0..72
+                  Record@0..72
+                    Name@0..1
+                      Ident@0..1 "t"
+                    Colon@1..2 ":"
+                    Whitespace@2..3 " "
+                    Body@3..72
+                      Type@3..72
+                        Name@3..8
+                          Bang@3..4 "!"
+                          Ident@4..8 "enum"
+                        Struct@8..72
+                          LBrace@8..9 "{"
+                          Whitespace@9..12 "\n  "
+                          Section@12..37
+                            SectionMarker@12..15 "---"
+                            Whitespace@15..16 " "
+                            Name@16..25
+                              Path@16..25
+                                Name@16..25
+                                  Octothorpe@16..17 "#"
+                                  Ident@17..22 "flags"
+                                  Whitespace@22..25 "\n  "
+                            Record@25..37
+                              Name@25..28
+                                Ident@25..26 "A"
+                                Colon@26..27 ":"
+                                Whitespace@27..28 " "
+                              Body@28..37
+                                InfixExpr@28..37
+                                  Literal@28..30
+                                    Number@28..29 "1"
+                                    Whitespace@29..30 " "
+                                  BitShiftRight@30..32 ".>"
+                                  Whitespace@32..33 " "
+                                  Literal@33..37
+                                    Number@33..34 "0"
+                                    Whitespace@34..37 "\n  "
+                          Section@37..71
+                            SectionMarker@37..40 "---"
+                            Whitespace@40..41 " "
+                            Name@41..51
+                              Path@41..51
+                                Name@41..51
+                                  Octothorpe@41..42 "#"
+                                  Ident@42..48 "ranges"
+                                  Whitespace@48..51 "\n  "
+                            Record@51..71
+                              Name@51..54
+                                Ident@51..52 "r"
+                                Colon@52..53 ":"
+                                Whitespace@53..54 " "
+                              Body@54..71
+                                InfixExpr@54..71
+                                  InfixExpr@54..61
+                                    Keyword@54..59
+                                      At@54..55 "@"
+                                      Ident@55..59 "self"
+                                    Dot@59..60 "."
+                                    Ref@60..61
+                                      Name@60..61
+                                        Ident@60..61 "A"
+                                  DotDot@61..63 ".."
+                                  InfixExpr@63..71
+                                    Keyword@63..68
+                                      At@63..64 "@"
+                                      Ident@64..68 "self"
+                                    Dot@68..69 "."
+                                    Ref@69..71
+                                      Name@69..71
+                                        Ident@69..70 "B"
+                                        Whitespace@70..71 "\n"
+                          RBrace@71..72 "}""##]],
+        );
+    }
+
     // TODO: Write proper test for circular declaration
     #[test]
     fn recover_on_bad_dec() {
         check(
             "a:\nb: a",
             expect![[r#"
-Root@0..7
-  Record@0..7
-    Name@0..3
-      Ident@0..1 "a"
-      Colon@1..2 ":"
-      Whitespace@2..3 "\n"
-    Body@3..7
-      InfixExpr@3..7
-        Ref@3..4
-          Path@3..4
-            Name@3..4
-              Ident@3..4 "b"
-        Colon@4..5 ":"
-        Whitespace@5..6 " "
-        Ref@6..7
-          Path@6..7
-            Name@6..7
-              Ident@6..7 "a""#]],
+                Root@0..7
+                  Record@0..3
+                    Name@0..1
+                      Ident@0..1 "a"
+                    Colon@1..2 ":"
+                    Whitespace@2..3 "\n"
+                    Body@3..3
+                  Record@3..7
+                    Name@3..4
+                      Ident@3..4 "b"
+                    Colon@4..5 ":"
+                    Whitespace@5..6 " "
+                    Body@6..7
+                      Ref@6..7
+                        Name@6..7
+                          Ident@6..7 "a"
+                error at 3..4: expected an expression, but found identifier"#]],
+        );
+    }
+
+    #[test]
+    fn recover_unclosed_struct_at_dedent() {
+        check(
+            "a: {\n  b: 1\nc: 2",
+            expect![[r#"
+                Root@0..16
+                  Record@0..12
+                    Name@0..1
+                      Ident@0..1 "a"
+                    Colon@1..2 ":"
+                    Whitespace@2..3 " "
+                    Body@3..12
+                      Struct@3..12
+                        LBrace@3..4 "{"
+                        Whitespace@4..7 "\n  "
+                        Record@7..12
+                          Name@7..10
+                            Ident@7..8 "b"
+                            Colon@8..9 ":"
+                            Whitespace@9..10 " "
+                          Body@10..12
+                            Literal@10..12
+                              Number@10..11 "1"
+                              Whitespace@11..12 "\n"
+                  Record@12..16
+                    Name@12..13
+                      Ident@12..13 "c"
+                    Colon@13..14 ":"
+                    Whitespace@14..15 " "
+                    Body@15..16
+                      Literal@15..16
+                        Number@15..16 "2"
+                error at 12..13: expected }, but found identifier"#]],
+        );
+    }
+
+    #[test]
+    fn unclosed_struct_keeps_indented_records() {
+        check(
+            "a: {\n  b: 1\n  c: 2",
+            expect![[r#"
+                Root@0..18
+                  Record@0..18
+                    Name@0..1
+                      Ident@0..1 "a"
+                    Colon@1..2 ":"
+                    Whitespace@2..3 " "
+                    Body@3..18
+                      Struct@3..18
+                        LBrace@3..4 "{"
+                        Whitespace@4..7 "\n  "
+                        Record@7..14
+                          Name@7..10
+                            Ident@7..8 "b"
+                            Colon@8..9 ":"
+                            Whitespace@9..10 " "
+                          Body@10..14
+                            Literal@10..14
+                              Number@10..11 "1"
+                              Whitespace@11..14 "\n  "
+                        Record@14..18
+                          Name@14..17
+                            Ident@14..15 "c"
+                            Colon@15..16 ":"
+                            Whitespace@16..17 " "
+                          Body@17..18
+                            Literal@17..18
+                              Number@17..18 "2"
+                error at 17..18: expected ,, ;, an operator, {, #, ---, identifier, $, >, <~, ! or }"#]],
+        );
+    }
+
+    #[test]
+    fn recover_stray_closer_in_struct() {
+        check(
+            "a: {\n  b: 1 ]\n  c: 2\n}",
+            expect![[r#"
+                Root@0..22
+                  Record@0..22
+                    Name@0..1
+                      Ident@0..1 "a"
+                    Colon@1..2 ":"
+                    Whitespace@2..3 " "
+                    Body@3..22
+                      Struct@3..22
+                        LBrace@3..4 "{"
+                        Whitespace@4..7 "\n  "
+                        Record@7..12
+                          Name@7..10
+                            Ident@7..8 "b"
+                            Colon@8..9 ":"
+                            Whitespace@9..10 " "
+                          Body@10..12
+                            Literal@10..12
+                              Number@10..11 "1"
+                              Whitespace@11..12 " "
+                        Error@12..16
+                          RBrack@12..13 "]"
+                          Whitespace@13..16 "\n  "
+                        Record@16..21
+                          Name@16..19
+                            Ident@16..17 "c"
+                            Colon@17..18 ":"
+                            Whitespace@18..19 " "
+                          Body@19..21
+                            Literal@19..21
+                              Number@19..20 "2"
+                              Whitespace@20..21 "\n"
+                        RBrace@21..22 "}"
+                error at 12..13: expected ,, ;, an operator, {, #, ---, identifier, $, >, <~, ! or }, but found ]"#]],
+        );
+    }
+
+    #[test]
+    fn recover_unclosed_paren_at_next_record() {
+        check(
+            "a: (1 + 2\nb: 3",
+            expect![[r#"
+                Root@0..14
+                  Record@0..10
+                    Name@0..1
+                      Ident@0..1 "a"
+                    Colon@1..2 ":"
+                    Whitespace@2..3 " "
+                    Body@3..10
+                      ParenExpr@3..10
+                        LParen@3..4 "("
+                        InfixExpr@4..10
+                          Literal@4..6
+                            Number@4..5 "1"
+                            Whitespace@5..6 " "
+                          Plus@6..7 "+"
+                          Whitespace@7..8 " "
+                          Literal@8..10
+                            Number@8..9 "2"
+                            Whitespace@9..10 "\n"
+                  Record@10..14
+                    Name@10..11
+                      Ident@10..11 "b"
+                    Colon@11..12 ":"
+                    Whitespace@12..13 " "
+                    Body@13..14
+                      Literal@13..14
+                        Number@13..14 "3"
+                error at 10..11: expected ,, ;, an operator or ), but found identifier"#]],
+        );
+    }
+
+    #[test]
+    fn recover_unclosed_list_at_next_record() {
+        check(
+            "a: [1, 2\nb: 3",
+            expect![[r#"
+                Root@0..13
+                  Record@0..9
+                    Name@0..1
+                      Ident@0..1 "a"
+                    Colon@1..2 ":"
+                    Whitespace@2..3 " "
+                    Body@3..9
+                      LBrack@3..4 "["
+                      List@4..9
+                        Row@4..9
+                          Entry@4..5
+                            Literal@4..5
+                              Number@4..5 "1"
+                          Comma@5..6 ","
+                          Whitespace@6..7 " "
+                          Entry@7..9
+                            Literal@7..9
+                              Number@7..8 "2"
+                              Whitespace@8..9 "\n"
+                  Record@9..13
+                    Name@9..10
+                      Ident@9..10 "b"
+                    Colon@10..11 ":"
+                    Whitespace@11..12 " "
+                    Body@12..13
+                      Literal@12..13
+                        Number@12..13 "3"
+                error at 9..10: expected ,, ;, an operator or ], but found identifier"#]],
+        );
+    }
+
+    #[test]
+    fn paren_leaves_closer_to_struct() {
+        check(
+            "a: { b: (1 + }\nc: 2",
+            expect![[r#"
+                Root@0..19
+                  Record@0..15
+                    Name@0..1
+                      Ident@0..1 "a"
+                    Colon@1..2 ":"
+                    Whitespace@2..3 " "
+                    Body@3..15
+                      Struct@3..15
+                        LBrace@3..4 "{"
+                        Whitespace@4..5 " "
+                        Record@5..13
+                          Name@5..8
+                            Ident@5..6 "b"
+                            Colon@6..7 ":"
+                            Whitespace@7..8 " "
+                          Body@8..13
+                            ParenExpr@8..13
+                              LParen@8..9 "("
+                              InfixExpr@9..13
+                                Literal@9..11
+                                  Number@9..10 "1"
+                                  Whitespace@10..11 " "
+                                Plus@11..12 "+"
+                                Whitespace@12..13 " "
+                        RBrace@13..14 "}"
+                        Whitespace@14..15 "\n"
+                  Record@15..19
+                    Name@15..16
+                      Ident@15..16 "c"
+                    Colon@16..17 ":"
+                    Whitespace@17..18 " "
+                    Body@18..19
+                      Literal@18..19
+                        Number@18..19 "2"
+                error at 13..14: expected an expression, but found }
+                error at 13..14: expected ), but found }"#]],
         );
     }
 }
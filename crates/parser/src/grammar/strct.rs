@@ -6,8 +6,21 @@ pub(super) fn strct(p: &mut Parser) -> CompletedMarker {
 
 fn build_struct(p: &mut Parser) -> CompletedMarker {
     let m = p.start();
+
+    // A struct that's never closed ends at the first record that isn't
+    // indented past the line it was opened on.
+    let unclosed = p.at_unclosed_brace();
+    let indent = p.line_indent();
+
     p.expect(TokenKind::LBrace);
+    p.enter(TokenKind::RBrace);
     loop {
+        if unclosed && p.at_record_start() && p.line_indent() <= indent {
+            p.clear_expected();
+            p.expect(TokenKind::RBrace);
+            break;
+        }
+
         if p.at(TokenKind::Octothorpe) {
             accessor::accessor(p);
     
@@ -36,10 +49,16 @@ fn build_struct(p: &mut Parser) -> CompletedMarker {
             break;
         }
         else {
+            // Skip what doesn't belong, unless something outside picks up
+            // from it.
+            let resumes = p.at_recovery();
             p.error();
-            break;
+            if resumes {
+                break;
+            }
         }
     }
+    p.exit();
     return m.complete(p, SyntaxKind::Struct);
 }
 
@@ -74,11 +93,11 @@ pub(super) fn build_section(p: &mut Parser) -> Option<CompletedMarker> {
     
 
     let close_brackets: bool = p.if_bump(TokenKind::LBrace);
+    if close_brackets {
+        p.enter(TokenKind::RBrace);
+    }
 
     loop {
-        println!(
-            "top of loop"
-        );
         if p.at(TokenKind::Octothorpe) {
             accessor::accessor(p);
     
@@ -90,7 +109,6 @@ pub(super) fn build_section(p: &mut Parser) -> Option<CompletedMarker> {
                break;
            }
         } else if p.at(TokenKind::Ident) {
-            println!("building ident");
             record::build_standard(p);
     
         } else if p.at (TokenKind::DollarSign) {
@@ -118,6 +136,9 @@ pub(super) fn build_section(p: &mut Parser) -> Option<CompletedMarker> {
             break;
         }
     }
+    if close_brackets {
+        p.exit();
+    }
 
     return Some(m.complete(p, SyntaxKind::Section))
 }
\ No newline at end of file
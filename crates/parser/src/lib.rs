@@ -1,10 +1,13 @@
 mod event;
 mod grammar;
 mod parser;
+mod reparse;
 mod sink;
 mod source;
 
-use crate::parser::{ParseError, Parser};
+use crate::parser::Parser;
+pub use crate::parser::ParseError;
+pub use reparse::TextEdit;
 use lexer::Lexer;
 use rowan::GreenNode;
 use sink::Sink;
@@ -21,6 +24,7 @@ pub fn parse(input: &str) -> Parse {
     sink.finish()
 }
 
+#[derive(Debug)]
 pub struct Parse {
     green_node: GreenNode,
     errors: Vec<ParseError>,
@@ -45,6 +49,10 @@ impl Parse {
     pub fn syntax(&self) -> SyntaxNode {
         SyntaxNode::new_root(self.green_node.clone())
     }
+
+    pub fn errors(&self) -> &[ParseError] {
+        &self.errors
+    }
 }
 
 #[cfg(test)]
@@ -1,7 +1,7 @@
 pub(crate) mod marker;
 
 mod parse_error;
-pub(crate) use parse_error::ParseError;
+pub use parse_error::ParseError;
 
 use crate::event::Event;
 use crate::grammar;
@@ -11,7 +11,7 @@ use marker::{Marker, CompletedMarker};
 use std::mem;
 use syntax::SyntaxKind;
 
-// Fix this
+/// Tokens an error never skips, because the grammar always has a use for them.
 const RECOVERY_SET: [TokenKind; 1] = [TokenKind::DotDotDot];
 const LITERAL_TOKENS: [TokenKind; 8] = [
     TokenKind::HexLiteral,
@@ -28,6 +28,8 @@ pub(crate) struct Parser<'t, 'input> {
     source: Source<'t, 'input>,
     events: Vec<Event>,
     expected_kinds: Vec<TokenKind>,
+    /// The closing delimiters of the groups being parsed, innermost last.
+    closers: Vec<TokenKind>,
 }
 
 impl<'t, 'input> Parser<'t, 'input> {
@@ -36,6 +38,7 @@ impl<'t, 'input> Parser<'t, 'input> {
             source,
             events: Vec::new(),
             expected_kinds: Vec::new(),
+            closers: Vec::new(),
         }
     }
 
@@ -44,6 +47,16 @@ impl<'t, 'input> Parser<'t, 'input> {
         self.events
     }
 
+    /// Parses a lone struct, or `None` if anything is left after it.
+    pub(crate) fn parse_struct(mut self) -> Option<Vec<Event>> {
+        grammar::lone_struct(&mut self);
+        if self.at_end() {
+            Some(self.events)
+        } else {
+            None
+        }
+    }
+
     pub(crate) fn start(&mut self) -> Marker {
         let pos = self.events.len();
         self.events.push(Event::Placeholder);
@@ -65,14 +78,10 @@ impl<'t, 'input> Parser<'t, 'input> {
         self.expected_kinds.clear();
     }
 
-    // TODO: I "Should" be able to back-climb and do a smart recovery based on core token sequences,
-    //       which would add a ton of utility for the LSP. Rather than recover on 'let', as you would
-    //       in rust, I'd have to have a set of context-specific recovery functions, recovering on :
-    //       in records, on :: in accessors, terminating an unclosed function call at the start of
-    //       the following block, etc.
+    /// Reports what was expected here, then skips the current token unless
+    /// something being parsed can pick back up from it.
     pub(crate) fn error(&mut self) {
         let current_token = self.source.peek_token();
-        println!("Failed on: {:?}", current_token);
 
         let (found, range) = if let Some(Token { kind, range, .. }) = current_token {
             (Some(*kind), *range)
@@ -88,60 +97,71 @@ impl<'t, 'input> Parser<'t, 'input> {
             range,
         }));
 
-        // TODO: Figure out how to finesse this.
-        if !self.at_set(&RECOVERY_SET) && !self.at_end() {
+        if !self.at_recovery() {
             let m = self.start();
             self.bump();
             m.complete(self, SyntaxKind::Error);
         }
     }
 
-    /// Effectively error(), but with the theoretical ability to recover somehow.
-    #[allow(dead_code)]
-    pub(crate) fn smart_error(&mut self, failed_on: SyntaxKind) {
-        let current_token = self.source.peek_token();
-        println!("Failed on: {:?}", current_token);
-
-        let (found, range) = if let Some(Token { kind, range, .. }) = current_token {
-            (Some(*kind), *range)
-        } else {
-            // If were at the end of the input we use the range of the very last token in the
-            // input.
-            (None, self.source.last_token_range().unwrap())
-        };
-
-        self.events.push(Event::Error(ParseError {
-            expected: mem::take(&mut self.expected_kinds),
-            found,
-            range,
-        }));
+    /// Opens a delimited group. Until the matching `exit`, errors inside it
+    /// leave `closer` alone, so a stray `}` in a paren still closes the
+    /// struct around it.
+    pub(crate) fn enter(&mut self, closer: TokenKind) {
+        self.closers.push(closer);
+    }
 
-        // Should I write error-recovery functions in each module, rather than here?
-        // Then I could skip this match statement, call some stub function that just
-        // does the above, and skip this whole match statement.
-        match failed_on {
-            SyntaxKind::Accessor => {},
-            SyntaxKind::Directive => {},
+    pub(crate) fn exit(&mut self) {
+        self.closers.pop();
+    }
 
-            SyntaxKind::Expr => {},
-            SyntaxKind::InfixExpr => {},
-            SyntaxKind::PrefixExpr => {},
-            SyntaxKind::ParenExpr => {},
+    /// Whether the current token is somewhere parsing can resume: the end of
+    /// the input, the closer of an open group, or the start of a record.
+    pub(crate) fn at_recovery(&mut self) -> bool {
+        match self.peek() {
+            None => true,
+            Some(kind) => {
+                RECOVERY_SET.contains(&kind) || self.closers.contains(&kind) || self.at_record_start()
+            }
+        }
+    }
 
-            SyntaxKind::FuncCall => {},
-            SyntaxKind::FuncArgs => {},
+    /// Whether a record starts here: a line beginning with `name:`, `name?:`,
+    /// `$name:`, `#name:` or `---`. Nothing else can start a line that way,
+    /// so these are where a broken record gives up and the next one begins.
+    pub(crate) fn at_record_start(&mut self) -> bool {
+        if !self.source.at_line_start() {
+            return false;
+        }
 
-            SyntaxKind::Ident => {},
+        let kinds = [0, 1, 2].map(|n| self.source.nth_kind(n));
+        matches!(
+            kinds,
+            [Some(TokenKind::SectionMarker), ..]
+                | [Some(TokenKind::Ident), Some(TokenKind::Colon), _]
+                | [Some(TokenKind::Ident), Some(TokenKind::QMark), Some(TokenKind::Colon)]
+                | [
+                    Some(TokenKind::DollarSign | TokenKind::Octothorpe),
+                    Some(TokenKind::Ident),
+                    Some(TokenKind::Colon)
+                ]
+        )
+    }
 
-            SyntaxKind::List => {},
+    /// Whether the current token is the first on its line, even if the line
+    /// break before it was already taken as trailing whitespace.
+    pub(crate) fn at_line_start(&mut self) -> bool {
+        self.source.at_line_start()
+    }
 
-            SyntaxKind::ScopeBlock => {},
-            SyntaxKind::Struct => {},
-            SyntaxKind::Literal => {},
-            SyntaxKind::Record => {},
-            _ => println!("Failed in {:?}", failed_on)
-        }
+    /// How far the line holding the current token is indented.
+    pub(crate) fn line_indent(&mut self) -> usize {
+        self.source.line_indent()
+    }
 
+    /// Whether the current token is a `{` that's never closed.
+    pub(crate) fn at_unclosed_brace(&mut self) -> bool {
+        self.source.at_unclosed_brace()
     }
 
     /// Advances the iterator and clears the expected token list
@@ -159,7 +179,11 @@ impl<'t, 'input> Parser<'t, 'input> {
     /// Check token and add it to list of expected tokens.
     /// Adds context information to error messages.
     pub(crate) fn at(&mut self, kind: TokenKind) -> bool {
-        self.expected_kinds.push(kind);
+        // Alternatives are often checked for more than once on the way to an
+        // error, but only need listing once.
+        if !self.expected_kinds.contains(&kind) {
+            self.expected_kinds.push(kind);
+        }
         self.peek() == Some(kind)
     }
 
@@ -171,6 +195,10 @@ impl<'t, 'input> Parser<'t, 'input> {
         return self.source.lookback() == Some(kind);
     }
 
+    pub(crate) fn at_line_break(&mut self) -> bool {
+        self.source.at_line_break()
+    }
+
     pub(crate) fn at_expandable(&mut self) -> bool {
         if let Some(curr) = self.peek() {
             return curr.is_prefix() || curr == TokenKind::Ident;
@@ -187,7 +215,6 @@ impl<'t, 'input> Parser<'t, 'input> {
         return false;
     }
 
-    #[allow(dead_code)]
     pub(crate) fn at_digraph(&mut self, pair: [TokenKind; 2]) -> bool {
         return self.source.peek_pair() == [Some(pair[0]), Some(pair[1])]
     }
@@ -1,37 +1,146 @@
+use diagnostics::Diagnostic;
 use lexer::TokenKind;
 use std::fmt;
 use text_size::TextRange;
 
-#[derive(Debug, PartialEq)]
-pub(crate) struct ParseError {
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
     pub(super) expected: Vec<TokenKind>,
     pub(super) found: Option<TokenKind>,
     pub(super) range: TextRange,
 }
 
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(
-            f,
-            "error at {}..{}: expected ",
-            u32::from(self.range.start()),
-            u32::from(self.range.end()),
-        )?;
+impl ParseError {
+    pub fn range(&self) -> TextRange {
+        self.range
+    }
+
+    pub(crate) fn found(&self) -> Option<TokenKind> {
+        self.found
+    }
+
+    /// The same error, at `range` instead.
+    pub(crate) fn moved(&self, range: TextRange) -> Self {
+        Self {
+            range,
+            ..self.clone()
+        }
+    }
+}
+
+/// Everything that can start an expression, in the order `lhs` checks for it.
+const EXPRESSION_TOKENS: [TokenKind; 26] = [
+    TokenKind::HexLiteral,
+    TokenKind::IntLiteral,
+    TokenKind::FloatLiteral,
+    TokenKind::OctalLiteral,
+    TokenKind::BinaryLiteral,
+    TokenKind::AddressLiteral,
+    TokenKind::RandIntLiteral,
+    TokenKind::RandFloatLiteral,
+    TokenKind::String,
+    TokenKind::LBrace,
+    TokenKind::Plus,
+    TokenKind::Minus,
+    TokenKind::Star,
+    TokenKind::Slash,
+    TokenKind::Carrot,
+    TokenKind::Percent,
+    TokenKind::Colon,
+    TokenKind::LessEqual,
+    TokenKind::GreaterEqual,
+    TokenKind::LAngleBrack,
+    TokenKind::RAngleBrack,
+    TokenKind::DotDot,
+    TokenKind::DotDotDot,
+    TokenKind::LParen,
+    TokenKind::LBrack,
+    TokenKind::LBird,
+];
+
+/// Everything that can join two expressions.
+const OPERATOR_TOKENS: [TokenKind; 14] = [
+    TokenKind::Plus,
+    TokenKind::Minus,
+    TokenKind::Star,
+    TokenKind::Slash,
+    TokenKind::Percent,
+    TokenKind::BitShiftRight,
+    TokenKind::BitShiftLeft,
+    TokenKind::DotDot,
+    TokenKind::Ampersand,
+    TokenKind::Bar,
+    TokenKind::RSquiggleArrow,
+    TokenKind::RArrow,
+    TokenKind::Dot,
+    TokenKind::ColonColon,
+];
+
+impl ParseError {
+    /// What was expected, with any whole set of expression starts or operators
+    /// named once where its first token would have been.
+    fn expected_names(&self) -> Vec<String> {
+        let groups = [
+            ("an expression", &EXPRESSION_TOKENS[..]),
+            ("an operator", &OPERATOR_TOKENS[..]),
+        ];
+        let whole: Vec<_> = groups
+            .into_iter()
+            .filter(|(_, kinds)| kinds.iter().all(|kind| self.expected.contains(kind)))
+            .collect();
 
-        let num_expected = self.expected.len();
+        let mut names = Vec::new();
+        for kind in &self.expected {
+            match whole.iter().find(|(_, kinds)| kinds.contains(kind)) {
+                Some((name, _)) => {
+                    if !names.iter().any(|n| n == name) {
+                        names.push(name.to_string());
+                    }
+                }
+                None => names.push(kind.to_string()),
+            }
+        }
+        names
+    }
+
+    fn write_expected(&self, f: &mut impl fmt::Write) -> fmt::Result {
+        let names = self.expected_names();
+        let num_expected = names.len();
         let is_first = |idx| idx == 0;
         let is_last = |idx| idx == num_expected - 1;
 
-        for (idx, expected_kind) in self.expected.iter().enumerate() {
+        for (idx, name) in names.iter().enumerate() {
             if is_first(idx) {
-                write!(f, "{}", expected_kind)?;
+                write!(f, "{}", name)?;
             } else if is_last(idx) {
-                write!(f, " or {}", expected_kind)?;
+                write!(f, " or {}", name)?;
             } else {
-                write!(f, ", {}", expected_kind)?;
+                write!(f, ", {}", name)?;
             }
         }
 
+        Ok(())
+    }
+
+    fn expected_list(&self) -> String {
+        let mut out = String::new();
+        // Writing into a `String` can't fail.
+        self.write_expected(&mut out).unwrap();
+        out
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "error at {}..{}: expected ",
+            u32::from(self.range.start()),
+            u32::from(self.range.end()),
+        )?;
+
+        self.write_expected(f)?;
+
         if let Some(found) = self.found {
             write!(f, ", but found {}", found)?;
         }
@@ -40,6 +149,18 @@ impl fmt::Display for ParseError {
     }
 }
 
+impl From<&ParseError> for Diagnostic {
+    fn from(error: &ParseError) -> Self {
+        let expected = error.expected_list();
+        match error.found {
+            Some(found) => Diagnostic::error(format!("expected {}, but found {}", expected, found))
+                .with_primary(error.range, format!("unexpected {}", found)),
+            None => Diagnostic::error(format!("expected {}", expected))
+                .with_primary(error.range, "expected here"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -108,4 +229,52 @@ mod tests {
             "error at 100..105: expected integer, identifier, - or (, but found =",
         );
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn every_expression_start_is_an_expression() {
+        let mut expected = EXPRESSION_TOKENS.to_vec();
+        expected.push(TokenKind::RParen);
+        check(
+            expected,
+            Some(TokenKind::Equals),
+            0..1,
+            "error at 0..1: expected an expression or ), but found =",
+        );
+    }
+
+    #[test]
+    fn every_operator_is_an_operator() {
+        let mut expected = vec![TokenKind::Comma];
+        expected.extend(OPERATOR_TOKENS);
+        expected.push(TokenKind::RBrack);
+        check(
+            expected,
+            Some(TokenKind::Ident),
+            0..1,
+            "error at 0..1: expected ,, an operator or ], but found identifier",
+        );
+    }
+
+    #[test]
+    fn some_operators_are_listed() {
+        check(
+            vec![TokenKind::Plus, TokenKind::Dot, TokenKind::RBrace],
+            None,
+            0..1,
+            "error at 0..1: expected +, . or }",
+        );
+    }
+
+    #[test]
+    fn diagnostic_labels_what_was_found() {
+        let error = ParseError {
+            expected: vec![TokenKind::Colon, TokenKind::Equals],
+            found: Some(TokenKind::IntLiteral),
+            range: TextRange::new(3.into(), 4.into()),
+        };
+        let diagnostic = Diagnostic::from(&error);
+
+        assert_eq!(diagnostic.message, "expected : or =, but found integer");
+        assert_eq!(diagnostic.primary().unwrap().message, "unexpected integer");
+    }
+}
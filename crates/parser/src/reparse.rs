@@ -0,0 +1,540 @@
+/*
+TL;DR: Parsing again after an edit, touching as little of the tree as we can.
+
+    hp: 5             `5` -> `50`              relex the one token
+    stats: {          an edit inside `{...}`   reparse the struct
+      hp: 5
+    }
+    max: hp           `hp` -> `hp + 1`         reparse the record, and the
+                                               one after it to see it still
+                                               ends where it did
+
+Green nodes outside what's reparsed are kept as they are, and the errors
+after it move with the text. Whenever an edit could change how anything
+around it parses, we parse the whole text again instead, so the result is
+always what `parse` would give.
+*/
+
+use crate::parser::Parser;
+use crate::sink::Sink;
+use crate::source::Source;
+use crate::{parse, Parse, ParseError};
+use lexer::{Lexer, Token, TokenKind};
+use rowan::{GreenNode, GreenToken, Language, NodeOrToken};
+use std::ops::Range;
+use syntax::{SyntaxKind, SyntaxNode, SyntaxToken, VADA};
+use text_size::{TextRange, TextSize};
+
+/// Replaces the text in `range` with `replacement`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextEdit {
+    pub range: TextRange,
+    pub replacement: String,
+}
+
+impl TextEdit {
+    pub fn new(range: TextRange, replacement: impl Into<String>) -> Self {
+        Self {
+            range,
+            replacement: replacement.into(),
+        }
+    }
+
+    pub fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        text.replace_range(Range::<usize>::from(self.range), &self.replacement);
+        text
+    }
+
+    /// Applies the edit to `text`, which starts at `start` in the edited text.
+    fn apply_at(&self, text: &str, start: TextSize) -> String {
+        let mut text = text.to_string();
+        text.replace_range(Range::<usize>::from(self.range - start), &self.replacement);
+        text
+    }
+}
+
+impl Parse {
+    /// The parse of this text with `edit` applied, reusing whatever the edit
+    /// can't have changed.
+    pub fn reparse(&self, edit: &TextEdit) -> Parse {
+        reparse_token(self, edit)
+            .or_else(|| reparse_block(self, edit))
+            .unwrap_or_else(|| parse(&edit.apply(&self.syntax().to_string())))
+    }
+}
+
+/// Relexes the token the edit falls in. It has to stay one token of the same
+/// kind, and keep its newlines out of it, since those decide where lines
+/// start.
+fn reparse_token(parse: &Parse, edit: &TextEdit) -> Option<Parse> {
+    let root = parse.syntax();
+    if !root.text_range().contains_range(edit.range) {
+        return None;
+    }
+
+    // An insertion between two tokens could belong to either.
+    let tokens: Vec<SyntaxToken> = if edit.range.is_empty() {
+        root.token_at_offset(edit.range.start()).collect()
+    } else {
+        root.covering_element(edit.range).into_token().into_iter().collect()
+    };
+
+    tokens.iter().find_map(|token| relex(parse, token, edit))
+}
+
+fn relex(parse: &Parse, token: &SyntaxToken, edit: &TextEdit) -> Option<Parse> {
+    let range = token.text_range();
+    if !range.contains_range(edit.range) {
+        return None;
+    }
+
+    let text = edit.apply_at(token.text(), range.start());
+    if text.contains('\n') || token.text().contains('\n') {
+        return None;
+    }
+
+    // Whitespace that a file starts with sets the indent of its first line.
+    let kind = lone_token(token.text())?;
+    let first = token.prev_token().is_none();
+    if lone_token(&text)? != kind || (first && kind == TokenKind::Whitespace) {
+        return None;
+    }
+
+    if !lexes_alone(token.prev_token(), &text, token.next_token()) {
+        return None;
+    }
+
+    let green = token.replace_with(GreenToken::new(VADA::kind_to_raw(kind.into()), &text));
+    let relexed = TextRange::at(range.start(), TextSize::of(text.as_str()));
+    let moved = parse
+        .errors
+        .iter()
+        .filter(|error| error.range() == range)
+        .map(|error| error.moved(relexed))
+        .collect();
+
+    Some(Parse {
+        green_node: green,
+        errors: splice_errors(&parse.errors, range, relexed, moved)?,
+    })
+}
+
+/// Reparses the smallest struct, or record at the top of the file, that
+/// holds the edit and can be parsed on its own.
+fn reparse_block(parse: &Parse, edit: &TextEdit) -> Option<Parse> {
+    let root = parse.syntax();
+    if !root.text_range().contains_range(edit.range) {
+        return None;
+    }
+
+    let covering = match root.covering_element(edit.range) {
+        NodeOrToken::Node(node) => node,
+        NodeOrToken::Token(token) => token.parent(),
+    };
+
+    covering.ancestors().find_map(|node| match node.kind() {
+        SyntaxKind::Struct => reparse_struct(parse, &node, edit),
+        SyntaxKind::Record | SyntaxKind::Schema
+            if node.parent().is_some_and(|parent| parent.kind() == SyntaxKind::Root) =>
+        {
+            reparse_record(parse, &node, edit)
+        }
+        _ => None,
+    })
+}
+
+/// Reparses a struct on its own. Its braces have to match before and after
+/// and the edit has to stay between them, so it still ends at the same `}`
+/// and nothing outside it can tell.
+fn reparse_struct(parse: &Parse, node: &SyntaxNode, edit: &TextEdit) -> Option<Parse> {
+    let range = node.text_range();
+    let old = node.text().to_string();
+    let inside = braced(&old)? + range.start();
+    if !inside.contains_range(edit.range) || !own_errors(&parse.errors, range, false) {
+        return None;
+    }
+
+    let text = edit.apply_at(&old, range.start());
+    braced(&text)?;
+    if !lexes_alone(node.first_token()?.prev_token(), &text, node.last_token()?.next_token()) {
+        return None;
+    }
+
+    let tokens = lex(&text);
+    let events = Parser::new(Source::new(&tokens)).parse_struct()?;
+    let lone = Sink::new(&tokens, events).finish();
+
+    // Running out of input, or stopping at a closer, would have gone
+    // differently with the rest of the file around it.
+    let outside = |error: &ParseError| {
+        matches!(
+            error.found(),
+            None | Some(TokenKind::RParen | TokenKind::RBrack | TokenKind::RBrace)
+        )
+    };
+    if lone.errors.iter().any(outside) {
+        return None;
+    }
+
+    let mut children = child_nodes(&lone)?;
+    if children.len() != 1 {
+        return None;
+    }
+
+    let reparsed = TextRange::at(range.start(), TextSize::of(text.as_str()));
+    Some(Parse {
+        green_node: node.replace_with(children.remove(0)),
+        errors: splice_errors(&parse.errors, range, reparsed, shifted(&lone.errors, range.start()))?,
+    })
+}
+
+/// Reparses a record at the top of the file on its own, together with the
+/// node after it to check that it still ends where it did. Whatever is
+/// before it looks a few tokens ahead to see where it starts, so those can't
+/// change.
+fn reparse_record(parse: &Parse, node: &SyntaxNode, edit: &TextEdit) -> Option<Parse> {
+    let root = parse.syntax();
+    let range = node.text_range();
+    if !range.contains_range(edit.range) {
+        return None;
+    }
+
+    let next = node.next_sibling();
+    let end = next.as_ref().map_or(range.end(), |next| next.text_range().end());
+    let at_end = end == root.text_range().end();
+    let window = TextRange::new(range.start(), end);
+    if !own_errors(&parse.errors, window, at_end) {
+        return None;
+    }
+
+    // It has to start a line of its own, as it does when parsed alone.
+    let before = node.first_token()?.prev_token();
+    if !before.as_ref().is_none_or(ends_line) {
+        return None;
+    }
+
+    let old = node.text().to_string();
+    let text = edit.apply_at(&old, range.start());
+    let (old_tokens, new_tokens) = (lex(&old), lex(&text));
+    let old_lead = lead(&old_tokens);
+    if old_lead.len() < 3 || old_lead != lead(&new_tokens) {
+        return None;
+    }
+    if !balanced(&old_tokens) || !balanced(&new_tokens) {
+        return None;
+    }
+
+    // The node after has to start a line the same way, and hold as much as
+    // this record looks ahead into it.
+    if let Some(next) = &next {
+        let kept = match (old_tokens.last(), new_tokens.last()) {
+            (Some(old), Some(new)) => {
+                old.kind == TokenKind::Whitespace && old.text.ends_with('\n') && old.text == new.text
+            }
+            _ => false,
+        };
+        let ahead = next
+            .descendants_with_tokens()
+            .filter_map(NodeOrToken::into_token)
+            .filter(|token| !is_trivia(token.kind()))
+            .take(3)
+            .count();
+        if !kept || (ahead < 3 && !at_end) {
+            return None;
+        }
+    }
+
+    if !lexes_alone(before, &text, node.last_token()?.next_token()) {
+        return None;
+    }
+
+    let lone = crate::parse(&format!(
+        "{}{}",
+        text,
+        next.as_ref().map_or(String::new(), |next| next.text().to_string())
+    ));
+    if !at_end && lone.errors.iter().any(|error| error.found().is_none()) {
+        return None;
+    }
+
+    let mut children = child_nodes(&lone)?;
+    if let Some(next) = &next {
+        if children.pop()? != next.green().to_owned() {
+            return None;
+        }
+    }
+    if children.is_empty() {
+        return None;
+    }
+
+    let index = root.children_with_tokens().position(|child| child.as_node() == Some(node))?;
+    let mut elements: Vec<NodeOrToken<GreenNode, GreenToken>> = root.green().children().map(owned).collect();
+    elements.splice(index..=index, children.into_iter().map(NodeOrToken::Node));
+
+    let reparsed = TextRange::at(range.start(), lone.syntax().text_range().len());
+    Some(Parse {
+        green_node: GreenNode::new(VADA::kind_to_raw(SyntaxKind::Root), elements),
+        errors: splice_errors(&parse.errors, window, reparsed, shifted(&lone.errors, range.start()))?,
+    })
+}
+
+fn lex(text: &str) -> Vec<Token<'_>> {
+    Lexer::new(text).collect()
+}
+
+/// The kind of the one token `text` lexes as.
+fn lone_token(text: &str) -> Option<TokenKind> {
+    match lex(text).as_slice() {
+        [token] => Some(token.kind),
+        _ => None,
+    }
+}
+
+/// Whether `text` lexes the same between the tokens around it as it does
+/// alone, so the edit doesn't run into them.
+fn lexes_alone(before: Option<SyntaxToken>, text: &str, after: Option<SyntaxToken>) -> bool {
+    let before = before.map_or(String::new(), |token| token.text().to_string());
+    let after = after.map_or(String::new(), |token| token.text().to_string());
+
+    let joined = format!("{}{}{}", before, text, after);
+    let apart = [before.as_str(), text, after.as_str()]
+        .into_iter()
+        .flat_map(|part| lex(part).into_iter().map(|token| (token.kind, token.text)))
+        .collect::<Vec<(TokenKind, &str)>>();
+
+    lex(&joined).iter().map(|token| (token.kind, token.text)).eq(apart)
+}
+
+/// Where the inside of a `{...}` is, if `text` is one followed only by
+/// trivia.
+fn braced(text: &str) -> Option<TextRange> {
+    let tokens = lex(text);
+    if tokens.first()?.kind != TokenKind::LBrace {
+        return None;
+    }
+
+    let mut depth = 0;
+    for (idx, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LBrace => depth += 1,
+            TokenKind::RBrace => depth -= 1,
+            _ => {}
+        }
+
+        if depth == 0 {
+            let rest = &tokens[idx + 1..];
+            return rest
+                .iter()
+                .all(|token| token.kind.is_trivia())
+                .then(|| TextRange::new(tokens[0].range.end(), token.range.start()));
+        }
+    }
+
+    None
+}
+
+/// Whether every `}` closes a `{` from the same tokens, and every `{` is
+/// closed, so the braces around them still pair up the same way.
+fn balanced(tokens: &[Token]) -> bool {
+    let mut depth = 0;
+    for token in tokens {
+        match token.kind {
+            TokenKind::LBrace => depth += 1,
+            TokenKind::RBrace if depth == 0 => return false,
+            TokenKind::RBrace => depth -= 1,
+            _ => {}
+        }
+    }
+
+    depth == 0
+}
+
+/// The first three tokens that aren't trivia.
+fn lead(tokens: &[Token]) -> Vec<TokenKind> {
+    tokens
+        .iter()
+        .map(|token| token.kind)
+        .filter(|kind| !kind.is_trivia())
+        .take(3)
+        .collect()
+}
+
+fn ends_line(token: &SyntaxToken) -> bool {
+    token.kind() == SyntaxKind::Whitespace && token.text().ends_with('\n')
+}
+
+fn is_trivia(kind: SyntaxKind) -> bool {
+    matches!(kind, SyntaxKind::Whitespace | SyntaxKind::Comment)
+}
+
+/// Whether the errors inside `range` all came from parsing it. One at its
+/// very start may be from whatever came before, and the end of the input
+/// only looks the same to a lone parse if it's really there.
+fn own_errors(errors: &[ParseError], range: TextRange, at_end: bool) -> bool {
+    errors
+        .iter()
+        .filter(|error| range.contains_range(error.range()))
+        .all(|error| error.range().start() != range.start() && (at_end || error.found().is_some()))
+}
+
+/// The nodes a lone parse made, or `None` if it left tokens outside them.
+fn child_nodes(lone: &Parse) -> Option<Vec<GreenNode>> {
+    lone.green_node
+        .children()
+        .map(|child| child.into_node().cloned())
+        .collect()
+}
+
+fn owned(element: NodeOrToken<&GreenNode, &GreenToken>) -> NodeOrToken<GreenNode, GreenToken> {
+    match element {
+        NodeOrToken::Node(node) => NodeOrToken::Node(node.clone()),
+        NodeOrToken::Token(token) => NodeOrToken::Token(token.clone()),
+    }
+}
+
+fn shifted(errors: &[ParseError], offset: TextSize) -> Vec<ParseError> {
+    errors
+        .iter()
+        .map(|error| error.moved(error.range() + offset))
+        .collect()
+}
+
+/// The errors once `old` has been reparsed into `new`: those before it stay,
+/// those after it move with the text, and those inside it are replaced by
+/// `inside`. `None` if one straddles its edge.
+fn splice_errors(
+    errors: &[ParseError],
+    old: TextRange,
+    new: TextRange,
+    inside: Vec<ParseError>,
+) -> Option<Vec<ParseError>> {
+    let mut spliced = Vec::new();
+    let mut inside = Some(inside);
+
+    for error in errors {
+        let range = error.range();
+        if range.end() <= old.start() {
+            spliced.push(error.clone());
+        } else if range.start() >= old.end() {
+            spliced.extend(inside.take().into_iter().flatten());
+            spliced.push(error.moved(range - old.end() + new.end()));
+        } else if !old.contains_range(range) {
+            return None;
+        }
+    }
+
+    spliced.extend(inside.into_iter().flatten());
+    Some(spliced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn edit(text: &str, from: &str, to: &str) -> TextEdit {
+        let start = text.find(from).unwrap();
+        TextEdit::new(
+            TextRange::at(TextSize::from(start as u32), TextSize::of(from)),
+            to,
+        )
+    }
+
+    fn check(text: &str, edit: &TextEdit) {
+        let full = parse(&edit.apply(text));
+        let reparsed = parse(text).reparse(edit);
+
+        assert_eq!(
+            reparsed.debug_tree(),
+            full.debug_tree(),
+            "reparsing {:?} with {:?}",
+            text,
+            edit
+        );
+    }
+
+    #[test]
+    fn relexes_one_token() {
+        let text = "hp: 5\nmax: hp\nbad: (1\n";
+        let edit = edit(text, "5", "50");
+
+        assert!(reparse_token(&parse(text), &edit).is_some());
+        check(text, &edit);
+    }
+
+    #[test]
+    fn keeps_untouched_green_nodes() {
+        let text = "hp: 5\nmax: hp\n";
+        let old = parse(text);
+        let new = old.reparse(&edit(text, "5", "50"));
+
+        assert_eq!(
+            old.syntax().children().nth(1).unwrap().green().to_owned(),
+            new.syntax().children().nth(1).unwrap().green().to_owned()
+        );
+    }
+
+    #[test]
+    fn reparses_a_struct() {
+        let text = "stats: {\n  hp: 5\n  max: hp\n}\nname: \"goblin\"\n";
+        let edit = edit(text, "5", "5 + (1");
+
+        let old = parse(text);
+        let stats = old.syntax().descendants().find(|node| node.kind() == SyntaxKind::Struct).unwrap();
+
+        assert!(reparse_token(&old, &edit).is_none());
+        assert!(reparse_struct(&old, &stats, &edit).is_some());
+        check(text, &edit);
+    }
+
+    #[test]
+    fn reparses_a_record() {
+        let text = "hp: 5\nmax: hp\nname: \"goblin\"\n";
+        let edit = edit(text, "hp\n", "hp + 1\nmin: 1 +\n");
+
+        assert!(reparse_token(&parse(text), &edit).is_none());
+        assert!(reparse_block(&parse(text), &edit).is_some());
+        check(text, &edit);
+    }
+
+    #[test]
+    fn parses_everything_when_braces_move() {
+        let text = "stats: {\n  hp: 5\n}\nname: 1\n";
+        let edit = edit(text, "5", "{5");
+
+        assert!(reparse_token(&parse(text), &edit).is_none());
+        assert!(reparse_block(&parse(text), &edit).is_none());
+        check(text, &edit);
+    }
+
+    #[test]
+    fn matches_a_full_parse() {
+        let texts = [
+            "hp: 5\nmax: hp\n",
+            "stats: {\n  hp: 5\n  max: hp * 2\n}\nname: \"goblin\"\n",
+            "a: {b: {c: 1}\n  d: [1, 2]}\ne: (1 + 2) * 3\n",
+            "$unit: {\n  hp: $int & 0..300\n}\ngoblin: $unit & {hp: 5}\n",
+            "#zalgo: @global::zalgo\nx: @zalgo.hp // comment\n",
+            "a: {\n  b: 1\nc: 2\nd: (3,\n",
+            "---\nhp?: 5\n---\nmax: hp\n",
+            "var: 1;2;3 ~> b\n  w: <~ thing.do(test, 4+4)\n",
+        ];
+        let insertions = ["x", "1", " ", "\n", "{", "}", ":", "(", "\"", "hp: "];
+
+        for text in texts {
+            for offset in 0..=text.len() {
+                let at = TextSize::from(offset as u32);
+                for insertion in insertions {
+                    check(text, &TextEdit::new(TextRange::empty(at), insertion));
+                }
+                if offset < text.len() {
+                    check(text, &TextEdit::new(TextRange::at(at, 1.into()), ""));
+                    check(text, &TextEdit::new(TextRange::at(at, 1.into()), "y"));
+                }
+                for end in offset..=text.len().min(offset + 8) {
+                    check(text, &TextEdit::new(TextRange::new(at, (end as u32).into()), "z: 1"));
+                }
+            }
+        }
+    }
+}
@@ -4,11 +4,28 @@ use text_size::TextRange;
 pub(crate) struct Source<'t, 'input> {
     tokens: &'t [Token<'input>],
     cursor: usize,
+    /// Indices of the `{` tokens that are never closed, in order.
+    unclosed: Vec<usize>,
 }
 
 impl<'t, 'input> Source<'t, 'input> {
     pub(crate) fn new(tokens: &'t [Token<'input>]) -> Self {
-        Self { tokens, cursor: 0 }
+        let mut open = Vec::new();
+        for (idx, token) in tokens.iter().enumerate() {
+            match token.kind {
+                TokenKind::LBrace => open.push(idx),
+                TokenKind::RBrace => {
+                    open.pop();
+                }
+                _ => {}
+            }
+        }
+
+        Self {
+            tokens,
+            cursor: 0,
+            unclosed: open,
+        }
     }
 
     pub(crate) fn next_token(&mut self) -> Option<&'t Token<'input>> {
@@ -74,13 +91,68 @@ impl<'t, 'input> Source<'t, 'input> {
 
 
 
+    /// Whether the next token starts on a new line, or there isn't one.
+    pub(crate) fn at_line_break(&self) -> bool {
+        let mut n = 0;
+        while let Some(Token { kind, text, .. }) = self.tokens.get(self.cursor + n) {
+            if !kind.is_trivia() {
+                return false;
+            }
+            if text.contains('\n') {
+                return true;
+            }
+            n += 1;
+        }
+        true
+    }
+
+    /// The kind of the `n`th token from here, not counting trivia.
+    pub(crate) fn nth_kind(&self, n: usize) -> Option<TokenKind> {
+        self.tokens[self.cursor.min(self.tokens.len())..]
+            .iter()
+            .map(|Token { kind, .. }| *kind)
+            .filter(|kind| !kind.is_trivia())
+            .nth(n)
+    }
+
+    /// Whether the next token is the first on its line.
+    pub(crate) fn at_line_start(&self) -> bool {
+        let next = self.cursor + self.skip_trivia();
+        self.tokens[..next]
+            .iter()
+            .rev()
+            .take_while(|Token { kind, .. }| kind.is_trivia())
+            .any(|Token { text, .. }| text.contains('\n'))
+            || self.tokens[..next].iter().all(|Token { kind, .. }| kind.is_trivia())
+    }
+
+    /// How far the line holding the next token is indented.
+    pub(crate) fn line_indent(&self) -> usize {
+        let next = self.cursor + self.skip_trivia();
+        for token in self.tokens[..next].iter().rev() {
+            if let Some((_, after)) = token.text.rsplit_once('\n') {
+                return if token.kind == TokenKind::Whitespace { after.len() } else { 0 };
+            }
+        }
+
+        match self.tokens.first() {
+            Some(Token { kind: TokenKind::Whitespace, text, .. }) if next > 0 => text.len(),
+            _ => 0,
+        }
+    }
+
+    /// Whether the next token is a `{` without a matching `}`.
+    pub(crate) fn at_unclosed_brace(&self) -> bool {
+        self.unclosed.binary_search(&(self.cursor + self.skip_trivia())).is_ok()
+    }
+
     fn eat_trivia(&mut self) {
         while self.at_trivia() {
             self.cursor += 1;
         }
     }
 
-    fn skip_trivia(&mut self) -> usize {
+    fn skip_trivia(&self) -> usize {
         let mut n: usize = 0;
         while self.tokens.get(self.cursor + n)
             .map(|Token { kind, .. }| *kind)
@@ -3,7 +3,7 @@ use num_derive::{FromPrimitive, ToPrimitive};
 use num_traits::{FromPrimitive, ToPrimitive};
 
 /// TL;DR: Lexer tokens + high-level ast nodes (fields, records, etc)
-#[derive(Debug, Copy, Clone, PartialEq, FromPrimitive, ToPrimitive)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, FromPrimitive, ToPrimitive)]
 pub enum SyntaxKind {
     // Token-like Types
     Whitespace,
@@ -11,12 +11,12 @@ pub enum SyntaxKind {
 
     // Literals
     Literal,
+    /// A decimal integer, like `42`.
     Number,
     BinaryNumber,
     OctalNumber,
     HexNumber,
     AddressLiteral,
-    IntLiteral,
     RandIntLiteral,
     FloatLiteral,
     RandFloatLiteral,
@@ -187,15 +187,14 @@ impl From<TokenKind> for SyntaxKind {
             TokenKind::Whitespace   => Self::Whitespace,
             TokenKind::Ident        => Self::Ident,
 
-            // I'll likely need more SyntaxKinds for these.
             TokenKind::BinaryLiteral    => Self::BinaryNumber,
             TokenKind::OctalLiteral     => Self::OctalNumber,
             TokenKind::HexLiteral       => Self::HexNumber,
             TokenKind::AddressLiteral   => Self::AddressLiteral,
             TokenKind::IntLiteral       => Self::Number,
-            TokenKind::RandIntLiteral   => Self::Number,
-            TokenKind::FloatLiteral     => Self::Number,
-            TokenKind::RandFloatLiteral => Self::Number,
+            TokenKind::RandIntLiteral   => Self::RandIntLiteral,
+            TokenKind::FloatLiteral     => Self::FloatLiteral,
+            TokenKind::RandFloatLiteral => Self::RandFloatLiteral,
 
             // Single-character tokens
             TokenKind::String       => Self::String,
@@ -316,4 +315,34 @@ impl rowan::Language for VADA {
     }
 }
 
+/// Points at a node by its kind and range, without keeping the tree alive.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SyntaxNodePtr {
+    kind: SyntaxKind,
+    range: rowan::TextRange,
+}
+
+impl SyntaxNodePtr {
+    pub fn new(node: &SyntaxNode) -> Self {
+        Self {
+            kind: node.kind(),
+            range: node.text_range(),
+        }
+    }
+
+    pub fn kind(&self) -> SyntaxKind {
+        self.kind
+    }
+
+    pub fn range(&self) -> rowan::TextRange {
+        self.range
+    }
+
+    /// Finds the node again, given the root of the tree it was taken from.
+    pub fn to_node(&self, root: &SyntaxNode) -> Option<SyntaxNode> {
+        root.descendants()
+            .find(|node| node.kind() == self.kind && node.text_range() == self.range)
+    }
+}
+
 // Should I setup some tests here?
\ No newline at end of file
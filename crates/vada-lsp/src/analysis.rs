@@ -0,0 +1,431 @@
+/*
+TL;DR: What the editor asks about a module, answered in byte offsets and
+`TextRange`s. Nothing in here knows about the protocol, `convert` turns
+the answers into its types.
+
+    diagnostics   parse, validation, lowering, resolution and accessor errors
+    symbols       records, schemas, sections and accessors, nested like the file
+    definition    where the reference or accessor under the cursor leads
+
+Modules don't keep their syntax trees, so the ones that need it are parsed
+again. Lowering is deterministic, so the ranges line up with the HIR's.
+*/
+
+use ast::{Member, Record, Stmt};
+use diagnostics::Diagnostic;
+use hir::{Database, HirExpr, HirRecord, HirStmt, Resolved};
+use smol_str::SmolStr;
+use text_size::{TextRange, TextSize};
+use workspace::{Import, ModuleId, Workspace};
+
+/// A place in a module of the workspace.
+pub(crate) type Target = (ModuleId, TextRange);
+
+pub(crate) fn diagnostics(ws: &Workspace, id: ModuleId) -> Vec<Diagnostic> {
+    let module = ws.module(id);
+    let mut diagnostics = module.parse.errors().iter().map(Diagnostic::from).collect::<Vec<Diagnostic>>();
+    diagnostics.extend(ast::validation::validate(&module.parse.syntax()).iter().map(Diagnostic::from));
+    diagnostics.extend(module.db.diagnostics().iter().map(Diagnostic::from));
+    diagnostics.extend(
+        hir::resolve(&module.db, &module.stmts)
+            .diagnostics
+            .iter()
+            .map(Diagnostic::from),
+    );
+
+    // Lone files have no workspace root to resolve accessors against.
+    if ws.root().as_os_str().is_empty() {
+        return diagnostics;
+    }
+
+    for accessor in accessors(&ast::Root::cast(module.parse.syntax()).unwrap()) {
+        if let Err(error) = ws.resolve(id, &source(&accessor)) {
+            diagnostics.push(
+                Diagnostic::error(error.to_string())
+                    .with_primary(accessor.text_range(), "doesn't lead anywhere"),
+            );
+        }
+    }
+    for error in ws.check() {
+        let involved = matches!(&error, workspace::WorkspaceError::Cycle(modules) if modules.contains(&module.path));
+        if involved {
+            diagnostics.push(Diagnostic::error(error.to_string()));
+        }
+    }
+
+    diagnostics
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SymbolKind {
+    Record,
+    Schema,
+    Section,
+    Accessor,
+}
+
+#[derive(Debug, PartialEq)]
+pub(crate) struct Symbol {
+    pub(crate) name: SmolStr,
+    pub(crate) kind: SymbolKind,
+    /// The whole definition.
+    pub(crate) range: TextRange,
+    /// Just the name, inside `range`.
+    pub(crate) name_range: TextRange,
+    pub(crate) children: Vec<Symbol>,
+}
+
+pub(crate) fn symbols(text: &str) -> Vec<Symbol> {
+    let root = ast::Root::cast(parser::parse(text).syntax()).unwrap();
+    root.stmts()
+        .filter_map(|stmt| match stmt {
+            Stmt::Record(record) => record_symbol(&record),
+            Stmt::Section(section) => section_symbol(&section),
+            Stmt::Accessor(accessor) => {
+                let name = accessor.name()?;
+                Some(Symbol {
+                    name: format!("#{}", name.text()).into(),
+                    kind: SymbolKind::Accessor,
+                    range: accessor.text_range(),
+                    name_range: name.text_range(),
+                    children: Vec::new(),
+                })
+            }
+            _ => None,
+        })
+        .collect()
+}
+
+fn record_symbol(record: &Record) -> Option<Symbol> {
+    let name = record.name_token()?;
+    let (schema, children) = match record {
+        Record::Mono(record) => (record.is_schema(), Vec::new()),
+        Record::Poly(record) => (record.is_schema(), member_symbols(record.members())),
+    };
+
+    Some(Symbol {
+        name: if schema {
+            format!("${}", name.text()).into()
+        } else {
+            name.text().into()
+        },
+        kind: if schema { SymbolKind::Schema } else { SymbolKind::Record },
+        range: record.text_range(),
+        name_range: name.text_range(),
+        children,
+    })
+}
+
+fn section_symbol(section: &ast::Section) -> Option<Symbol> {
+    let name = section.name_token()?;
+    Some(Symbol {
+        name: format!("#{}", name.text()).into(),
+        kind: SymbolKind::Section,
+        range: section.text_range(),
+        name_range: name.text_range(),
+        children: member_symbols(section.members()),
+    })
+}
+
+fn member_symbols(members: Vec<Member>) -> Vec<Symbol> {
+    members
+        .iter()
+        .filter_map(|member| match member {
+            Member::Record(record) => record_symbol(record),
+            Member::Section(section) => section_symbol(section),
+        })
+        .collect()
+}
+
+/// Where the accessor or reference at `offset` leads. Accessors can fan out
+/// to several modules, so there can be more than one place.
+pub(crate) fn definition(ws: &Workspace, id: ModuleId, offset: TextSize) -> Vec<Target> {
+    let module = ws.module(id);
+    let root = ast::Root::cast(module.parse.syntax()).unwrap();
+
+    if let Some(accessor) = accessors(&root)
+        .into_iter()
+        .find(|accessor| accessor.text_range().contains_inclusive(offset))
+    {
+        return match ws.resolve(id, &source(&accessor)) {
+            Ok(import) => targets(ws, &import),
+            Err(_) => Vec::new(),
+        };
+    }
+
+    let mut refs = Vec::new();
+    for stmt in &module.stmts {
+        match stmt {
+            HirStmt::Record(record) => record_refs(&module.db, record, &mut refs),
+            HirStmt::Field { value, .. } | HirStmt::Expr(value) => expr_refs(&module.db, value, &mut refs),
+            HirStmt::Accessor { .. } => {}
+        }
+    }
+    let (var, fields, range) = match refs
+        .into_iter()
+        .filter(|(_, _, range)| range.contains_inclusive(offset))
+        .min_by_key(|(_, _, range)| range.len())
+    {
+        Some(found) => found,
+        None => return Vec::new(),
+    };
+
+    let resolution = hir::resolve(&module.db, &module.stmts);
+    match resolution.at(range) {
+        Some(Resolved::Definition(def)) => module
+            .db
+            .source_map()
+            .record(resolution.defs[def].record)
+            .map(|ptr| (id, trim(&module.text, ptr.range())))
+            .into_iter()
+            .collect(),
+        // Names that come from another file are followed through the
+        // accessor that brought them in.
+        Some(Resolved::External) => ws
+            .imports(id)
+            .into_iter()
+            .find(|(name, _)| name == var)
+            .and_then(|(_, import)| import.ok())
+            .map(|mut import| {
+                import.fields.extend(fields.iter().cloned());
+                targets(ws, &import)
+            })
+            .unwrap_or_default(),
+        None => Vec::new(),
+    }
+}
+
+/// The records an import leads to, or the modules themselves if it stops at
+/// a file.
+fn targets(ws: &Workspace, import: &Import) -> Vec<Target> {
+    let mut targets = Vec::new();
+    for id in &import.modules {
+        let module = ws.module(*id);
+        if import.fields.is_empty() {
+            targets.push((*id, TextRange::empty(0.into())));
+            continue;
+        }
+
+        let one = Import {
+            modules: vec![*id],
+            fields: import.fields.clone(),
+        };
+        for record in ws.records(&one) {
+            let record = match record {
+                HirRecord::Mono { id, .. } | HirRecord::Poly { id, .. } | HirRecord::Section { id, .. } => *id,
+                HirRecord::Missing => continue,
+            };
+            if let Some(ptr) = module.db.source_map().record(record) {
+                targets.push((*id, trim(&module.text, ptr.range())));
+            }
+        }
+    }
+    targets
+}
+
+fn accessors(root: &ast::Root) -> Vec<ast::Accessor> {
+    root.stmts()
+        .filter_map(|stmt| match stmt {
+            Stmt::Accessor(accessor) => Some(accessor),
+            _ => None,
+        })
+        .collect()
+}
+
+/// An accessor's source the way `Workspace::resolve` takes it, as it's
+/// lowered into `HirStmt::Accessor`.
+fn source(accessor: &ast::Accessor) -> Vec<SmolStr> {
+    accessor.source().iter().map(|token| token.text().into()).collect()
+}
+
+type Ref<'a> = (&'a SmolStr, &'a [SmolStr], TextRange);
+
+fn record_refs<'a>(db: &'a Database, record: &'a HirRecord, refs: &mut Vec<Ref<'a>>) {
+    match record {
+        HirRecord::Missing => {}
+        HirRecord::Mono { value, constraint, .. } => {
+            expr_refs(db, value, refs);
+            if let Some(constraint) = constraint {
+                expr_refs(db, constraint, refs);
+            }
+        }
+        HirRecord::Poly { value, constraint, .. } => {
+            for record in value {
+                record_refs(db, record, refs);
+            }
+            if let Some(constraint) = constraint {
+                expr_refs(db, constraint, refs);
+            }
+        }
+        HirRecord::Section { value, .. } => {
+            for record in value {
+                record_refs(db, record, refs);
+            }
+        }
+    }
+}
+
+fn expr_refs<'a>(db: &'a Database, expr: &'a HirExpr, refs: &mut Vec<Ref<'a>>) {
+    match expr {
+        HirExpr::Binary { lhs, rhs, .. } => {
+            expr_refs(db, &db[*lhs], refs);
+            expr_refs(db, &db[*rhs], refs);
+        }
+        HirExpr::Unary { expr, .. } | HirExpr::Constraint { expr, .. } => expr_refs(db, &db[*expr], refs),
+        HirExpr::Range { start, end, .. } => {
+            for bound in start.iter().chain(end) {
+                expr_refs(db, &db[*bound], refs);
+            }
+        }
+        HirExpr::Ref { var, fields, range } => refs.push((var, fields, *range)),
+        HirExpr::Struct { fields } => {
+            for record in fields {
+                record_refs(db, record, refs);
+            }
+        }
+        HirExpr::List { items } => {
+            for item in items {
+                expr_refs(db, item, refs);
+            }
+        }
+        HirExpr::Type { args, .. } | HirExpr::Transform { args, .. } => {
+            for arg in args {
+                expr_refs(db, &db[*arg], refs);
+            }
+        }
+        HirExpr::Missing
+        | HirExpr::Int { .. }
+        | HirExpr::Float { .. }
+        | HirExpr::Address { .. }
+        | HirExpr::RandInt { .. }
+        | HirExpr::RandFloat { .. }
+        | HirExpr::String { .. }
+        | HirExpr::Scope { .. }
+        | HirExpr::Enum(_) => {}
+    }
+}
+
+/// Record nodes own the whitespace after them, which a jump shouldn't select.
+fn trim(text: &str, range: TextRange) -> TextRange {
+    let len = text[range].trim_end().len();
+    TextRange::at(range.start(), TextSize::from(len as u32))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn workspace(files: &[(&str, &str)]) -> Workspace {
+        Workspace::from_sources(
+            files
+                .iter()
+                .map(|(file, text)| (PathBuf::from(file), text.to_string())),
+        )
+    }
+
+    fn module(ws: &Workspace, file: &str) -> ModuleId {
+        ws.modules()
+            .find(|(_, module)| module.file == std::path::Path::new(file))
+            .map(|(id, _)| id)
+            .unwrap()
+    }
+
+    /// The text at each place `definition` leads to from the `$0` marker.
+    fn check_definition(files: &[(&str, &str)], from: &str) -> Vec<(String, String)> {
+        let (_, marked) = files.iter().find(|(file, _)| *file == from).unwrap();
+        let offset = marked.find("$0").unwrap() as u32;
+        let files = files
+            .iter()
+            .map(|(file, text)| (*file, text.replace("$0", "")))
+            .collect::<Vec<(&str, String)>>();
+        let files = files.iter().map(|(file, text)| (*file, text.as_str())).collect::<Vec<_>>();
+
+        let ws = workspace(&files);
+        definition(&ws, module(&ws, from), offset.into())
+            .into_iter()
+            .map(|(id, range)| {
+                let module = ws.module(id);
+                (module.file.display().to_string(), module.text[range].to_string())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn diagnostics_from_every_stage() {
+        let ws = workspace(&[("a.vada", "a: 99999999999999999999999\nb: c\nd: {")]);
+        let messages = diagnostics(&ws, module(&ws, "a.vada"))
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect::<Vec<String>>();
+
+        assert!(messages.iter().any(|message| message.starts_with("expected")));
+        assert!(messages.iter().any(|message| message.starts_with("number literal is larger")));
+        assert!(messages.contains(&"unresolved name `c`".to_string()));
+    }
+
+    #[test]
+    fn nested_symbols() {
+        let text = "#z: @global::zalgo\n$unit: { hp: !int }\ngoblin: {\n  hp: 5\n  --- #loot:\n  gold: 2\n}\n";
+        let outline = |symbols: &[Symbol]| {
+            symbols
+                .iter()
+                .map(|symbol| {
+                    let children = symbol.children.iter().map(|c| c.name.to_string()).collect::<Vec<_>>();
+                    (symbol.name.to_string(), symbol.kind, children)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let symbols = symbols(text);
+        assert_eq!(
+            outline(&symbols),
+            vec![
+                ("#z".to_string(), SymbolKind::Accessor, vec![]),
+                ("$unit".to_string(), SymbolKind::Schema, vec!["hp".to_string()]),
+                ("goblin".to_string(), SymbolKind::Record, vec!["hp".to_string(), "#loot".to_string()]),
+            ]
+        );
+        assert_eq!(&text[symbols[2].range], "goblin: {\n  hp: 5\n  --- #loot:\n  gold: 2\n}");
+        assert_eq!(&text[symbols[2].name_range], "goblin");
+        assert_eq!(outline(&symbols[2].children[1..]), vec![("#loot".to_string(), SymbolKind::Section, vec!["gold".to_string()])]);
+    }
+
+    #[test]
+    fn definition_of_local_reference() {
+        assert_eq!(
+            check_definition(&[("a.vada", "hp: 5\ngoblin: {\n  max: 9\n  cur: m$0ax\n}\nb: goblin.max\n")], "a.vada"),
+            vec![("a.vada".to_string(), "max: 9".to_string())]
+        );
+        assert_eq!(
+            check_definition(&[("a.vada", "hp: 5\ngoblin: {\n  max: 9\n}\nb: goblin.m$0ax\n")], "a.vada"),
+            vec![("a.vada".to_string(), "max: 9".to_string())]
+        );
+    }
+
+    #[test]
+    fn definition_through_accessor() {
+        let files = [
+            ("zalgo.vada", "tools: {\n  hammer: 3\n  axe: 4\n}\n"),
+            ("units/goblin.vada", "#z: @global::zalgo\nweapon: z.tools.a$0xe\n"),
+        ];
+        assert_eq!(
+            check_definition(&files, "units/goblin.vada"),
+            vec![("zalgo.vada".to_string(), "axe: 4".to_string())]
+        );
+    }
+
+    #[test]
+    fn definition_of_accessor_source() {
+        let files = [
+            ("zalgo.vada", "tools: {\n  axe: 4\n}\n"),
+            ("units/goblin.vada", "#t: @global::zalgo::to$0ols\n"),
+            ("units/orc.vada", "#z: @global::zal$0go\n"),
+        ];
+        assert_eq!(
+            check_definition(&files, "units/goblin.vada"),
+            vec![("zalgo.vada".to_string(), "tools: {\n  axe: 4\n}".to_string())]
+        );
+        assert_eq!(check_definition(&files, "units/orc.vada"), vec![("zalgo.vada".to_string(), String::new())]);
+    }
+}
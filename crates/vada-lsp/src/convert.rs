@@ -0,0 +1,126 @@
+/*
+TL;DR: Our byte offsets and diagnostics, as the protocol wants them.
+
+    offset 8 in "a: 1\nhé 5"   <->   line 1, character 2
+
+Positions count UTF-16 code units, not bytes or characters, so `é` is one
+and an emoji is two.
+*/
+
+use crate::analysis::{Symbol, SymbolKind};
+use diagnostics::{LineCol, LineIndex, Severity};
+use lsp_types::{DiagnosticRelatedInformation, DiagnosticSeverity, DocumentSymbol, Location, Position, Url};
+use text_size::{TextRange, TextSize};
+
+pub(crate) fn position(index: &LineIndex, offset: TextSize) -> Position {
+    let LineCol { line, col } = index.line_col(offset);
+    let character = index
+        .line(line)
+        .chars()
+        .take(col as usize)
+        .map(char::len_utf16)
+        .sum::<usize>();
+
+    Position::new(line, character as u32)
+}
+
+pub(crate) fn offset(index: &LineIndex, position: Position) -> TextSize {
+    let mut units = 0;
+    let col = index
+        .line(position.line)
+        .chars()
+        .take_while(|c| {
+            units += c.len_utf16() as u32;
+            units <= position.character
+        })
+        .count();
+
+    index.offset(LineCol {
+        line: position.line,
+        col: col as u32,
+    })
+}
+
+pub(crate) fn range(index: &LineIndex, range: TextRange) -> lsp_types::Range {
+    lsp_types::Range::new(position(index, range.start()), position(index, range.end()))
+}
+
+/// The primary label becomes the diagnostic's range, secondary labels become
+/// related information, and notes are added to the message.
+pub(crate) fn diagnostic(
+    index: &LineIndex,
+    uri: &Url,
+    diagnostic: &diagnostics::Diagnostic,
+) -> lsp_types::Diagnostic {
+    let primary = diagnostic
+        .primary()
+        .or_else(|| diagnostic.labels.first())
+        .map_or_else(TextRange::default, |label| label.range);
+
+    let mut message = diagnostic.message.clone();
+    for note in &diagnostic.notes {
+        message += &format!("\nnote: {}", note);
+    }
+
+    let related = diagnostic
+        .labels
+        .iter()
+        .filter(|label| !label.primary)
+        .map(|label| DiagnosticRelatedInformation {
+            location: Location::new(uri.clone(), range(index, label.range)),
+            message: label.message.clone(),
+        })
+        .collect::<Vec<DiagnosticRelatedInformation>>();
+
+    lsp_types::Diagnostic {
+        range: range(index, primary),
+        severity: Some(match diagnostic.severity {
+            Severity::Error => DiagnosticSeverity::ERROR,
+            Severity::Warning => DiagnosticSeverity::WARNING,
+        }),
+        source: Some("vada".to_string()),
+        message,
+        related_information: (!related.is_empty()).then_some(related),
+        ..lsp_types::Diagnostic::default()
+    }
+}
+
+pub(crate) fn symbol(index: &LineIndex, symbol: &Symbol) -> DocumentSymbol {
+    #[allow(deprecated)]
+    DocumentSymbol {
+        name: symbol.name.to_string(),
+        detail: None,
+        kind: match symbol.kind {
+            SymbolKind::Record => lsp_types::SymbolKind::FIELD,
+            SymbolKind::Schema => lsp_types::SymbolKind::INTERFACE,
+            SymbolKind::Section => lsp_types::SymbolKind::NAMESPACE,
+            SymbolKind::Accessor => lsp_types::SymbolKind::MODULE,
+        },
+        tags: None,
+        deprecated: None,
+        range: range(index, symbol.range),
+        selection_range: range(index, symbol.name_range),
+        children: Some(symbol.children.iter().map(|child| self::symbol(index, child)).collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn positions_count_utf16() {
+        let index = LineIndex::new("a: 1\nhé: \"🦀\" 5\n");
+        let roundtrip = |offset: u32| {
+            let position = position(&index, offset.into());
+            (position.line, position.character, u32::from(self::offset(&index, position)))
+        };
+
+        assert_eq!(roundtrip(0), (0, 0, 0));
+        assert_eq!(roundtrip(8), (1, 2, 8));
+        assert_eq!(roundtrip(11), (1, 5, 11));
+        assert_eq!(roundtrip(15), (1, 7, 15));
+        assert_eq!(u32::from(offset(&index, Position::new(1, 6))), 11);
+        assert_eq!(u32::from(offset(&index, Position::new(5, 0))), 19);
+    }
+}
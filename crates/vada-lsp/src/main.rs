@@ -0,0 +1,81 @@
+/*
+TL;DR: `vada-lsp`, the language server, spoken over stdin and stdout.
+
+    textDocument/publishDiagnostics   every error `vada check` would report,
+                                      as the file is typed
+    textDocument/documentSymbol       records, schemas, sections and accessors
+    textDocument/definition           where a reference or accessor leads
+
+The folder the editor opens is loaded as a workspace, so accessors between
+its files resolve, with open files taken from the editor instead of disk.
+Files outside it are checked on their own. Documents are synced whole and
+everything is rebuilt on each change.
+*/
+
+mod analysis;
+mod convert;
+mod server;
+
+use lsp_server::{Connection, Message};
+use lsp_types::{InitializeParams, OneOf, ServerCapabilities, TextDocumentSyncCapability, TextDocumentSyncKind};
+use server::Server;
+use std::error::Error;
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::from(1)
+        }
+    }
+}
+
+fn run() -> Result<(), Box<dyn Error + Send + Sync>> {
+    let (connection, io_threads) = Connection::stdio();
+
+    let capabilities = serde_json::to_value(ServerCapabilities {
+        text_document_sync: Some(TextDocumentSyncCapability::Kind(TextDocumentSyncKind::FULL)),
+        document_symbol_provider: Some(OneOf::Left(true)),
+        definition_provider: Some(OneOf::Left(true)),
+        ..ServerCapabilities::default()
+    })?;
+    let params = serde_json::from_value::<InitializeParams>(connection.initialize(capabilities)?)?;
+
+    let mut server = Server::new(root(&params));
+    for message in &connection.receiver {
+        match message {
+            Message::Request(request) => {
+                if connection.handle_shutdown(&request)? {
+                    break;
+                }
+                connection.sender.send(server.request(request).into())?;
+            }
+            Message::Notification(notification) => {
+                for published in server.notify(notification) {
+                    connection.sender.send(published.into())?;
+                }
+            }
+            Message::Response(_) => {}
+        }
+    }
+
+    drop(connection);
+    io_threads.join()?;
+    Ok(())
+}
+
+/// The first folder the editor opened, if it opened one.
+fn root(params: &InitializeParams) -> Option<PathBuf> {
+    #[allow(deprecated)]
+    let uri = params
+        .workspace_folders
+        .as_ref()
+        .and_then(|folders| folders.first())
+        .map(|folder| &folder.uri)
+        .or(params.root_uri.as_ref())?;
+
+    uri.to_file_path().ok()
+}
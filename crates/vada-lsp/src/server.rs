@@ -0,0 +1,266 @@
+use crate::{analysis, convert};
+use diagnostics::LineIndex;
+use lsp_server::{ErrorCode, Notification, Request, RequestId, Response};
+use lsp_types::notification::{
+    DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as _, PublishDiagnostics,
+};
+use lsp_types::request::{DocumentSymbolRequest, GotoDefinition, Request as _};
+use lsp_types::{
+    DocumentSymbolParams, DocumentSymbolResponse, GotoDefinitionParams, GotoDefinitionResponse, Location,
+    PublishDiagnosticsParams, Url,
+};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use workspace::{ModuleId, Workspace};
+
+/// The open documents, and the workspaces built from them and the disk.
+pub(crate) struct Server {
+    root: Option<PathBuf>,
+    open: HashMap<PathBuf, String>,
+    workspaces: Vec<Workspace>,
+}
+
+impl Server {
+    pub(crate) fn new(root: Option<PathBuf>) -> Self {
+        let mut server = Self {
+            root,
+            open: HashMap::new(),
+            workspaces: Vec::new(),
+        };
+        server.reload();
+        server
+    }
+
+    pub(crate) fn request(&self, request: Request) -> Response {
+        match request.method.as_str() {
+            DocumentSymbolRequest::METHOD => {
+                self.respond(request, |server, params: DocumentSymbolParams| {
+                    server.symbols(&params.text_document.uri).map(DocumentSymbolResponse::Nested)
+                })
+            }
+            GotoDefinition::METHOD => self.respond(request, |server, params: GotoDefinitionParams| {
+                let position = params.text_document_position_params;
+                server
+                    .definition(&position.text_document.uri, position.position)
+                    .map(GotoDefinitionResponse::Array)
+            }),
+            _ => Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("unsupported request `{}`", request.method),
+            ),
+        }
+    }
+
+    /// Handles a notification, returning the diagnostics to publish because
+    /// of it.
+    pub(crate) fn notify(&mut self, notification: Notification) -> Vec<Notification> {
+        let closed = match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => {
+                let params = match serde_json::from_value::<lsp_types::DidOpenTextDocumentParams>(notification.params) {
+                    Ok(params) => params,
+                    Err(_) => return Vec::new(),
+                };
+                if let Ok(file) = params.text_document.uri.to_file_path() {
+                    self.open.insert(file, params.text_document.text);
+                }
+                None
+            }
+            // Documents are synced whole, so the last change has all of it.
+            DidChangeTextDocument::METHOD => {
+                let params = match serde_json::from_value::<lsp_types::DidChangeTextDocumentParams>(notification.params) {
+                    Ok(params) => params,
+                    Err(_) => return Vec::new(),
+                };
+                match (params.text_document.uri.to_file_path(), params.content_changes.into_iter().last()) {
+                    (Ok(file), Some(change)) => {
+                        self.open.insert(file, change.text);
+                    }
+                    _ => return Vec::new(),
+                }
+                None
+            }
+            DidCloseTextDocument::METHOD => {
+                let params = match serde_json::from_value::<lsp_types::DidCloseTextDocumentParams>(notification.params) {
+                    Ok(params) => params,
+                    Err(_) => return Vec::new(),
+                };
+                if let Ok(file) = params.text_document.uri.to_file_path() {
+                    self.open.remove(&file);
+                }
+                Some(params.text_document.uri)
+            }
+            _ => return Vec::new(),
+        };
+
+        self.reload();
+
+        // A closed document's diagnostics are cleared, since nothing will
+        // update them until it's opened again.
+        let mut published = self.publish();
+        if let Some(uri) = closed {
+            published.push(publish(uri, Vec::new()));
+        }
+        published
+    }
+
+    /// Rebuilds every workspace. Open files under the root belong to its
+    /// workspace, the rest are checked on their own.
+    fn reload(&mut self) {
+        self.workspaces.clear();
+
+        let mut lone = self.open.clone();
+        if let Some(root) = &self.root {
+            match Workspace::load_with(root, &self.open) {
+                Ok(ws) => {
+                    lone.retain(|file, _| !file.starts_with(root));
+                    self.workspaces.push(ws);
+                }
+                Err(error) => eprintln!("error: {}", error),
+            }
+        }
+
+        let mut lone = lone.into_iter().collect::<Vec<(PathBuf, String)>>();
+        lone.sort();
+        for (file, text) in lone {
+            self.workspaces.push(Workspace::from_sources([(file, text)]));
+        }
+    }
+
+    /// Diagnostics for every open document. Any of them can change when one
+    /// does, through accessors.
+    fn publish(&self) -> Vec<Notification> {
+        let mut files = self.open.keys().collect::<Vec<&PathBuf>>();
+        files.sort();
+
+        files
+            .into_iter()
+            .filter_map(|file| {
+                let (ws, id) = self.find(file)?;
+                let uri = Url::from_file_path(file).ok()?;
+                let module = ws.module(id);
+                let index = LineIndex::new(&module.text);
+                let diagnostics = analysis::diagnostics(ws, id)
+                    .iter()
+                    .map(|diagnostic| convert::diagnostic(&index, &uri, diagnostic))
+                    .collect();
+                Some(publish(uri, diagnostics))
+            })
+            .collect()
+    }
+
+    fn symbols(&self, uri: &Url) -> Option<Vec<lsp_types::DocumentSymbol>> {
+        let (ws, id) = self.find(&uri.to_file_path().ok()?)?;
+        let text = &ws.module(id).text;
+        let index = LineIndex::new(text);
+
+        Some(
+            analysis::symbols(text)
+                .iter()
+                .map(|symbol| convert::symbol(&index, symbol))
+                .collect(),
+        )
+    }
+
+    fn definition(&self, uri: &Url, position: lsp_types::Position) -> Option<Vec<Location>> {
+        let (ws, id) = self.find(&uri.to_file_path().ok()?)?;
+        let offset = convert::offset(&LineIndex::new(&ws.module(id).text), position);
+
+        Some(
+            analysis::definition(ws, id, offset)
+                .into_iter()
+                .filter_map(|(id, range)| {
+                    let module = ws.module(id);
+                    let uri = Url::from_file_path(&module.file).ok()?;
+                    Some(Location::new(uri, convert::range(&LineIndex::new(&module.text), range)))
+                })
+                .collect(),
+        )
+    }
+
+    fn find(&self, file: &Path) -> Option<(&Workspace, ModuleId)> {
+        self.workspaces.iter().find_map(|ws| {
+            ws.modules()
+                .find(|(_, module)| module.file == file)
+                .map(|(id, _)| (ws, id))
+        })
+    }
+
+    /// Answers a request with `handler`, or with an error if its params don't
+    /// parse. Documents we don't know about get a `null` result.
+    fn respond<P, R>(&self, request: Request, handler: impl FnOnce(&Self, P) -> Option<R>) -> Response
+    where
+        P: serde::de::DeserializeOwned,
+        R: serde::Serialize,
+    {
+        let id: RequestId = request.id;
+        match serde_json::from_value::<P>(request.params) {
+            Ok(params) => Response::new_ok(id, handler(self, params)),
+            Err(error) => Response::new_err(id, ErrorCode::InvalidParams as i32, error.to_string()),
+        }
+    }
+}
+
+fn publish(uri: Url, diagnostics: Vec<lsp_types::Diagnostic>) -> Notification {
+    Notification::new(
+        PublishDiagnostics::METHOD.to_string(),
+        PublishDiagnosticsParams::new(uri, diagnostics, None),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn open(server: &mut Server, uri: &str, text: &str) -> Vec<Notification> {
+        server.notify(Notification::new(
+            DidOpenTextDocument::METHOD.to_string(),
+            json!({ "textDocument": { "uri": uri, "languageId": "vada", "version": 0, "text": text } }),
+        ))
+    }
+
+    #[test]
+    fn publishes_diagnostics_on_open_and_change() {
+        let mut server = Server::new(None);
+        let published = open(&mut server, "file:///units/goblin.vada", "hp: 5\nmax: hq\n");
+
+        assert_eq!(published.len(), 1);
+        let params = serde_json::from_value::<PublishDiagnosticsParams>(published[0].params.clone()).unwrap();
+        assert_eq!(params.diagnostics.len(), 1);
+        assert_eq!(params.diagnostics[0].message, "unresolved name `hq`\nnote: names are looked up in the enclosing structs, then the file's records and accessors");
+        assert_eq!(params.diagnostics[0].range, lsp_types::Range::new(lsp_types::Position::new(1, 5), lsp_types::Position::new(1, 7)));
+
+        let published = server.notify(Notification::new(
+            DidChangeTextDocument::METHOD.to_string(),
+            json!({
+                "textDocument": { "uri": "file:///units/goblin.vada", "version": 1 },
+                "contentChanges": [{ "text": "hp: 5\nmax: hp\n" }],
+            }),
+        ));
+        let params = serde_json::from_value::<PublishDiagnosticsParams>(published[0].params.clone()).unwrap();
+        assert!(params.diagnostics.is_empty());
+    }
+
+    #[test]
+    fn answers_definition_requests() {
+        let mut server = Server::new(None);
+        open(&mut server, "file:///goblin.vada", "hp: 5\nmax: hp\n");
+
+        let response = server.request(Request::new(
+            RequestId::from(1),
+            GotoDefinition::METHOD.to_string(),
+            json!({ "textDocument": { "uri": "file:///goblin.vada" }, "position": { "line": 1, "character": 6 } }),
+        ));
+        assert_eq!(
+            response.result,
+            Some(json!([{
+                "uri": "file:///goblin.vada",
+                "range": { "start": { "line": 0, "character": 0 }, "end": { "line": 0, "character": 5 } },
+            }]))
+        );
+
+        let response = server.request(Request::new(RequestId::from(2), "vada/unknown".to_string(), json!(null)));
+        assert_eq!(response.error.unwrap().code, ErrorCode::MethodNotFound as i32);
+    }
+}
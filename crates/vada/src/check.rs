@@ -0,0 +1,173 @@
+use crate::{Format, Outcome};
+use diagnostics::Diagnostic;
+use eval::Value;
+use smol_str::SmolStr;
+use workspace::{Import, Module, ModuleId, Workspace, WorkspaceError};
+
+pub(crate) fn check(workspaces: &[Workspace], format: Format, seed: u64) -> Outcome {
+    let mut outcome = Outcome::Ok;
+    let mut report = |file: &str, text: Option<&str>, diagnostic: Diagnostic| {
+        print(&diagnostic, file, text, format);
+        outcome = Outcome::Failed;
+    };
+
+    for ws in workspaces {
+        for (id, module) in ws.modules() {
+            for diagnostic in problems(ws, id, seed) {
+                report(&module.file.display().to_string(), Some(&module.text), diagnostic);
+            }
+        }
+
+        // Workspace errors name the modules they're about, but not a place
+        // in them, so there's nothing to quote.
+        for error in workspace_errors(ws) {
+            report(&ws.root().display().to_string(), None, Diagnostic::error(error.to_string()));
+        }
+    }
+
+    outcome
+}
+
+/// Prints a diagnostic about `file`, quoting the lines of `text` it points
+/// at. Without the text, it's printed on its own.
+pub(crate) fn print(diagnostic: &Diagnostic, file: &str, text: Option<&str>, format: Format) {
+    match (format, text) {
+        (Format::Human, Some(text)) => eprintln!("{}", diagnostic.render(file, text)),
+        (Format::Human, None) => eprintln!("{}", diagnostic),
+        (Format::Json, text) => println!("{}", diagnostic.to_json(file, text.unwrap_or_default())),
+    }
+}
+
+/// Prints what each file evaluates to. Files with problems are reported
+/// instead, as `check` would report them.
+pub(crate) fn eval(workspaces: &[Workspace], seed: u64) -> Outcome {
+    let mut outcome = Outcome::Ok;
+    let modules = modules(workspaces).collect::<Vec<_>>();
+
+    for (ws, id, module) in &modules {
+        let problems = problems(ws, *id, seed);
+        if !problems.is_empty() {
+            for diagnostic in problems {
+                print(&diagnostic, &module.file.display().to_string(), Some(&module.text), Format::Human);
+            }
+            outcome = Outcome::Failed;
+            continue;
+        }
+
+        let value = eval::eval_seeded(&module.db, &module.stmts, seed, &imports(ws, *id, seed)).0.resolved();
+        if modules.len() > 1 {
+            println!("// {}", module.file.display());
+        }
+        println!("{}", value);
+    }
+
+    for ws in workspaces {
+        for error in workspace_errors(ws) {
+            print(&Diagnostic::error(error.to_string()), &ws.root().display().to_string(), None, Format::Human);
+            outcome = Outcome::Failed;
+        }
+    }
+
+    outcome
+}
+
+/// Accessors that lead nowhere, and cycles between modules. Lone files have
+/// no workspace root to resolve accessors against, so they have none.
+fn workspace_errors(ws: &Workspace) -> Vec<WorkspaceError> {
+    if ws.root().as_os_str().is_empty() {
+        Vec::new()
+    } else {
+        ws.check()
+    }
+}
+
+/// Every module of every workspace, with the workspace it's in.
+pub(crate) fn modules(workspaces: &[Workspace]) -> impl Iterator<Item = (&Workspace, ModuleId, &Module)> {
+    workspaces
+        .iter()
+        .flat_map(|ws| ws.modules().map(move |(id, module)| (ws, id, module)))
+}
+
+/// Everything wrong with a module, in pipeline order. Evaluation only runs
+/// once the earlier stages are clean, since their errors would otherwise
+/// show up again as `_|_`. It rolls with `seed`, since what's rolled can
+/// break constraints.
+pub(crate) fn problems(ws: &Workspace, id: ModuleId, seed: u64) -> Vec<Diagnostic> {
+    let module = ws.module(id);
+    let mut problems = module.parse.errors().iter().map(Diagnostic::from).collect::<Vec<Diagnostic>>();
+    problems.extend(ast::validation::validate(&module.parse.syntax()).iter().map(Diagnostic::from));
+    problems.extend(module.db.diagnostics().iter().map(Diagnostic::from));
+    problems.extend(
+        hir::resolve(&module.db, &module.stmts)
+            .diagnostics
+            .iter()
+            .map(Diagnostic::from),
+    );
+
+    if problems.is_empty() {
+        let value = eval::eval_seeded(&module.db, &module.stmts, seed, &imports(ws, id, seed)).0.resolved();
+        problems.extend(
+            value
+                .bottoms()
+                .into_iter()
+                .map(|(path, reason, range)| {
+                    let diagnostic = Diagnostic::error(format!("`{}` is _|_: {}", path, reason));
+                    match range {
+                        Some(range) => diagnostic.with_primary(range, "because of this"),
+                        None => diagnostic,
+                    }
+                }),
+        );
+    }
+
+    problems
+}
+
+/// What each of a module's accessors points at, for evaluating the module.
+/// The modules they lead to are evaluated first, with their own imports,
+/// and lose their ranges, which point into other files. Accessors that lead
+/// nowhere, or back to a module being evaluated, are `_|_`.
+pub(crate) fn imports(ws: &Workspace, id: ModuleId, seed: u64) -> Vec<(SmolStr, Value)> {
+    imports_into(ws, id, seed, &mut Vec::new())
+}
+
+fn imports_into(ws: &Workspace, id: ModuleId, seed: u64, active: &mut Vec<ModuleId>) -> Vec<(SmolStr, Value)> {
+    active.push(id);
+    let imports = ws
+        .imports(id)
+        .into_iter()
+        .map(|(name, import)| {
+            let value = match import {
+                Ok(import) => imported(ws, &import, seed, active),
+                Err(error) => Value::bottom(error.to_string()),
+            };
+            (name, value)
+        })
+        .collect();
+    active.pop();
+
+    imports
+}
+
+/// The records of every module an import leads to, as one struct, followed
+/// down the import's fields.
+fn imported(ws: &Workspace, import: &Import, seed: u64, active: &mut Vec<ModuleId>) -> Value {
+    let mut records = Vec::new();
+    for &id in &import.modules {
+        let module = ws.module(id);
+        if active.contains(&id) {
+            return Value::bottom(format!("`{}` imports itself through its accessors", module.path));
+        }
+
+        let imports = imports_into(ws, id, seed, active);
+        if let Value::Struct(fields) = eval::eval_seeded(&module.db, &module.stmts, seed, &imports).0 {
+            records.extend(fields);
+        }
+    }
+
+    import
+        .fields
+        .iter()
+        .try_fold(Value::Struct(records), |value, field| value.field(field).cloned())
+        .map_or_else(|| Value::bottom(format!("there's no record `{}`", import.fields.join("."))), Value::unlocated)
+}
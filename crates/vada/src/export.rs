@@ -0,0 +1,83 @@
+use crate::{check, ExportEnums, ExportFormat, Outcome};
+use diagnostics::Diagnostic;
+use eval::Value;
+use export::{Enums, Format};
+use std::fs;
+use std::path::Path;
+use workspace::{Workspace, WorkspaceError};
+
+/// Prints each file's data, one document after another. Files with errors,
+/// or with values the format can't hold, are reported instead. Every roll
+/// that went into the data is written to `provenance`, if there is one.
+pub(crate) fn export(
+    workspaces: &[Workspace],
+    format: ExportFormat,
+    enums: ExportEnums,
+    seed: u64,
+    provenance: Option<&Path>,
+) -> Result<Outcome, WorkspaceError> {
+    let mut outcome = Outcome::Ok;
+    let mut rolls = String::new();
+    let format = match format {
+        ExportFormat::Json => Format::Json,
+        ExportFormat::Yaml => Format::Yaml,
+        ExportFormat::Toml => Format::Toml,
+    };
+    let enums = match enums {
+        ExportEnums::Names => Enums::Names,
+        ExportEnums::Values => Enums::Values,
+    };
+
+    for (ws, id, module) in check::modules(workspaces) {
+        let file = module.file.display().to_string();
+        let problems = check::problems(ws, id, seed);
+        if !problems.is_empty() {
+            for diagnostic in problems {
+                eprintln!("{}", diagnostic.render(&file, &module.text));
+            }
+            outcome = Outcome::Failed;
+            continue;
+        }
+
+        let (value, made) = eval::output_seeded(&module.db, &module.stmts, seed, &check::imports(ws, id, seed));
+        for roll in made {
+            rolls.push_str(&provenance_line(&file, seed, &roll));
+            rolls.push('\n');
+        }
+
+        match export::export(&value.resolved(), format, enums) {
+            Ok(text) => print!("{}", text),
+            Err(error) => {
+                eprintln!("{}", Diagnostic::error(error.to_string()).render(&file, &module.text));
+                outcome = Outcome::Failed;
+            }
+        }
+    }
+
+    if let Some(path) = provenance {
+        fs::write(path, rolls).map_err(|error| WorkspaceError::Io {
+            path: path.to_path_buf(),
+            error,
+        })?;
+    }
+    Ok(outcome)
+}
+
+/// A roll as a line of JSON, with everything needed to make it again.
+fn provenance_line(file: &str, seed: u64, roll: &eval::Roll) -> String {
+    let value = match roll.value {
+        Value::Int(n) => serde_json::Value::from(n),
+        Value::Float(f) => serde_json::Value::from(f),
+        ref value => serde_json::Value::from(value.to_string()),
+    };
+
+    serde_json::json!({
+        "file": file,
+        "path": roll.path,
+        "index": roll.index,
+        "dice": roll.dice,
+        "seed": seed,
+        "roll": value,
+    })
+    .to_string()
+}
@@ -0,0 +1,27 @@
+use crate::Outcome;
+use std::fs;
+use workspace::{Workspace, WorkspaceError};
+
+/// Rewrites every file that isn't formatted, or with `check`, only lists them.
+pub(crate) fn fmt(workspaces: &[Workspace], check: bool) -> Result<Outcome, WorkspaceError> {
+    let mut outcome = Outcome::Ok;
+
+    for (_, module) in workspaces.iter().flat_map(Workspace::modules) {
+        let formatted = formatter::format(&module.text);
+        if formatted == module.text {
+            continue;
+        }
+
+        if check {
+            println!("{}", module.file.display());
+            outcome = Outcome::Failed;
+        } else {
+            fs::write(&module.file, formatted).map_err(|error| WorkspaceError::Io {
+                path: module.file.clone(),
+                error,
+            })?;
+        }
+    }
+
+    Ok(outcome)
+}
@@ -0,0 +1,44 @@
+use crate::Outcome;
+use diagnostics::Diagnostic;
+use import::Format;
+use std::fs;
+use std::path::PathBuf;
+use workspace::WorkspaceError;
+
+/// Prints each data file as the value it imports as, or why it can't be.
+pub(crate) fn import(files: &[PathBuf]) -> Result<Outcome, WorkspaceError> {
+    let mut outcome = Outcome::Ok;
+
+    for file in files {
+        let text = fs::read_to_string(file).map_err(|error| WorkspaceError::Io {
+            path: file.clone(),
+            error,
+        })?;
+        let name = file.display().to_string();
+
+        let format = match Format::of(file) {
+            Some(format) => format,
+            None => {
+                let diagnostic = Diagnostic::error("only .json, .yaml and .yml files can be imported");
+                eprintln!("{}", diagnostic.render(&name, &text));
+                outcome = Outcome::Failed;
+                continue;
+            }
+        };
+
+        match import::import(&text, format) {
+            Ok(imported) => {
+                if files.len() > 1 {
+                    println!("// {}", name);
+                }
+                println!("{}", imported.value);
+            }
+            Err(error) => {
+                eprintln!("{}", Diagnostic::from(&error).render(&name, &text));
+                outcome = Outcome::Failed;
+            }
+        }
+    }
+
+    Ok(outcome)
+}
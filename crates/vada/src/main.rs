@@ -1,41 +1,207 @@
-use parser::parse;
-use std::io::{self, Write};
+/*
+TL;DR: The `vada` command line.
 
-fn main() -> io::Result<()> {
-    let stdin = io::stdin();
-    let mut stdout = io::stdout();
+    vada check  [--format json] [--seed <n>] <paths>...
+                                   report every error, exit 1 if there were any
+    vada eval   [--seed <n>] <paths>...
+                                   print each file's evaluated value
+    vada export [--format json|yaml|toml] [--seed <n>] [--provenance <file>]
+                <paths>...         print each file's data, JSON by default
+    vada import <files>...         print JSON or YAML files as Vada values
+    vada validate --schema <file> --name <name> [--format json] [--seed <n>]
+                <paths>...         check every record in each file against a
+                                   schema, reporting every violation
+    vada fmt    [--check] <paths>...
+    vada dump-ast / dump-hir <paths>...
+    vada repl
 
-    let mut input = String::new();
+Paths can be files or directories. Directories are loaded as a workspace, so
+accessors between their files are checked too.
 
-    loop {
-        write!(stdout, "→ ")?;
-        stdout.flush()?;
+Errors are rendered with the source lines they point at, or as one JSON object
+per line with `--format json`.
 
-        stdin.read_line(&mut input)?;
+Random literals like `2d6` are rolled with `--seed`, 0 by default, and roll
+the same for the same seed. `--provenance` writes every roll an export made to
+a file, as one JSON object per line, so the build can be made again.
 
-        let parse = parse(&input);
-        println!("{}", parse.debug_tree());
+Exit codes: 0 on success, 1 if a file has errors, 2 if something couldn't be
+read or written.
+*/
 
-        let syntax = parse.syntax();
+mod check;
+mod export;
+mod fmt;
+mod import;
+mod repl;
+mod validate;
 
-        for error in ast::validation::validate(&syntax) {
-            println!("{}", error);
-        }
+use clap::{Parser, Subcommand, ValueEnum};
+use std::fs;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use workspace::{Workspace, WorkspaceError};
+
+#[derive(Parser)]
+#[command(name = "vada", version, about = "Checks, evaluates and formats Vada data files")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Parse, lower, resolve and evaluate files, reporting every error.
+    Check {
+        #[arg(long, value_enum, default_value_t = Format::Human)]
+        format: Format,
+        /// The seed random literals are rolled with.
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        paths: Vec<PathBuf>,
+    },
+    /// Print the evaluated value of each file.
+    Eval {
+        /// The seed random literals are rolled with.
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        paths: Vec<PathBuf>,
+    },
+    /// Print the data in each file, leaving out schemas.
+    Export {
+        #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+        format: ExportFormat,
+        /// Whether enum members are written as their names or their ints.
+        #[arg(long, value_enum, default_value_t = ExportEnums::Names)]
+        enums: ExportEnums,
+        /// The seed random literals are rolled with.
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        /// A file to write every roll to, one JSON object per line.
+        #[arg(long)]
+        provenance: Option<PathBuf>,
+        paths: Vec<PathBuf>,
+    },
+    /// Read JSON or YAML files and print them as Vada values.
+    Import { files: Vec<PathBuf> },
+    /// Check every record in Vada, JSON or YAML files against a schema.
+    Validate {
+        /// The Vada file the schema is in.
+        #[arg(long)]
+        schema: PathBuf,
+        /// The schema's name, with or without its `$`.
+        #[arg(long)]
+        name: String,
+        #[arg(long, value_enum, default_value_t = Format::Human)]
+        format: Format,
+        /// The seed random literals are rolled with.
+        #[arg(long, default_value_t = 0)]
+        seed: u64,
+        paths: Vec<PathBuf>,
+    },
+    /// Rewrite files in the canonical format.
+    Fmt {
+        /// List the files that aren't formatted instead of rewriting them.
+        #[arg(long)]
+        check: bool,
+        paths: Vec<PathBuf>,
+    },
+    /// Print the syntax tree of each file.
+    DumpAst { paths: Vec<PathBuf> },
+    /// Print the lowered HIR of each file.
+    DumpHir { paths: Vec<PathBuf> },
+    /// Read lines from stdin and print what they parse and lower to.
+    Repl,
+}
+
+/// How `check` and `validate` print what they find.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum Format {
+    Human,
+    Json,
+}
 
-        let root = ast::Root::cast(syntax).unwrap();
+/// What `export` writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ExportFormat {
+    Json,
+    Yaml,
+    Toml,
+}
 
-        // dbg!(root
-        //     .stmts()
-        //     .filter_map(|stmt| if let ast::Stmt::Record(var_def) = stmt {
-        //         return Some(var_def::cast)
-        //         Some(var_def.value())
-        //     } else {
-        //         None
-        //     })
-        //     .collect::<Vec<_>>());
+/// How `export` writes enum members.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub(crate) enum ExportEnums {
+    Names,
+    Values,
+}
 
-        dbg!(hir::lower(root));
+/// What a subcommand found, which decides the exit code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Outcome {
+    Ok,
+    Failed,
+}
+
+fn main() -> ExitCode {
+    let cli = Cli::parse();
+
+    let result = match cli.command {
+        Command::Check { format, seed, paths } => load(&paths).map(|ws| check::check(&ws, format, seed)),
+        Command::Eval { seed, paths } => load(&paths).map(|ws| check::eval(&ws, seed)),
+        Command::Export { format, enums, seed, provenance, paths } => load(&paths)
+            .and_then(|ws| export::export(&ws, format, enums, seed, provenance.as_deref())),
+        Command::Import { files } => import::import(&files),
+        Command::Validate { schema, name, format, seed, paths } => {
+            validate::validate(&schema, &name, &paths, format, seed)
+        }
+        Command::Fmt { check, paths } => load(&paths).and_then(|ws| fmt::fmt(&ws, check)),
+        Command::DumpAst { paths } => load(&paths).map(|ws| dump(&ws, |text| parser::parse(text).debug_tree())),
+        Command::DumpHir { paths } => load(&paths).map(|ws| {
+            dump(&ws, |text| {
+                let root = ast::Root::cast(parser::parse(text).syntax()).unwrap();
+                let (_, stmts) = hir::lower(root);
+                format!("{:#?}", stmts)
+            })
+        }),
+        Command::Repl => repl::run().map(|_| Outcome::Ok).map_err(|error| WorkspaceError::Io {
+            path: PathBuf::from("<stdin>"),
+            error,
+        }),
+    };
+
+    match result {
+        Ok(Outcome::Ok) => ExitCode::SUCCESS,
+        Ok(Outcome::Failed) => ExitCode::from(1),
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::from(2)
+        }
+    }
+}
+
+/// Loads every path, each directory as its own workspace and every lone
+/// file as a workspace of one.
+fn load(paths: &[PathBuf]) -> Result<Vec<Workspace>, WorkspaceError> {
+    let mut workspaces = Vec::new();
+    for path in paths {
+        if path.is_dir() {
+            workspaces.push(Workspace::load(path)?);
+        } else {
+            let text = fs::read_to_string(path).map_err(|error| WorkspaceError::Io {
+                path: path.clone(),
+                error,
+            })?;
+            workspaces.push(Workspace::from_sources([(path.clone(), text)]));
+        }
+    }
+    Ok(workspaces)
+}
 
-        input.clear();
+fn dump(workspaces: &[Workspace], dump: impl Fn(&str) -> String) -> Outcome {
+    for (_, module) in workspaces.iter().flat_map(Workspace::modules) {
+        println!("// {}", module.file.display());
+        println!("{}", dump(&module.text));
     }
-}
\ No newline at end of file
+    Outcome::Ok
+}
@@ -0,0 +1,34 @@
+use parser::parse;
+use std::io::{self, Write};
+
+pub(crate) fn run() -> io::Result<()> {
+    let stdin = io::stdin();
+    let mut stdout = io::stdout();
+
+    let mut input = String::new();
+
+    loop {
+        write!(stdout, "→ ")?;
+        stdout.flush()?;
+
+        if stdin.read_line(&mut input)? == 0 {
+            return Ok(());
+        }
+
+        let parse = parse(&input);
+        println!("{}", parse.debug_tree());
+
+        let syntax = parse.syntax();
+
+        for error in ast::validation::validate(&syntax) {
+            println!("{}", error);
+        }
+
+        let root = ast::Root::cast(syntax).unwrap();
+
+        let (_, stmts) = hir::lower(root);
+        println!("{:#?}", stmts);
+
+        input.clear();
+    }
+}
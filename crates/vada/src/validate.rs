@@ -0,0 +1,166 @@
+use crate::{check, load, Format, Outcome};
+use diagnostics::Diagnostic;
+use eval::{Step, Value, Violation};
+use hir::{HirRecord, HirStmt};
+use std::fs;
+use std::path::{Path, PathBuf};
+use text_size::TextRange;
+use workspace::{Module, ModuleId, Workspace, WorkspaceError};
+
+/// Checks every record in the data files against the schema `$name` from
+/// `schema`, reporting every violation in each. `schema` can be a file or a
+/// directory, where the first module declaring `$name` is used. Data files
+/// can be Vada, or JSON and YAML, which are imported first. Random literals
+/// in Vada files are rolled with `seed`, as `check` and `export` roll them.
+pub(crate) fn validate(
+    schema: &Path,
+    name: &str,
+    paths: &[PathBuf],
+    format: Format,
+    seed: u64,
+) -> Result<Outcome, WorkspaceError> {
+    let mut outcome = Outcome::Ok;
+    let mut report = |file: &str, text: Option<&str>, diagnostic: Diagnostic| {
+        check::print(&diagnostic, file, text, format);
+        outcome = Outcome::Failed;
+    };
+
+    let name = name.trim_start_matches('$');
+    let schemas = load(&[schema.to_path_buf()])?;
+    let modules = check::modules(&schemas).collect::<Vec<(&Workspace, ModuleId, &Module)>>();
+    let schema_file = schema.display().to_string();
+
+    if modules.is_empty() {
+        report(&schema_file, None, Diagnostic::error(format!("there are no Vada files in {}", schema_file)));
+        return Ok(outcome);
+    }
+
+    let mut broken = false;
+    for &(ws, id, module) in &modules {
+        for diagnostic in check::problems(ws, id, seed) {
+            report(&module.file.display().to_string(), Some(&module.text), diagnostic);
+            broken = true;
+        }
+    }
+    if broken {
+        return Ok(outcome);
+    }
+    let schema = match modules.iter().find_map(|(_, _, module)| eval::schema(&module.db, &module.stmts, name)) {
+        Some(schema) => schema,
+        None => {
+            let error = Diagnostic::error(format!("there's no schema `${}` in {}", name, schema_file));
+            report(&schema_file, None, error);
+            return Ok(outcome);
+        }
+    };
+
+    for path in paths {
+        if let Some(data_format) = import::Format::of(path) {
+            let text = fs::read_to_string(path).map_err(|error| WorkspaceError::Io {
+                path: path.clone(),
+                error,
+            })?;
+            let file = path.display().to_string();
+
+            match import::import(&text, data_format) {
+                Ok(imported) => {
+                    for violation in violations(&schema, &imported.value) {
+                        report(&file, Some(&text), diagnostic(&violation, imported.locate(violation.path())));
+                    }
+                }
+                Err(error) => report(&file, Some(&text), Diagnostic::from(&error)),
+            }
+            continue;
+        }
+
+        let workspaces = load(std::slice::from_ref(path))?;
+        for (ws, id, module) in check::modules(&workspaces) {
+            let file = module.file.display().to_string();
+            let problems = check::problems(ws, id, seed);
+            if !problems.is_empty() {
+                for diagnostic in problems {
+                    report(&file, Some(&module.text), diagnostic);
+                }
+                continue;
+            }
+
+            let imports = check::imports(ws, id, seed);
+            let data = eval::output_seeded(&module.db, &module.stmts, seed, &imports).0.resolved();
+            for violation in violations(&schema, &data) {
+                report(&file, Some(&module.text), diagnostic(&violation, locate(module, violation.path())));
+            }
+        }
+    }
+
+    Ok(outcome)
+}
+
+/// Checks each record of a file on its own: the fields of a struct, or the
+/// items of a list.
+fn violations(schema: &Value, data: &Value) -> Vec<Violation> {
+    match data {
+        Value::Struct(records) => records
+            .iter()
+            .flat_map(|(name, record)| eval::validate(schema, record, &[Step::Field(name.clone())]))
+            .collect(),
+        Value::List(records) => records
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, record)| eval::validate(schema, record, &[Step::Index(idx)]))
+            .collect(),
+        _ => eval::validate(schema, data, &[]),
+    }
+}
+
+fn diagnostic(violation: &Violation, range: Option<TextRange>) -> Diagnostic {
+    let diagnostic = Diagnostic::error(violation.to_string());
+    let label = match violation {
+        Violation::Missing { .. } => "missing from this",
+        Violation::Unknown { .. } => "not in the schema",
+        Violation::Mismatch { .. } => "wrong kind of value",
+        Violation::Constraint { .. } => "not allowed by the schema",
+    };
+
+    match range {
+        Some(range) => diagnostic.with_primary(range, label),
+        None => diagnostic,
+    }
+}
+
+/// The record a path leads to, or the closest one it gets to. Paths stop at
+/// list indices, since list items aren't records.
+fn locate(module: &Module, path: &[Step]) -> Option<TextRange> {
+    let fields = path.iter().map_while(|step| match step {
+        Step::Field(name) => Some(name),
+        Step::Index(_) => None,
+    });
+
+    let mut records = module
+        .stmts
+        .iter()
+        .filter_map(|stmt| match stmt {
+            HirStmt::Record(HirRecord::Section { value, .. }) => Some(hir::visible(value)),
+            HirStmt::Record(record) => Some(vec![record]),
+            _ => None,
+        })
+        .flatten()
+        .collect::<Vec<&HirRecord>>();
+    let mut range = None;
+
+    for name in fields {
+        let record = records.iter().find(|record| match record {
+            HirRecord::Mono { name: Some(n), .. } | HirRecord::Poly { name: Some(n), .. } => n == name,
+            _ => false,
+        });
+        let (id, value) = match record {
+            Some(HirRecord::Mono { id, .. }) => (id, None),
+            Some(HirRecord::Poly { id, value, .. }) => (id, Some(value)),
+            _ => break,
+        };
+
+        range = module.db.source_map().record(*id).map(|ptr| ptr.range());
+        records = value.map_or(Vec::new(), |value| hir::visible(value));
+    }
+
+    range
+}
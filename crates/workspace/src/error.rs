@@ -0,0 +1,94 @@
+use crate::ModulePath;
+use smol_str::SmolStr;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+#[derive(Debug)]
+pub enum WorkspaceError {
+    Io {
+        path: PathBuf,
+        error: io::Error,
+    },
+    /// An `@` root that isn't `@global`, `@local`, `@parent` or `@siblings`.
+    UnknownRoot {
+        module: ModulePath,
+        root: SmolStr,
+    },
+    /// `@global`, or `@parent` at the top of the workspace, has no parent.
+    NoParent {
+        module: ModulePath,
+    },
+    NotFound {
+        module: ModulePath,
+        source: Vec<SmolStr>,
+    },
+    /// Modules that import each other, with the first repeated at the end.
+    Cycle(Vec<ModulePath>),
+}
+
+impl fmt::Display for WorkspaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, error } => write!(f, "couldn't read {}: {}", path.display(), error),
+            Self::UnknownRoot { module, root } => {
+                write!(f, "{}: unknown accessor root `@{}`", module, root)
+            }
+            Self::NoParent { module } => {
+                write!(f, "{}: `@parent` goes above the workspace root", module)
+            }
+            Self::NotFound { module, source } => {
+                write!(f, "{}: nothing found at `{}`", module, display_source(source))
+            }
+            Self::Cycle(modules) => {
+                write!(f, "import cycle: ")?;
+                for (idx, module) in modules.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, " -> ")?;
+                    }
+                    write!(f, "{}", module)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for WorkspaceError {}
+
+/// Puts accessor tokens back together, so `@`, `global`, `zalgo` reads as
+/// `@global::zalgo`.
+fn display_source(source: &[SmolStr]) -> String {
+    let mut out = String::new();
+    for (idx, segment) in source.iter().enumerate() {
+        if idx > 0 && source[idx - 1] != "@" {
+            out.push_str("::");
+        }
+        out.push_str(segment);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn display_not_found() {
+        let error = WorkspaceError::NotFound {
+            module: ModulePath::new(vec!["units".into()]),
+            source: vec!["@".into(), "global".into(), "zalgo".into()],
+        };
+
+        assert_eq!(error.to_string(), "units: nothing found at `@global::zalgo`");
+    }
+
+    #[test]
+    fn display_cycle() {
+        let a = ModulePath::new(vec!["a".into()]);
+        let b = ModulePath::new(vec!["b".into()]);
+        let error = WorkspaceError::Cycle(vec![a.clone(), b, a]);
+
+        assert_eq!(error.to_string(), "import cycle: a -> b -> a");
+    }
+}
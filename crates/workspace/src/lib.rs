@@ -0,0 +1,400 @@
+/*
+TL;DR: Every `.vada` file under a root directory is a module, named by its
+path from the root.
+
+    root/
+    ├─ zalgo.vada          zalgo
+    └─ units/
+       ├─ goblin.vada      units::goblin
+       └─ tools/
+          └─ axe.vada      units::tools::axe
+
+Accessors like `#tools: @parent::@siblings` are resolved against that tree,
+and the imports between modules form a graph that must not have cycles.
+*/
+
+mod error;
+mod path;
+mod resolve;
+pub use error::WorkspaceError;
+pub use path::ModulePath;
+pub use resolve::Import;
+
+use hir::{Database, HirRecord, HirStmt};
+use la_arena::{Arena, Idx};
+use parser::Parse;
+use smol_str::SmolStr;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+pub type ModuleId = Idx<Module>;
+
+/// A single lowered `.vada` file.
+#[derive(Debug)]
+pub struct Module {
+    pub path: ModulePath,
+    pub file: PathBuf,
+    pub text: String,
+    pub parse: Parse,
+    pub db: Database,
+    pub stmts: Vec<HirStmt>,
+}
+
+#[derive(Debug, Default)]
+pub struct Workspace {
+    root: PathBuf,
+    modules: Arena<Module>,
+}
+
+impl Workspace {
+    /// Discovers, parses and lowers every `.vada` file under `root`.
+    pub fn load(root: impl AsRef<Path>) -> Result<Self, WorkspaceError> {
+        Self::load_with(root, &HashMap::new())
+    }
+
+    /// Like `load`, but files in `open` are taken from memory instead of disk,
+    /// the way an editor has them. Open files under `root` that haven't been
+    /// saved yet are included too.
+    pub fn load_with(
+        root: impl AsRef<Path>,
+        open: &HashMap<PathBuf, String>,
+    ) -> Result<Self, WorkspaceError> {
+        let root = root.as_ref();
+        let mut files = Vec::new();
+        discover(root, &mut files)?;
+        files.extend(
+            open.keys()
+                .filter(|file| file.starts_with(root) && !files.contains(file))
+                .cloned()
+                .collect::<Vec<PathBuf>>(),
+        );
+        files.sort();
+
+        let mut sources = Vec::new();
+        for file in files {
+            let text = match open.get(&file) {
+                Some(text) => text.clone(),
+                None => fs::read_to_string(&file).map_err(|error| WorkspaceError::Io {
+                    path: file.clone(),
+                    error,
+                })?,
+            };
+            let relative = file.strip_prefix(root).unwrap_or(&file).to_path_buf();
+            sources.push((relative, text));
+        }
+
+        let mut workspace = Self::from_sources(sources);
+        workspace.root = root.to_path_buf();
+        for (_, module) in workspace.modules.iter_mut() {
+            module.file = root.join(&module.file);
+        }
+
+        Ok(workspace)
+    }
+
+    /// Builds a workspace from files that are already in memory, keyed by their
+    /// path relative to the root.
+    pub fn from_sources(sources: impl IntoIterator<Item = (PathBuf, String)>) -> Self {
+        let mut modules = Arena::new();
+        for (file, text) in sources {
+            let parse = parser::parse(&text);
+            let root = ast::Root::cast(parse.syntax()).unwrap();
+            let (db, stmts) = hir::lower(root);
+
+            modules.alloc(Module {
+                path: ModulePath::from_file(&file),
+                file,
+                text,
+                parse,
+                db,
+                stmts,
+            });
+        }
+
+        Self {
+            root: PathBuf::new(),
+            modules,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn module(&self, id: ModuleId) -> &Module {
+        &self.modules[id]
+    }
+
+    pub fn modules(&self) -> impl Iterator<Item = (ModuleId, &Module)> {
+        self.modules.iter()
+    }
+
+    pub fn find(&self, path: &ModulePath) -> Option<ModuleId> {
+        self.modules
+            .iter()
+            .find(|(_, m)| m.path == *path)
+            .map(|(id, _)| id)
+    }
+
+    /// Every accessor in a module, by name, with what it resolved to.
+    pub fn imports(&self, id: ModuleId) -> Vec<(SmolStr, Result<Import, WorkspaceError>)> {
+        self.modules[id]
+            .stmts
+            .iter()
+            .filter_map(|stmt| match stmt {
+                HirStmt::Accessor { name, source } => Some((name.clone(), self.resolve(id, source))),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Resolves every accessor in the workspace, reporting the ones that lead
+    /// nowhere and any cycles between the ones that do.
+    pub fn check(&self) -> Vec<WorkspaceError> {
+        let mut errors = Vec::new();
+        let mut edges: HashMap<ModuleId, Vec<ModuleId>> = HashMap::new();
+
+        for (id, _) in self.modules.iter() {
+            for (_, import) in self.imports(id) {
+                match import {
+                    Ok(import) => edges.entry(id).or_default().extend(import.modules),
+                    Err(error) => errors.push(error),
+                }
+            }
+        }
+
+        for cycle in find_cycles(&self.modules, &edges) {
+            let paths = cycle.into_iter().map(|id| self.modules[id].path.clone()).collect();
+            errors.push(WorkspaceError::Cycle(paths));
+        }
+
+        errors
+    }
+
+    /// The records an import points at, following its fields down through
+    /// nested records.
+    pub fn records(&self, import: &Import) -> Vec<&HirRecord> {
+        import
+            .modules
+            .iter()
+            .flat_map(|id| {
+                let top = self.modules[*id]
+                    .stmts
+                    .iter()
+                    .filter_map(|stmt| match stmt {
+                        HirStmt::Record(HirRecord::Section { value, .. }) => Some(hir::visible(value)),
+                        HirStmt::Record(record) => Some(vec![record]),
+                        _ => None,
+                    })
+                    .flatten()
+                    .collect::<Vec<&HirRecord>>();
+
+                descend(top, &import.fields)
+            })
+            .collect()
+    }
+}
+
+fn descend<'a>(records: Vec<&'a HirRecord>, fields: &[SmolStr]) -> Vec<&'a HirRecord> {
+    let (field, rest) = match fields.split_first() {
+        Some(split) => split,
+        None => return records,
+    };
+
+    let found = records.into_iter().find(|record| match record {
+        HirRecord::Mono { name, .. } | HirRecord::Poly { name, .. } => name.as_ref() == Some(field),
+        HirRecord::Missing | HirRecord::Section { .. } => false,
+    });
+
+    match found {
+        Some(record) if rest.is_empty() => vec![record],
+        Some(HirRecord::Poly { value, .. }) => descend(hir::visible(value), rest),
+        _ => Vec::new(),
+    }
+}
+
+fn discover(dir: &Path, files: &mut Vec<PathBuf>) -> Result<(), WorkspaceError> {
+    let io_error = |error| WorkspaceError::Io {
+        path: dir.to_path_buf(),
+        error,
+    };
+
+    for entry in fs::read_dir(dir).map_err(io_error)? {
+        let path = entry.map_err(io_error)?.path();
+        let hidden = path
+            .file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with('.'));
+
+        if hidden {
+            continue;
+        } else if path.is_dir() {
+            discover(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "vada") {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Depth-first search for back edges. Each cycle is reported once, starting
+/// and ending at the module it was entered through.
+fn find_cycles(
+    modules: &Arena<Module>,
+    edges: &HashMap<ModuleId, Vec<ModuleId>>,
+) -> Vec<Vec<ModuleId>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum State {
+        Unvisited,
+        Active,
+        Done,
+    }
+
+    fn visit(
+        id: ModuleId,
+        edges: &HashMap<ModuleId, Vec<ModuleId>>,
+        states: &mut HashMap<ModuleId, State>,
+        stack: &mut Vec<ModuleId>,
+        cycles: &mut Vec<Vec<ModuleId>>,
+    ) {
+        states.insert(id, State::Active);
+        stack.push(id);
+
+        for &next in edges.get(&id).into_iter().flatten() {
+            match states.get(&next).copied().unwrap_or(State::Unvisited) {
+                State::Unvisited => visit(next, edges, states, stack, cycles),
+                State::Active => {
+                    let start = stack.iter().position(|m| *m == next).unwrap();
+                    let mut cycle = stack[start..].to_vec();
+                    cycle.push(next);
+                    cycles.push(cycle);
+                }
+                State::Done => {}
+            }
+        }
+
+        stack.pop();
+        states.insert(id, State::Done);
+    }
+
+    let mut states = HashMap::new();
+    let mut cycles = Vec::new();
+    for (id, _) in modules.iter() {
+        if !states.contains_key(&id) {
+            visit(id, edges, &mut states, &mut Vec::new(), &mut cycles);
+        }
+    }
+
+    cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(files: &[(&str, &str)]) -> Workspace {
+        Workspace::from_sources(
+            files
+                .iter()
+                .map(|(file, text)| (PathBuf::from(file), text.to_string())),
+        )
+    }
+
+    #[test]
+    fn modules_are_named_by_path() {
+        let ws = workspace(&[("units/goblin.vada", "hp: 5"), ("zalgo.vada", "a: 1")]);
+        let paths = ws.modules().map(|(_, m)| m.path.to_string()).collect::<Vec<String>>();
+
+        assert_eq!(paths, vec!["units::goblin", "zalgo"]);
+    }
+
+    #[test]
+    fn imports_resolve_to_records() {
+        let ws = workspace(&[
+            ("zalgo.vada", "tools: {\n  hammer: 3\n  axe: 4\n}"),
+            ("units.vada", "#hammer: @global::zalgo::tools::hammer"),
+        ]);
+        let units = ws.find(&ModulePath::new(vec!["units".into()])).unwrap();
+
+        let imports = ws.imports(units);
+        assert_eq!(imports.len(), 1);
+
+        let (name, import) = &imports[0];
+        assert_eq!(name, "hammer");
+
+        let records = ws.records(import.as_ref().unwrap());
+        assert_eq!(records.len(), 1);
+        assert!(matches!(
+            records[0],
+            HirRecord::Mono { name: Some(name), .. } if name == "hammer"
+        ));
+    }
+
+    #[test]
+    fn check_reports_cycles() {
+        let ws = workspace(&[
+            ("a.vada", "#b: @local::b"),
+            ("b.vada", "#c: @local::c"),
+            ("c.vada", "#a: @local::a"),
+            ("d.vada", "#a: @local::a"),
+        ]);
+
+        let errors = ws.check().iter().map(ToString::to_string).collect::<Vec<String>>();
+        assert_eq!(errors, vec!["import cycle: a -> b -> c -> a"]);
+    }
+
+    #[test]
+    fn check_reports_unresolved_accessors() {
+        let ws = workspace(&[("a.vada", "#b: @local::b")]);
+
+        let errors = ws.check().iter().map(ToString::to_string).collect::<Vec<String>>();
+        assert_eq!(errors, vec!["a: nothing found at `@local::b`"]);
+    }
+
+    #[test]
+    fn check_reports_missing_records() {
+        let ws = workspace(&[
+            ("zalgo.vada", "tools: {\n  hammer: 3\n}"),
+            ("units.vada", "#h: @global::zalgo::tools::hamer"),
+        ]);
+
+        let errors = ws.check().iter().map(ToString::to_string).collect::<Vec<String>>();
+        assert_eq!(errors, vec!["units: nothing found at `@global::zalgo::tools::hamer`"]);
+    }
+
+    #[test]
+    fn load_from_disk() {
+        let root = std::env::temp_dir().join(format!("vada-workspace-{}", std::process::id()));
+        fs::create_dir_all(root.join("units/.hidden")).unwrap();
+        fs::write(root.join("zalgo.vada"), "a: 1").unwrap();
+        fs::write(root.join("units/goblin.vada"), "#z: @global::zalgo").unwrap();
+        fs::write(root.join("units/notes.txt"), "not vada").unwrap();
+        fs::write(root.join("units/.hidden/skip.vada"), "a: 1").unwrap();
+
+        let ws = Workspace::load(&root).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        let paths = ws.modules().map(|(_, m)| m.path.to_string()).collect::<Vec<String>>();
+        assert_eq!(paths, vec!["units::goblin", "zalgo"]);
+        assert_eq!(ws.module(ws.find(&ModulePath::new(vec!["zalgo".into()])).unwrap()).file, root.join("zalgo.vada"));
+        assert!(ws.check().is_empty());
+    }
+
+    #[test]
+    fn load_with_open_files() {
+        let root = std::env::temp_dir().join(format!("vada-workspace-open-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("zalgo.vada"), "a: 1").unwrap();
+
+        let open = HashMap::from([
+            (root.join("zalgo.vada"), "a: 2".to_string()),
+            (root.join("unsaved.vada"), "b: 3".to_string()),
+        ]);
+        let ws = Workspace::load_with(&root, &open).unwrap();
+        fs::remove_dir_all(&root).unwrap();
+
+        let texts = ws.modules().map(|(_, m)| m.text.as_str()).collect::<Vec<&str>>();
+        assert_eq!(texts, vec!["b: 3", "a: 2"]);
+    }
+}
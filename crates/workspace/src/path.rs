@@ -0,0 +1,79 @@
+use smol_str::SmolStr;
+use std::fmt;
+use std::path::{Component, Path};
+
+/// Where a module sits in the workspace, like `zalgo::tools` for
+/// `zalgo/tools.vada`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct ModulePath(Vec<SmolStr>);
+
+impl ModulePath {
+    pub fn new(segments: Vec<SmolStr>) -> Self {
+        Self(segments)
+    }
+
+    /// Builds the module path for a file, relative to the workspace root.
+    pub fn from_file(relative: &Path) -> Self {
+        let mut segments = relative
+            .parent()
+            .into_iter()
+            .flat_map(Path::components)
+            .filter_map(|c| match c {
+                Component::Normal(s) => Some(SmolStr::from(s.to_string_lossy().as_ref())),
+                _ => None,
+            })
+            .collect::<Vec<SmolStr>>();
+
+        if let Some(stem) = relative.file_stem() {
+            segments.push(stem.to_string_lossy().as_ref().into());
+        }
+
+        Self(segments)
+    }
+
+    pub fn segments(&self) -> &[SmolStr] {
+        &self.0
+    }
+
+    /// The directory the module lives in.
+    pub fn parent(&self) -> Option<ModulePath> {
+        let (_, parent) = self.0.split_last()?;
+        Some(Self(parent.to_vec()))
+    }
+
+    pub fn join(&self, segment: impl Into<SmolStr>) -> ModulePath {
+        let mut segments = self.0.clone();
+        segments.push(segment.into());
+        Self(segments)
+    }
+
+    pub fn starts_with(&self, prefix: &ModulePath) -> bool {
+        self.0.starts_with(&prefix.0)
+    }
+}
+
+impl fmt::Display for ModulePath {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "@global");
+        }
+        write!(f, "{}", self.0.join("::"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn module_path_from_file() {
+        let path = ModulePath::from_file(Path::new("zalgo/tools.vada"));
+        assert_eq!(path, ModulePath::new(vec!["zalgo".into(), "tools".into()]));
+        assert_eq!(path.to_string(), "zalgo::tools");
+    }
+
+    #[test]
+    fn root_displays_as_global() {
+        assert_eq!(ModulePath::default().to_string(), "@global");
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{ModuleId, ModulePath, Workspace, WorkspaceError};
+use smol_str::SmolStr;
+
+/// What an accessor points at: a set of modules, and the path to follow
+/// through their records.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Import {
+    pub modules: Vec<ModuleId>,
+    pub fields: Vec<SmolStr>,
+}
+
+enum Segment<'a> {
+    /// `@global`, `@local`, ...
+    Root(&'a str),
+    Name(&'a SmolStr),
+}
+
+/// Accessor sources come in as tokens, with roots split into `@` and a name.
+fn segments(source: &[SmolStr]) -> Vec<Segment<'_>> {
+    let mut out = Vec::new();
+    let mut tokens = source.iter();
+    while let Some(token) = tokens.next() {
+        if token == "@" {
+            if let Some(root) = tokens.next() {
+                out.push(Segment::Root(root));
+            }
+        } else {
+            out.push(Segment::Name(token));
+        }
+    }
+    out
+}
+
+impl Workspace {
+    /// Resolves an accessor's source, as written in `from`.
+    ///
+    /// Paths start out in the importing module's directory. `@global` jumps to
+    /// the workspace root, `@local` back to the module's own directory,
+    /// `@parent` up a directory, and `@siblings` fans out to everything next to
+    /// where the path currently is. Names step into directories and modules, and
+    /// once a module has been reached, into its records, which have to be there.
+    pub fn resolve(&self, from: ModuleId, source: &[SmolStr]) -> Result<Import, WorkspaceError> {
+        let module = &self.modules[from].path;
+        let not_found = || WorkspaceError::NotFound {
+            module: module.clone(),
+            source: source.to_vec(),
+        };
+
+        let mut cursor = vec![module.parent().unwrap_or_default()];
+        let mut fields = Vec::new();
+
+        for (idx, segment) in segments(source).into_iter().enumerate() {
+            match segment {
+                _ if !fields.is_empty() => match segment {
+                    Segment::Name(name) => fields.push(name.clone()),
+                    Segment::Root(_) => return Err(not_found()),
+                },
+                Segment::Root("global") => cursor = vec![ModulePath::default()],
+                Segment::Root("local") => cursor = vec![module.parent().unwrap_or_default()],
+                Segment::Root("parent") => {
+                    cursor = cursor
+                        .iter()
+                        .map(|c| c.parent().ok_or_else(|| WorkspaceError::NoParent {
+                            module: module.clone(),
+                        }))
+                        .collect::<Result<_, _>>()?;
+                }
+                Segment::Root("siblings") => {
+                    // On its own, `@siblings` means the module's own siblings.
+                    let base = if idx == 0 { vec![module.clone()] } else { cursor };
+                    cursor = base.iter().flat_map(|c| self.siblings(c)).collect();
+                }
+                Segment::Root(root) => {
+                    return Err(WorkspaceError::UnknownRoot {
+                        module: module.clone(),
+                        root: root.into(),
+                    })
+                }
+                Segment::Name(name) => {
+                    let next = cursor
+                        .iter()
+                        .map(|c| c.join(name.clone()))
+                        .filter(|path| self.contains(path))
+                        .collect::<Vec<ModulePath>>();
+
+                    if !next.is_empty() {
+                        cursor = next;
+                    } else if cursor.len() == 1 && self.find(&cursor[0]).is_some() {
+                        fields.push(name.clone());
+                    } else {
+                        return Err(not_found());
+                    }
+                }
+            }
+        }
+
+        let modules = self
+            .modules
+            .iter()
+            .filter(|(id, m)| {
+                cursor.iter().any(|c| m.path == *c || (m.path.starts_with(c) && *id != from))
+            })
+            .map(|(id, _)| id)
+            .collect::<Vec<ModuleId>>();
+
+        if modules.is_empty() {
+            return Err(not_found());
+        }
+
+        // Names past the module have to be records in it.
+        let import = Import { modules, fields };
+        if !import.fields.is_empty() && self.records(&import).is_empty() {
+            return Err(not_found());
+        }
+
+        Ok(import)
+    }
+
+    /// Whether `path` is a module, or a directory with modules in it.
+    fn contains(&self, path: &ModulePath) -> bool {
+        self.modules.iter().any(|(_, m)| m.path.starts_with(path))
+    }
+
+    /// The modules and directories that share a directory with `path`.
+    fn siblings(&self, path: &ModulePath) -> Vec<ModulePath> {
+        let parent = match path.parent() {
+            Some(parent) => parent,
+            None => return Vec::new(),
+        };
+        let depth = parent.segments().len();
+
+        let mut siblings = self
+            .modules
+            .iter()
+            .filter(|(_, m)| m.path.starts_with(&parent) && m.path.segments().len() > depth)
+            .map(|(_, m)| parent.join(m.path.segments()[depth].clone()))
+            .filter(|sibling| sibling != path)
+            .collect::<Vec<ModulePath>>();
+
+        siblings.sort();
+        siblings.dedup();
+        siblings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn workspace() -> Workspace {
+        Workspace::from_sources(
+            [
+                ("zalgo.vada", "tools: {\n  hammer: 3\n}"),
+                ("units/goblin.vada", "a: 1"),
+                ("units/orc.vada", "a: 1"),
+                ("units/tools/axe.vada", "a: 1"),
+                ("factions/major.vada", "a: 1"),
+            ]
+            .iter()
+            .map(|(file, text)| (PathBuf::from(file), text.to_string())),
+        )
+    }
+
+    fn source(text: &str) -> Vec<SmolStr> {
+        text.split("::")
+            .flat_map(|segment| match segment.strip_prefix('@') {
+                Some(root) => vec!["@".into(), root.into()],
+                None => vec![segment.into()],
+            })
+            .collect()
+    }
+
+    fn check(from: &str, text: &str, modules: &[&str], fields: &[&str]) {
+        let ws = workspace();
+        let from = ws.find_str(from).unwrap();
+        let import = ws.resolve(from, &source(text)).unwrap();
+
+        let found = import
+            .modules
+            .iter()
+            .map(|id| ws.module(*id).path.to_string())
+            .collect::<Vec<String>>();
+
+        assert_eq!(found, modules);
+        assert_eq!(import.fields, fields.iter().map(|f| SmolStr::from(*f)).collect::<Vec<_>>());
+    }
+
+    impl Workspace {
+        fn find_str(&self, path: &str) -> Option<ModuleId> {
+            self.find(&ModulePath::new(path.split("::").map(Into::into).collect()))
+        }
+    }
+
+    #[test]
+    fn resolve_global() {
+        check("units::goblin", "@global::zalgo", &["zalgo"], &[]);
+    }
+
+    #[test]
+    fn resolve_local() {
+        check("units::goblin", "@local::orc", &["units::orc"], &[]);
+    }
+
+    #[test]
+    fn resolve_without_root_is_local() {
+        check("units::goblin", "tools::axe", &["units::tools::axe"], &[]);
+    }
+
+    #[test]
+    fn resolve_into_records() {
+        check("units::goblin", "@global::zalgo::tools::hammer", &["zalgo"], &["tools", "hammer"]);
+    }
+
+    #[test]
+    fn resolve_directory() {
+        check("zalgo", "@global::units", &["units::goblin", "units::orc", "units::tools::axe"], &[]);
+    }
+
+    #[test]
+    fn resolve_parent() {
+        check("units::tools::axe", "@parent::goblin", &["units::goblin"], &[]);
+    }
+
+    #[test]
+    fn resolve_siblings() {
+        check("units::goblin", "@siblings", &["units::orc", "units::tools::axe"], &[]);
+    }
+
+    #[test]
+    fn resolve_parent_siblings() {
+        check("units::tools::axe", "@parent::@siblings", &["zalgo", "factions::major"], &[]);
+    }
+
+    #[test]
+    fn resolve_missing() {
+        let ws = workspace();
+        let from = ws.find_str("zalgo").unwrap();
+        let error = ws.resolve(from, &source("@global::nope")).unwrap_err();
+
+        assert_eq!(error.to_string(), "zalgo: nothing found at `@global::nope`");
+    }
+
+    #[test]
+    fn resolve_missing_record() {
+        let ws = workspace();
+        let from = ws.find_str("units::goblin").unwrap();
+        let error = ws.resolve(from, &source("@global::zalgo::tools::hamer")).unwrap_err();
+
+        assert_eq!(error.to_string(), "units::goblin: nothing found at `@global::zalgo::tools::hamer`");
+    }
+
+    #[test]
+    fn resolve_unknown_root() {
+        let ws = workspace();
+        let from = ws.find_str("zalgo").unwrap();
+        let error = ws.resolve(from, &source("@arc::coretypes")).unwrap_err();
+
+        assert_eq!(error.to_string(), "zalgo: unknown accessor root `@arc`");
+    }
+
+    #[test]
+    fn resolve_parent_above_root() {
+        let ws = workspace();
+        let from = ws.find_str("zalgo").unwrap();
+        let error = ws.resolve(from, &source("@parent::zalgo")).unwrap_err();
+
+        assert_eq!(error.to_string(), "zalgo: `@parent` goes above the workspace root");
+    }
+}